@@ -26,6 +26,20 @@ SET row_security = off;
 -- *not* creating schema, since initdb creates it
 
 
+--
+-- Name: activity_type; Type: TYPE; Schema: public; Owner: -
+--
+
+CREATE TYPE public.activity_type AS ENUM (
+    'competing',
+    'custom',
+    'listening',
+    'playing',
+    'streaming',
+    'watching'
+);
+
+
 --
 -- Name: online_status; Type: TYPE; Schema: public; Owner: -
 --
@@ -46,7 +60,15 @@ CREATE TYPE public.online_status AS ENUM (
 CREATE TYPE public.shipment_carrier AS ENUM (
     'fedex',
     'ups',
-    'usps'
+    'usps',
+    'australia_post',
+    'canada_post',
+    'dhl_ecommerce',
+    'dhl_express',
+    'lasership',
+    'ontrac',
+    'purolator',
+    'royal_mail'
 );
 
 
@@ -150,26 +172,47 @@ ALTER SEQUENCE public.command_id_seq OWNED BY public.command.id;
 
 
 --
--- Name: message; Type: TABLE; Schema: public; Owner: -
+-- Name: digest_config; Type: TABLE; Schema: public; Owner: -
 --
 
-CREATE TABLE public.message (
-    id bigint NOT NULL,
+CREATE TABLE public.digest_config (
+    guild_id bigint NOT NULL,
+    channel_id bigint NOT NULL,
+    last_post_date timestamp with time zone
+);
+
+
+--
+-- Name: game_alias; Type: TABLE; Schema: public; Owner: -
+--
+
+CREATE TABLE public.game_alias (
+    alias character varying(512) NOT NULL,
+    game character varying(512) NOT NULL,
     create_date timestamp with time zone DEFAULT now() NOT NULL,
-    discord_id numeric NOT NULL,
     author_id bigint NOT NULL,
-    channel_id bigint NOT NULL,
-    guild_id bigint,
-    content text,
-    update_date timestamp with time zone DEFAULT now() NOT NULL
+    guild_id bigint NOT NULL
 );
 
 
 --
--- Name: message_id_seq; Type: SEQUENCE; Schema: public; Owner: -
+-- Name: game_session; Type: TABLE; Schema: public; Owner: -
 --
 
-CREATE SEQUENCE public.message_id_seq
+CREATE TABLE public.game_session (
+    id bigint NOT NULL,
+    user_id bigint NOT NULL,
+    game_name character varying(512) NOT NULL,
+    start_date timestamp with time zone DEFAULT now() NOT NULL,
+    end_date timestamp with time zone
+);
+
+
+--
+-- Name: game_session_id_seq; Type: SEQUENCE; Schema: public; Owner: -
+--
+
+CREATE SEQUENCE public.game_session_id_seq
     START WITH 1
     INCREMENT BY 1
     NO MINVALUE
@@ -178,34 +221,36 @@ CREATE SEQUENCE public.message_id_seq
 
 
 --
--- Name: message_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: -
+-- Name: game_session_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: -
 --
 
-ALTER SEQUENCE public.message_id_seq OWNED BY public.message.id;
+ALTER SEQUENCE public.game_session_id_seq OWNED BY public.game_session.id;
 
 
 --
--- Name: playtime_button; Type: TABLE; Schema: public; Owner: -
+-- Name: game_subscription; Type: TABLE; Schema: public; Owner: -
 --
 
-CREATE TABLE public.playtime_button (
-    id integer NOT NULL,
+CREATE TABLE public.game_subscription (
+    id bigint NOT NULL,
     create_date timestamp with time zone DEFAULT now() NOT NULL,
-    author_id bigint NOT NULL,
-    user_ids bigint[] NOT NULL,
-    username character varying(32),
-    start_date timestamp with time zone,
-    end_date timestamp with time zone NOT NULL,
-    start_offset integer NOT NULL
+    guild_id bigint NOT NULL,
+    user_id bigint NOT NULL,
+    game character varying(512) NOT NULL,
+    min_players integer DEFAULT 1 NOT NULL,
+    channel_id bigint,
+    quiet_start smallint,
+    quiet_end smallint,
+    cooldown_minutes integer DEFAULT 60 NOT NULL,
+    last_notify_date timestamp with time zone
 );
 
 
 --
--- Name: playtime_button_id_seq; Type: SEQUENCE; Schema: public; Owner: -
+-- Name: game_subscription_id_seq; Type: SEQUENCE; Schema: public; Owner: -
 --
 
-CREATE SEQUENCE public.playtime_button_id_seq
-    AS integer
+CREATE SEQUENCE public.game_subscription_id_seq
     START WITH 1
     INCREMENT BY 1
     NO MINVALUE
@@ -214,35 +259,30 @@ CREATE SEQUENCE public.playtime_button_id_seq
 
 
 --
--- Name: playtime_button_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: -
+-- Name: game_subscription_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: -
 --
 
-ALTER SEQUENCE public.playtime_button_id_seq OWNED BY public.playtime_button.id;
+ALTER SEQUENCE public.game_subscription_id_seq OWNED BY public.game_subscription.id;
 
 
 --
--- Name: shipment; Type: TABLE; Schema: public; Owner: -
+-- Name: karma_season; Type: TABLE; Schema: public; Owner: -
 --
 
-CREATE TABLE public.shipment (
-    id integer NOT NULL,
-    create_date timestamp with time zone DEFAULT now() NOT NULL,
-    update_date timestamp with time zone DEFAULT now() NOT NULL,
-    carrier public.shipment_carrier NOT NULL,
-    tracking_number character varying(100) NOT NULL,
-    author_id bigint NOT NULL,
-    channel_id bigint NOT NULL,
-    status public.shipment_tracking_status NOT NULL,
-    comment character varying(50)
+CREATE TABLE public.karma_season (
+    id bigint NOT NULL,
+    guild_id bigint NOT NULL,
+    number integer NOT NULL,
+    start_date timestamp with time zone NOT NULL,
+    end_date timestamp with time zone NOT NULL
 );
 
 
 --
--- Name: shipment_id_seq; Type: SEQUENCE; Schema: public; Owner: -
+-- Name: karma_season_id_seq; Type: SEQUENCE; Schema: public; Owner: -
 --
 
-CREATE SEQUENCE public.shipment_id_seq
-    AS integer
+CREATE SEQUENCE public.karma_season_id_seq
     START WITH 1
     INCREMENT BY 1
     NO MINVALUE
@@ -251,42 +291,44 @@ CREATE SEQUENCE public.shipment_id_seq
 
 
 --
--- Name: shipment_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: -
+-- Name: karma_season_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: -
 --
 
-ALTER SEQUENCE public.shipment_id_seq OWNED BY public.shipment.id;
+ALTER SEQUENCE public.karma_season_id_seq OWNED BY public.karma_season.id;
 
 
 --
--- Name: user_karma; Type: TABLE; Schema: public; Owner: -
+-- Name: karma_season_standing; Type: TABLE; Schema: public; Owner: -
 --
 
-CREATE TABLE public.user_karma (
-    guild_id bigint NOT NULL,
+CREATE TABLE public.karma_season_standing (
+    season_id bigint NOT NULL,
     user_id bigint NOT NULL,
     karma integer NOT NULL
 );
 
 
 --
--- Name: user_presence; Type: TABLE; Schema: public; Owner: -
+-- Name: message; Type: TABLE; Schema: public; Owner: -
 --
 
-CREATE TABLE public.user_presence (
+CREATE TABLE public.message (
     id bigint NOT NULL,
     create_date timestamp with time zone DEFAULT now() NOT NULL,
-    user_id bigint NOT NULL,
-    status public.online_status NOT NULL,
-    game_name character varying(512),
-    is_startup boolean DEFAULT false NOT NULL
+    discord_id numeric NOT NULL,
+    author_id bigint NOT NULL,
+    channel_id bigint NOT NULL,
+    guild_id bigint,
+    content text,
+    update_date timestamp with time zone DEFAULT now() NOT NULL
 );
 
 
 --
--- Name: user_presence_id_seq; Type: SEQUENCE; Schema: public; Owner: -
+-- Name: message_id_seq; Type: SEQUENCE; Schema: public; Owner: -
 --
 
-CREATE SEQUENCE public.user_presence_id_seq
+CREATE SEQUENCE public.message_id_seq
     START WITH 1
     INCREMENT BY 1
     NO MINVALUE
@@ -295,32 +337,30 @@ CREATE SEQUENCE public.user_presence_id_seq
 
 
 --
--- Name: user_presence_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: -
+-- Name: message_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: -
 --
 
-ALTER SEQUENCE public.user_presence_id_seq OWNED BY public.user_presence.id;
+ALTER SEQUENCE public.message_id_seq OWNED BY public.message.id;
 
 
 --
--- Name: vote; Type: TABLE; Schema: public; Owner: -
+-- Name: online_session; Type: TABLE; Schema: public; Owner: -
 --
 
-CREATE TABLE public.vote (
-    id integer NOT NULL,
-    create_date timestamp with time zone DEFAULT now() NOT NULL,
-    guild_id bigint NOT NULL,
-    voter_id bigint NOT NULL,
-    votee_id bigint NOT NULL,
-    is_upvote boolean NOT NULL
+CREATE TABLE public.online_session (
+    id bigint NOT NULL,
+    user_id bigint NOT NULL,
+    status public.online_status NOT NULL,
+    start_date timestamp with time zone DEFAULT now() NOT NULL,
+    end_date timestamp with time zone
 );
 
 
 --
--- Name: vote_id_seq; Type: SEQUENCE; Schema: public; Owner: -
+-- Name: online_session_id_seq; Type: SEQUENCE; Schema: public; Owner: -
 --
 
-CREATE SEQUENCE public.vote_id_seq
-    AS integer
+CREATE SEQUENCE public.online_session_id_seq
     START WITH 1
     INCREMENT BY 1
     NO MINVALUE
@@ -329,198 +369,920 @@ CREATE SEQUENCE public.vote_id_seq
 
 
 --
--- Name: vote_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: -
+-- Name: online_session_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: -
 --
 
-ALTER SEQUENCE public.vote_id_seq OWNED BY public.vote.id;
+ALTER SEQUENCE public.online_session_id_seq OWNED BY public.online_session.id;
 
 
 --
--- Name: bot_start id; Type: DEFAULT; Schema: public; Owner: -
+-- Name: paginator; Type: TABLE; Schema: public; Owner: -
 --
 
-ALTER TABLE ONLY public.bot_start ALTER COLUMN id SET DEFAULT nextval('public.bot_start_id_seq'::regclass);
+CREATE TABLE public.paginator (
+    id integer NOT NULL,
+    create_date timestamp with time zone DEFAULT now() NOT NULL,
+    expire_date timestamp with time zone NOT NULL,
+    author_id bigint NOT NULL,
+    author_only boolean NOT NULL,
+    kind character varying(32) NOT NULL,
+    payload jsonb NOT NULL,
+    page integer NOT NULL,
+    page_count integer NOT NULL
+);
 
 
 --
--- Name: command id; Type: DEFAULT; Schema: public; Owner: -
+-- Name: paginator_id_seq; Type: SEQUENCE; Schema: public; Owner: -
 --
 
-ALTER TABLE ONLY public.command ALTER COLUMN id SET DEFAULT nextval('public.command_id_seq'::regclass);
+CREATE SEQUENCE public.paginator_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
 
 
 --
--- Name: message id; Type: DEFAULT; Schema: public; Owner: -
+-- Name: paginator_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: -
 --
 
-ALTER TABLE ONLY public.message ALTER COLUMN id SET DEFAULT nextval('public.message_id_seq'::regclass);
+ALTER SEQUENCE public.paginator_id_seq OWNED BY public.paginator.id;
 
 
 --
--- Name: playtime_button id; Type: DEFAULT; Schema: public; Owner: -
+-- Name: shipment; Type: TABLE; Schema: public; Owner: -
 --
 
-ALTER TABLE ONLY public.playtime_button ALTER COLUMN id SET DEFAULT nextval('public.playtime_button_id_seq'::regclass);
+CREATE TABLE public.shipment (
+    id integer NOT NULL,
+    create_date timestamp with time zone DEFAULT now() NOT NULL,
+    update_date timestamp with time zone DEFAULT now() NOT NULL,
+    carrier public.shipment_carrier NOT NULL,
+    tracking_number character varying(100) NOT NULL,
+    author_id bigint NOT NULL,
+    channel_id bigint NOT NULL,
+    status public.shipment_tracking_status NOT NULL,
+    comment character varying(50),
+    eta timestamp with time zone,
+    substatus character varying(50),
+    webhook_date timestamp with time zone
+);
 
 
 --
--- Name: shipment id; Type: DEFAULT; Schema: public; Owner: -
+-- Name: shipment_id_seq; Type: SEQUENCE; Schema: public; Owner: -
 --
 
-ALTER TABLE ONLY public.shipment ALTER COLUMN id SET DEFAULT nextval('public.shipment_id_seq'::regclass);
+CREATE SEQUENCE public.shipment_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
 
 
 --
--- Name: user_presence id; Type: DEFAULT; Schema: public; Owner: -
+-- Name: shipment_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: -
 --
 
-ALTER TABLE ONLY public.user_presence ALTER COLUMN id SET DEFAULT nextval('public.user_presence_id_seq'::regclass);
+ALTER SEQUENCE public.shipment_id_seq OWNED BY public.shipment.id;
 
 
 --
--- Name: vote id; Type: DEFAULT; Schema: public; Owner: -
+-- Name: shipment_event; Type: TABLE; Schema: public; Owner: -
 --
 
-ALTER TABLE ONLY public.vote ALTER COLUMN id SET DEFAULT nextval('public.vote_id_seq'::regclass);
+CREATE TABLE public.shipment_event (
+    id bigint NOT NULL,
+    create_date timestamp with time zone DEFAULT now() NOT NULL,
+    shipment_id integer NOT NULL,
+    status public.shipment_tracking_status NOT NULL,
+    substatus character varying(50),
+    details text,
+    location character varying(200),
+    event_date timestamp with time zone NOT NULL
+);
 
 
 --
--- Name: bot_start bot_start_pkey; Type: CONSTRAINT; Schema: public; Owner: -
+-- Name: shipment_event_id_seq; Type: SEQUENCE; Schema: public; Owner: -
 --
 
-ALTER TABLE ONLY public.bot_start
-    ADD CONSTRAINT bot_start_pkey PRIMARY KEY (id);
+CREATE SEQUENCE public.shipment_event_id_seq
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
 
 
 --
--- Name: command command_pkey; Type: CONSTRAINT; Schema: public; Owner: -
+-- Name: shipment_event_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: -
 --
 
-ALTER TABLE ONLY public.command
-    ADD CONSTRAINT command_pkey PRIMARY KEY (id);
+ALTER SEQUENCE public.shipment_event_id_seq OWNED BY public.shipment_event.id;
 
 
 --
--- Name: message message_pkey; Type: CONSTRAINT; Schema: public; Owner: -
+-- Name: shipment_notify_config; Type: TABLE; Schema: public; Owner: -
 --
 
-ALTER TABLE ONLY public.message
-    ADD CONSTRAINT message_pkey PRIMARY KEY (id);
+CREATE TABLE public.shipment_notify_config (
+    user_id bigint NOT NULL,
+    transit boolean DEFAULT true NOT NULL,
+    out_for_delivery boolean DEFAULT true NOT NULL,
+    eta boolean DEFAULT false NOT NULL,
+    returned boolean DEFAULT true NOT NULL,
+    failure boolean DEFAULT true NOT NULL
+);
 
 
 --
--- Name: shipment shipment_pkey; Type: CONSTRAINT; Schema: public; Owner: -
+-- Name: sound; Type: TABLE; Schema: public; Owner: -
 --
 
-ALTER TABLE ONLY public.shipment
-    ADD CONSTRAINT shipment_pkey PRIMARY KEY (id);
+CREATE TABLE public.sound (
+    id integer NOT NULL,
+    create_date timestamp with time zone DEFAULT now() NOT NULL,
+    guild_id bigint NOT NULL,
+    author_id bigint NOT NULL,
+    name character varying(32) NOT NULL,
+    duration_ms integer NOT NULL
+);
 
 
 --
--- Name: shipment shipment_uk_carrier_number; Type: CONSTRAINT; Schema: public; Owner: -
+-- Name: sound_id_seq; Type: SEQUENCE; Schema: public; Owner: -
 --
 
-ALTER TABLE ONLY public.shipment
-    ADD CONSTRAINT shipment_uk_carrier_number UNIQUE (carrier, tracking_number);
+CREATE SEQUENCE public.sound_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
 
 
 --
--- Name: user_karma user_karma_pkey; Type: CONSTRAINT; Schema: public; Owner: -
+-- Name: sound_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: -
 --
 
-ALTER TABLE ONLY public.user_karma
-    ADD CONSTRAINT user_karma_pkey PRIMARY KEY (guild_id, user_id);
+ALTER SEQUENCE public.sound_id_seq OWNED BY public.sound.id;
 
 
 --
--- Name: user_presence user_presence_pkey; Type: CONSTRAINT; Schema: public; Owner: -
+-- Name: sound_config; Type: TABLE; Schema: public; Owner: -
 --
 
-ALTER TABLE ONLY public.user_presence
-    ADD CONSTRAINT user_presence_pkey PRIMARY KEY (id);
+CREATE TABLE public.sound_config (
+    guild_id bigint NOT NULL,
+    volume smallint DEFAULT 100 NOT NULL
+);
 
 
 --
--- Name: vote vote_pkey; Type: CONSTRAINT; Schema: public; Owner: -
+-- Name: user_activity; Type: TABLE; Schema: public; Owner: -
 --
 
-ALTER TABLE ONLY public.vote
-    ADD CONSTRAINT vote_pkey PRIMARY KEY (id);
+CREATE TABLE public.user_activity (
+    id bigint NOT NULL,
+    user_id bigint NOT NULL,
+    kind public.activity_type NOT NULL,
+    name character varying(512) NOT NULL,
+    details character varying(512),
+    state character varying(512),
+    url character varying(512),
+    emoji character varying(128),
+    start_date timestamp with time zone DEFAULT now() NOT NULL,
+    end_date timestamp with time zone
+);
 
 
 --
--- Name: user_karma_guild_id_idx; Type: INDEX; Schema: public; Owner: -
+-- Name: user_activity_id_seq; Type: SEQUENCE; Schema: public; Owner: -
 --
 
-CREATE INDEX user_karma_guild_id_idx ON public.user_karma USING btree (guild_id);
+CREATE SEQUENCE public.user_activity_id_seq
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
 
 
 --
--- Name: vote_voter_id_idx; Type: INDEX; Schema: public; Owner: -
+-- Name: user_activity_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: -
 --
 
-CREATE INDEX vote_voter_id_idx ON public.vote USING btree (voter_id);
+ALTER SEQUENCE public.user_activity_id_seq OWNED BY public.user_activity.id;
 
 
 --
--- Name: bot_start bot_start_row_update_date; Type: TRIGGER; Schema: public; Owner: -
+-- Name: user_karma; Type: TABLE; Schema: public; Owner: -
 --
 
-CREATE TRIGGER bot_start_row_update_date BEFORE UPDATE ON public.bot_start FOR EACH ROW EXECUTE FUNCTION public.row_update_date();
+CREATE TABLE public.user_karma (
+    guild_id bigint NOT NULL,
+    user_id bigint NOT NULL,
+    karma integer NOT NULL
+);
 
 
 --
--- Name: message message_row_update_date; Type: TRIGGER; Schema: public; Owner: -
+-- Name: user_presence; Type: TABLE; Schema: public; Owner: -
 --
 
-CREATE TRIGGER message_row_update_date BEFORE UPDATE ON public.message FOR EACH ROW EXECUTE FUNCTION public.row_update_date();
+CREATE TABLE public.user_presence (
+    id bigint NOT NULL,
+    create_date timestamp with time zone DEFAULT now() NOT NULL,
+    user_id bigint NOT NULL,
+    status public.online_status NOT NULL,
+    game_name character varying(512),
+    is_startup boolean DEFAULT false NOT NULL
+);
 
 
 --
--- Name: shipment shipment_row_update_date; Type: TRIGGER; Schema: public; Owner: -
+-- Name: user_presence_id_seq; Type: SEQUENCE; Schema: public; Owner: -
 --
 
-CREATE TRIGGER shipment_row_update_date BEFORE UPDATE ON public.shipment FOR EACH ROW EXECUTE FUNCTION public.row_update_date();
+CREATE SEQUENCE public.user_presence_id_seq
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
 
 
 --
--- Name: SCHEMA public; Type: ACL; Schema: -; Owner: -
+-- Name: user_presence_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: -
 --
 
-REVOKE USAGE ON SCHEMA public FROM PUBLIC;
-GRANT ALL ON SCHEMA public TO PUBLIC;
+ALTER SEQUENCE public.user_presence_id_seq OWNED BY public.user_presence.id;
 
 
 --
--- Name: TABLE bot_start; Type: ACL; Schema: public; Owner: -
+-- Name: user_timezone; Type: TABLE; Schema: public; Owner: -
 --
 
-GRANT SELECT,INSERT,UPDATE ON TABLE public.bot_start TO rustyz;
+CREATE TABLE public.user_timezone (
+    user_id bigint NOT NULL,
+    timezone character varying(64) NOT NULL
+);
 
 
 --
--- Name: SEQUENCE bot_start_id_seq; Type: ACL; Schema: public; Owner: -
+-- Name: voice_session; Type: TABLE; Schema: public; Owner: -
 --
 
-GRANT USAGE ON SEQUENCE public.bot_start_id_seq TO rustyz;
+CREATE TABLE public.voice_session (
+    id bigint NOT NULL,
+    guild_id bigint NOT NULL,
+    channel_id bigint NOT NULL,
+    user_id bigint NOT NULL,
+    join_date timestamp with time zone DEFAULT now() NOT NULL,
+    leave_date timestamp with time zone,
+    mute boolean NOT NULL,
+    deaf boolean NOT NULL,
+    stream boolean NOT NULL
+);
 
 
 --
--- Name: TABLE command; Type: ACL; Schema: public; Owner: -
+-- Name: voice_session_id_seq; Type: SEQUENCE; Schema: public; Owner: -
 --
 
-GRANT SELECT,INSERT ON TABLE public.command TO rustyz;
+CREATE SEQUENCE public.voice_session_id_seq
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
 
 
 --
--- Name: SEQUENCE command_id_seq; Type: ACL; Schema: public; Owner: -
+-- Name: voice_session_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: -
 --
 
-GRANT USAGE ON SEQUENCE public.command_id_seq TO rustyz;
+ALTER SEQUENCE public.voice_session_id_seq OWNED BY public.voice_session.id;
 
 
 --
--- Name: TABLE message; Type: ACL; Schema: public; Owner: -
+-- Name: vote; Type: TABLE; Schema: public; Owner: -
+--
+
+CREATE TABLE public.vote (
+    id integer NOT NULL,
+    create_date timestamp with time zone DEFAULT now() NOT NULL,
+    guild_id bigint NOT NULL,
+    voter_id bigint NOT NULL,
+    votee_id bigint NOT NULL,
+    is_upvote boolean NOT NULL,
+    reason character varying(200),
+    message_id bigint
+);
+
+
+--
+-- Name: vote_id_seq; Type: SEQUENCE; Schema: public; Owner: -
+--
+
+CREATE SEQUENCE public.vote_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+
+--
+-- Name: vote_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: -
+--
+
+ALTER SEQUENCE public.vote_id_seq OWNED BY public.vote.id;
+
+
+--
+-- Name: vote_config; Type: TABLE; Schema: public; Owner: -
+--
+
+CREATE TABLE public.vote_config (
+    guild_id bigint NOT NULL,
+    cooldown_min_seconds integer,
+    cooldown_max_seconds integer,
+    reciprocal_hours integer,
+    max_daily_votes integer,
+    min_account_days integer,
+    min_member_days integer,
+    max_bulk_votes integer,
+    season_days integer,
+    channel_ids bigint[],
+    self_vote_reply character varying(200),
+    cooldown_reply character varying(200),
+    reciprocal_reply character varying(200),
+    limit_reply character varying(200),
+    too_new_reply character varying(200),
+    channel_reply character varying(200),
+    upvote_emoji character varying(100),
+    downvote_emoji character varying(100)
+);
+
+
+--
+-- Name: bot_start id; Type: DEFAULT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.bot_start ALTER COLUMN id SET DEFAULT nextval('public.bot_start_id_seq'::regclass);
+
+
+--
+-- Name: command id; Type: DEFAULT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.command ALTER COLUMN id SET DEFAULT nextval('public.command_id_seq'::regclass);
+
+
+--
+-- Name: game_session id; Type: DEFAULT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.game_session ALTER COLUMN id SET DEFAULT nextval('public.game_session_id_seq'::regclass);
+
+
+--
+-- Name: game_subscription id; Type: DEFAULT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.game_subscription ALTER COLUMN id SET DEFAULT nextval('public.game_subscription_id_seq'::regclass);
+
+
+--
+-- Name: karma_season id; Type: DEFAULT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.karma_season ALTER COLUMN id SET DEFAULT nextval('public.karma_season_id_seq'::regclass);
+
+
+--
+-- Name: message id; Type: DEFAULT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.message ALTER COLUMN id SET DEFAULT nextval('public.message_id_seq'::regclass);
+
+
+--
+-- Name: online_session id; Type: DEFAULT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.online_session ALTER COLUMN id SET DEFAULT nextval('public.online_session_id_seq'::regclass);
+
+
+--
+-- Name: paginator id; Type: DEFAULT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.paginator ALTER COLUMN id SET DEFAULT nextval('public.paginator_id_seq'::regclass);
+
+
+--
+-- Name: shipment id; Type: DEFAULT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.shipment ALTER COLUMN id SET DEFAULT nextval('public.shipment_id_seq'::regclass);
+
+
+--
+-- Name: shipment_event id; Type: DEFAULT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.shipment_event ALTER COLUMN id SET DEFAULT nextval('public.shipment_event_id_seq'::regclass);
+
+
+--
+-- Name: sound id; Type: DEFAULT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.sound ALTER COLUMN id SET DEFAULT nextval('public.sound_id_seq'::regclass);
+
+
+--
+-- Name: user_activity id; Type: DEFAULT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.user_activity ALTER COLUMN id SET DEFAULT nextval('public.user_activity_id_seq'::regclass);
+
+
+--
+-- Name: user_presence id; Type: DEFAULT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.user_presence ALTER COLUMN id SET DEFAULT nextval('public.user_presence_id_seq'::regclass);
+
+
+--
+-- Name: voice_session id; Type: DEFAULT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.voice_session ALTER COLUMN id SET DEFAULT nextval('public.voice_session_id_seq'::regclass);
+
+
+--
+-- Name: vote id; Type: DEFAULT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.vote ALTER COLUMN id SET DEFAULT nextval('public.vote_id_seq'::regclass);
+
+
+--
+-- Name: bot_start bot_start_pkey; Type: CONSTRAINT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.bot_start
+    ADD CONSTRAINT bot_start_pkey PRIMARY KEY (id);
+
+
+--
+-- Name: command command_pkey; Type: CONSTRAINT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.command
+    ADD CONSTRAINT command_pkey PRIMARY KEY (id);
+
+
+--
+-- Name: digest_config digest_config_pkey; Type: CONSTRAINT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.digest_config
+    ADD CONSTRAINT digest_config_pkey PRIMARY KEY (guild_id);
+
+
+--
+-- Name: game_alias game_alias_pkey; Type: CONSTRAINT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.game_alias
+    ADD CONSTRAINT game_alias_pkey PRIMARY KEY (guild_id, alias);
+
+
+--
+-- Name: game_session game_session_pkey; Type: CONSTRAINT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.game_session
+    ADD CONSTRAINT game_session_pkey PRIMARY KEY (id);
+
+
+--
+-- Name: game_subscription game_subscription_guild_id_user_id_game_key; Type: CONSTRAINT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.game_subscription
+    ADD CONSTRAINT game_subscription_guild_id_user_id_game_key UNIQUE (guild_id, user_id, game);
+
+
+--
+-- Name: game_subscription game_subscription_pkey; Type: CONSTRAINT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.game_subscription
+    ADD CONSTRAINT game_subscription_pkey PRIMARY KEY (id);
+
+
+--
+-- Name: karma_season karma_season_guild_id_number_key; Type: CONSTRAINT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.karma_season
+    ADD CONSTRAINT karma_season_guild_id_number_key UNIQUE (guild_id, number);
+
+
+--
+-- Name: karma_season karma_season_pkey; Type: CONSTRAINT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.karma_season
+    ADD CONSTRAINT karma_season_pkey PRIMARY KEY (id);
+
+
+--
+-- Name: karma_season_standing karma_season_standing_pkey; Type: CONSTRAINT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.karma_season_standing
+    ADD CONSTRAINT karma_season_standing_pkey PRIMARY KEY (season_id, user_id);
+
+
+--
+-- Name: message message_pkey; Type: CONSTRAINT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.message
+    ADD CONSTRAINT message_pkey PRIMARY KEY (id);
+
+
+--
+-- Name: online_session online_session_pkey; Type: CONSTRAINT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.online_session
+    ADD CONSTRAINT online_session_pkey PRIMARY KEY (id);
+
+
+--
+-- Name: paginator paginator_pkey; Type: CONSTRAINT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.paginator
+    ADD CONSTRAINT paginator_pkey PRIMARY KEY (id);
+
+
+--
+-- Name: shipment shipment_pkey; Type: CONSTRAINT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.shipment
+    ADD CONSTRAINT shipment_pkey PRIMARY KEY (id);
+
+
+--
+-- Name: shipment shipment_uk_carrier_number; Type: CONSTRAINT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.shipment
+    ADD CONSTRAINT shipment_uk_carrier_number UNIQUE (carrier, tracking_number);
+
+
+--
+-- Name: shipment_event shipment_event_pkey; Type: CONSTRAINT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.shipment_event
+    ADD CONSTRAINT shipment_event_pkey PRIMARY KEY (id);
+
+
+--
+-- Name: shipment_event shipment_event_shipment_id_event_date_status_key; Type: CONSTRAINT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.shipment_event
+    ADD CONSTRAINT shipment_event_shipment_id_event_date_status_key UNIQUE (shipment_id, event_date, status);
+
+
+--
+-- Name: shipment_notify_config shipment_notify_config_pkey; Type: CONSTRAINT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.shipment_notify_config
+    ADD CONSTRAINT shipment_notify_config_pkey PRIMARY KEY (user_id);
+
+
+--
+-- Name: sound sound_pkey; Type: CONSTRAINT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.sound
+    ADD CONSTRAINT sound_pkey PRIMARY KEY (id);
+
+
+--
+-- Name: sound sound_uk_guild_name; Type: CONSTRAINT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.sound
+    ADD CONSTRAINT sound_uk_guild_name UNIQUE (guild_id, name);
+
+
+--
+-- Name: sound_config sound_config_pkey; Type: CONSTRAINT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.sound_config
+    ADD CONSTRAINT sound_config_pkey PRIMARY KEY (guild_id);
+
+
+--
+-- Name: user_activity user_activity_pkey; Type: CONSTRAINT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.user_activity
+    ADD CONSTRAINT user_activity_pkey PRIMARY KEY (id);
+
+
+--
+-- Name: user_karma user_karma_pkey; Type: CONSTRAINT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.user_karma
+    ADD CONSTRAINT user_karma_pkey PRIMARY KEY (guild_id, user_id);
+
+
+--
+-- Name: user_presence user_presence_pkey; Type: CONSTRAINT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.user_presence
+    ADD CONSTRAINT user_presence_pkey PRIMARY KEY (id);
+
+
+--
+-- Name: user_timezone user_timezone_pkey; Type: CONSTRAINT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.user_timezone
+    ADD CONSTRAINT user_timezone_pkey PRIMARY KEY (user_id);
+
+
+--
+-- Name: voice_session voice_session_pkey; Type: CONSTRAINT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.voice_session
+    ADD CONSTRAINT voice_session_pkey PRIMARY KEY (id);
+
+
+--
+-- Name: vote vote_pkey; Type: CONSTRAINT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.vote
+    ADD CONSTRAINT vote_pkey PRIMARY KEY (id);
+
+
+--
+-- Name: vote_config vote_config_pkey; Type: CONSTRAINT; Schema: public; Owner: -
+--
+
+ALTER TABLE ONLY public.vote_config
+    ADD CONSTRAINT vote_config_pkey PRIMARY KEY (guild_id);
+
+
+--
+-- Name: game_session_open_idx; Type: INDEX; Schema: public; Owner: -
+--
+
+CREATE INDEX game_session_open_idx ON public.game_session USING btree (user_id) WHERE (end_date IS NULL);
+
+
+--
+-- Name: game_session_user_id_start_date_idx; Type: INDEX; Schema: public; Owner: -
+--
+
+CREATE INDEX game_session_user_id_start_date_idx ON public.game_session USING btree (user_id, start_date);
+
+
+--
+-- Name: game_subscription_guild_id_game_idx; Type: INDEX; Schema: public; Owner: -
+--
+
+CREATE INDEX game_subscription_guild_id_game_idx ON public.game_subscription USING btree (guild_id, game);
+
+
+--
+-- Name: message_channel_id_create_date_idx; Type: INDEX; Schema: public; Owner: -
+--
+
+CREATE INDEX message_channel_id_create_date_idx ON public.message USING btree (channel_id, create_date);
+
+
+--
+-- Name: message_guild_id_create_date_idx; Type: INDEX; Schema: public; Owner: -
+--
+
+CREATE INDEX message_guild_id_create_date_idx ON public.message USING btree (guild_id, create_date);
+
+
+--
+-- Name: online_session_open_idx; Type: INDEX; Schema: public; Owner: -
+--
+
+CREATE INDEX online_session_open_idx ON public.online_session USING btree (user_id) WHERE (end_date IS NULL);
+
+
+--
+-- Name: online_session_user_id_start_date_idx; Type: INDEX; Schema: public; Owner: -
+--
+
+CREATE INDEX online_session_user_id_start_date_idx ON public.online_session USING btree (user_id, start_date);
+
+
+--
+-- Name: paginator_expire_date_idx; Type: INDEX; Schema: public; Owner: -
+--
+
+CREATE INDEX paginator_expire_date_idx ON public.paginator USING btree (expire_date);
+
+
+--
+-- Name: user_karma_guild_id_idx; Type: INDEX; Schema: public; Owner: -
+--
+
+CREATE INDEX user_karma_guild_id_idx ON public.user_karma USING btree (guild_id);
+
+
+--
+-- Name: user_activity_open_idx; Type: INDEX; Schema: public; Owner: -
+--
+
+CREATE INDEX user_activity_open_idx ON public.user_activity USING btree (user_id) WHERE (end_date IS NULL);
+
+
+--
+-- Name: user_activity_user_id_kind_start_date_idx; Type: INDEX; Schema: public; Owner: -
+--
+
+CREATE INDEX user_activity_user_id_kind_start_date_idx ON public.user_activity USING btree (user_id, kind, start_date);
+
+
+--
+-- Name: voice_session_guild_id_user_id_idx; Type: INDEX; Schema: public; Owner: -
+--
+
+CREATE INDEX voice_session_guild_id_user_id_idx ON public.voice_session USING btree (guild_id, user_id);
+
+
+--
+-- Name: vote_message_id_idx; Type: INDEX; Schema: public; Owner: -
+--
+
+CREATE INDEX vote_message_id_idx ON public.vote USING btree (message_id) WHERE (message_id IS NOT NULL);
+
+
+--
+-- Name: vote_voter_id_idx; Type: INDEX; Schema: public; Owner: -
+--
+
+CREATE INDEX vote_voter_id_idx ON public.vote USING btree (voter_id);
+
+
+--
+-- Name: bot_start bot_start_row_update_date; Type: TRIGGER; Schema: public; Owner: -
+--
+
+CREATE TRIGGER bot_start_row_update_date BEFORE UPDATE ON public.bot_start FOR EACH ROW EXECUTE FUNCTION public.row_update_date();
+
+
+--
+-- Name: message message_row_update_date; Type: TRIGGER; Schema: public; Owner: -
+--
+
+CREATE TRIGGER message_row_update_date BEFORE UPDATE ON public.message FOR EACH ROW EXECUTE FUNCTION public.row_update_date();
+
+
+--
+-- Name: shipment shipment_row_update_date; Type: TRIGGER; Schema: public; Owner: -
+--
+
+CREATE TRIGGER shipment_row_update_date BEFORE UPDATE ON public.shipment FOR EACH ROW EXECUTE FUNCTION public.row_update_date();
+
+
+--
+-- Name: SCHEMA public; Type: ACL; Schema: -; Owner: -
+--
+
+REVOKE USAGE ON SCHEMA public FROM PUBLIC;
+GRANT ALL ON SCHEMA public TO PUBLIC;
+
+
+--
+-- Name: TABLE bot_start; Type: ACL; Schema: public; Owner: -
+--
+
+GRANT SELECT,INSERT,UPDATE ON TABLE public.bot_start TO rustyz;
+
+
+--
+-- Name: SEQUENCE bot_start_id_seq; Type: ACL; Schema: public; Owner: -
+--
+
+GRANT USAGE ON SEQUENCE public.bot_start_id_seq TO rustyz;
+
+
+--
+-- Name: TABLE command; Type: ACL; Schema: public; Owner: -
+--
+
+GRANT SELECT,INSERT ON TABLE public.command TO rustyz;
+
+
+--
+-- Name: SEQUENCE command_id_seq; Type: ACL; Schema: public; Owner: -
+--
+
+GRANT USAGE ON SEQUENCE public.command_id_seq TO rustyz;
+
+
+--
+-- Name: TABLE digest_config; Type: ACL; Schema: public; Owner: -
+--
+
+GRANT SELECT,INSERT,UPDATE,DELETE ON TABLE public.digest_config TO rustyz;
+
+
+--
+-- Name: TABLE game_alias; Type: ACL; Schema: public; Owner: -
+--
+
+GRANT SELECT,INSERT,UPDATE,DELETE ON TABLE public.game_alias TO rustyz;
+
+
+--
+-- Name: TABLE game_session; Type: ACL; Schema: public; Owner: -
+--
+
+GRANT SELECT,INSERT,UPDATE ON TABLE public.game_session TO rustyz;
+
+
+--
+-- Name: SEQUENCE game_session_id_seq; Type: ACL; Schema: public; Owner: -
+--
+
+GRANT USAGE ON SEQUENCE public.game_session_id_seq TO rustyz;
+
+
+--
+-- Name: TABLE game_subscription; Type: ACL; Schema: public; Owner: -
+--
+
+GRANT SELECT,INSERT,UPDATE,DELETE ON TABLE public.game_subscription TO rustyz;
+
+
+--
+-- Name: SEQUENCE game_subscription_id_seq; Type: ACL; Schema: public; Owner: -
+--
+
+GRANT USAGE ON SEQUENCE public.game_subscription_id_seq TO rustyz;
+
+
+--
+-- Name: TABLE karma_season; Type: ACL; Schema: public; Owner: -
+--
+
+GRANT SELECT,INSERT ON TABLE public.karma_season TO rustyz;
+
+
+--
+-- Name: SEQUENCE karma_season_id_seq; Type: ACL; Schema: public; Owner: -
+--
+
+GRANT USAGE ON SEQUENCE public.karma_season_id_seq TO rustyz;
+
+
+--
+-- Name: TABLE karma_season_standing; Type: ACL; Schema: public; Owner: -
+--
+
+GRANT SELECT,INSERT ON TABLE public.karma_season_standing TO rustyz;
+
+
+--
+-- Name: TABLE message; Type: ACL; Schema: public; Owner: -
 --
 
 GRANT SELECT,INSERT,DELETE,UPDATE ON TABLE public.message TO rustyz;
@@ -534,24 +1296,38 @@ GRANT USAGE ON SEQUENCE public.message_id_seq TO rustyz;
 
 
 --
--- Name: TABLE playtime_button; Type: ACL; Schema: public; Owner: -
+-- Name: TABLE online_session; Type: ACL; Schema: public; Owner: -
+--
+
+GRANT SELECT,INSERT,UPDATE ON TABLE public.online_session TO rustyz;
+
+
+--
+-- Name: SEQUENCE online_session_id_seq; Type: ACL; Schema: public; Owner: -
+--
+
+GRANT USAGE ON SEQUENCE public.online_session_id_seq TO rustyz;
+
+
+--
+-- Name: TABLE paginator; Type: ACL; Schema: public; Owner: -
 --
 
-GRANT SELECT,INSERT,UPDATE ON TABLE public.playtime_button TO rustyz;
+GRANT SELECT,INSERT,DELETE,UPDATE ON TABLE public.paginator TO rustyz;
 
 
 --
--- Name: SEQUENCE playtime_button_id_seq; Type: ACL; Schema: public; Owner: -
+-- Name: SEQUENCE paginator_id_seq; Type: ACL; Schema: public; Owner: -
 --
 
-GRANT USAGE ON SEQUENCE public.playtime_button_id_seq TO rustyz;
+GRANT USAGE ON SEQUENCE public.paginator_id_seq TO rustyz;
 
 
 --
 -- Name: TABLE shipment; Type: ACL; Schema: public; Owner: -
 --
 
-GRANT SELECT,INSERT,UPDATE ON TABLE public.shipment TO rustyz;
+GRANT SELECT,INSERT,UPDATE,DELETE ON TABLE public.shipment TO rustyz;
 
 
 --
@@ -561,6 +1337,62 @@ GRANT SELECT,INSERT,UPDATE ON TABLE public.shipment TO rustyz;
 GRANT USAGE ON SEQUENCE public.shipment_id_seq TO rustyz;
 
 
+--
+-- Name: TABLE shipment_event; Type: ACL; Schema: public; Owner: -
+--
+
+GRANT SELECT,INSERT,DELETE ON TABLE public.shipment_event TO rustyz;
+
+
+--
+-- Name: SEQUENCE shipment_event_id_seq; Type: ACL; Schema: public; Owner: -
+--
+
+GRANT USAGE ON SEQUENCE public.shipment_event_id_seq TO rustyz;
+
+
+--
+-- Name: TABLE shipment_notify_config; Type: ACL; Schema: public; Owner: -
+--
+
+GRANT SELECT,INSERT,UPDATE ON TABLE public.shipment_notify_config TO rustyz;
+
+
+--
+-- Name: TABLE sound; Type: ACL; Schema: public; Owner: -
+--
+
+GRANT SELECT,INSERT,DELETE ON TABLE public.sound TO rustyz;
+
+
+--
+-- Name: SEQUENCE sound_id_seq; Type: ACL; Schema: public; Owner: -
+--
+
+GRANT USAGE ON SEQUENCE public.sound_id_seq TO rustyz;
+
+
+--
+-- Name: TABLE sound_config; Type: ACL; Schema: public; Owner: -
+--
+
+GRANT SELECT,INSERT,UPDATE ON TABLE public.sound_config TO rustyz;
+
+
+--
+-- Name: TABLE user_activity; Type: ACL; Schema: public; Owner: -
+--
+
+GRANT SELECT,INSERT,UPDATE ON TABLE public.user_activity TO rustyz;
+
+
+--
+-- Name: SEQUENCE user_activity_id_seq; Type: ACL; Schema: public; Owner: -
+--
+
+GRANT USAGE ON SEQUENCE public.user_activity_id_seq TO rustyz;
+
+
 --
 -- Name: TABLE user_karma; Type: ACL; Schema: public; Owner: -
 --
@@ -582,11 +1414,32 @@ GRANT SELECT,INSERT ON TABLE public.user_presence TO rustyz;
 GRANT USAGE ON SEQUENCE public.user_presence_id_seq TO rustyz;
 
 
+--
+-- Name: TABLE user_timezone; Type: ACL; Schema: public; Owner: -
+--
+
+GRANT SELECT,INSERT,UPDATE ON TABLE public.user_timezone TO rustyz;
+
+
+--
+-- Name: TABLE voice_session; Type: ACL; Schema: public; Owner: -
+--
+
+GRANT SELECT,INSERT,UPDATE ON TABLE public.voice_session TO rustyz;
+
+
+--
+-- Name: SEQUENCE voice_session_id_seq; Type: ACL; Schema: public; Owner: -
+--
+
+GRANT USAGE ON SEQUENCE public.voice_session_id_seq TO rustyz;
+
+
 --
 -- Name: TABLE vote; Type: ACL; Schema: public; Owner: -
 --
 
-GRANT SELECT,INSERT,UPDATE ON TABLE public.vote TO rustyz;
+GRANT SELECT,INSERT,UPDATE,DELETE ON TABLE public.vote TO rustyz;
 
 
 --
@@ -596,6 +1449,13 @@ GRANT SELECT,INSERT,UPDATE ON TABLE public.vote TO rustyz;
 GRANT USAGE ON SEQUENCE public.vote_id_seq TO rustyz;
 
 
+--
+-- Name: TABLE vote_config; Type: ACL; Schema: public; Owner: -
+--
+
+GRANT SELECT,INSERT,UPDATE,DELETE ON TABLE public.vote_config TO rustyz;
+
+
 --
 -- PostgreSQL database dump complete
 --
@@ -0,0 +1,423 @@
+use font8x8::legacy::{BASIC_LEGACY, LATIN_LEGACY};
+use image::codecs::png::PngEncoder;
+use image::{ImageEncoder, ImageResult, Rgb, RgbImage};
+
+// Colors roughly matching Discord's dark theme so charts don't stand out in the channel
+pub const BACKGROUND: Rgb<u8> = Rgb([49, 51, 56]);
+pub const GRID: Rgb<u8> = Rgb([78, 80, 88]);
+pub const TEXT: Rgb<u8> = Rgb([219, 222, 225]);
+pub const MUTED_TEXT: Rgb<u8> = Rgb([148, 155, 164]);
+
+// Glyphs are 8x8 pixels before scaling
+pub const GLYPH_SIZE: u32 = 8;
+
+// Stops of the color scale used for heatmaps, from low to high (approximately viridis)
+const SCALE_STOPS: [[u8; 3]; 5] = [
+    [68, 1, 84],
+    [59, 82, 139],
+    [33, 145, 140],
+    [94, 201, 98],
+    [253, 231, 37],
+];
+
+// Distinct colors for series in bar and area charts
+pub const PALETTE: [Rgb<u8>; 10] = [
+    Rgb([88, 101, 242]),
+    Rgb([87, 242, 135]),
+    Rgb([254, 231, 92]),
+    Rgb([235, 69, 158]),
+    Rgb([237, 66, 69]),
+    Rgb([52, 152, 219]),
+    Rgb([230, 126, 34]),
+    Rgb([155, 89, 182]),
+    Rgb([26, 188, 156]),
+    Rgb([149, 165, 166]),
+];
+
+// Height of each bar drawn by draw_bar_chart, including the gap below it
+pub const BAR_HEIGHT: u32 = 22;
+// Height of each row of entries drawn by draw_legend
+pub const LEGEND_ROW_HEIGHT: u32 = 16;
+const LEGEND_COLUMN_WIDTH: u32 = 190;
+// Width reserved left of a plot for axis labels
+const AXIS_LABEL_WIDTH: u32 = 48;
+
+// A named line of values, one per point along the x axis
+pub struct Series {
+    pub name: String,
+    pub values: Vec<f64>,
+}
+
+fn glyph(c: char) -> [u8; 8] {
+    let code = c as usize;
+    if c.is_ascii() {
+        BASIC_LEGACY[code]
+    } else if (0xA0..0x100).contains(&code) {
+        LATIN_LEGACY[code - 0xA0]
+    } else {
+        BASIC_LEGACY[usize::from(b'?')]
+    }
+}
+
+// Width in pixels of text drawn at scale
+pub fn text_width(text: &str, scale: u32) -> u32 {
+    u32::try_from(text.chars().count())
+        .unwrap_or(u32::MAX)
+        .saturating_mul(GLYPH_SIZE * scale)
+}
+
+// Draws text with its top left corner at x, y, clipping anything outside the image
+pub fn draw_text(image: &mut RgbImage, x: u32, y: u32, text: &str, color: Rgb<u8>, scale: u32) {
+    for (i, c) in (0..).zip(text.chars()) {
+        let glyph_x = x + i * GLYPH_SIZE * scale;
+        for (row, bits) in (0..).zip(glyph(c)) {
+            for col in 0..GLYPH_SIZE {
+                if bits & (1 << col) != 0 {
+                    fill_rect(
+                        image,
+                        glyph_x + col * scale,
+                        y + row * scale,
+                        scale,
+                        scale,
+                        color,
+                    );
+                }
+            }
+        }
+    }
+}
+
+// Fills a rectangle, clipping anything outside the image
+pub fn fill_rect(image: &mut RgbImage, x: u32, y: u32, width: u32, height: u32, color: Rgb<u8>) {
+    for py in y..(y + height).min(image.height()) {
+        for px in x..(x + width).min(image.width()) {
+            image.put_pixel(px, py, color);
+        }
+    }
+}
+
+// Maps t in [0, 1] onto the heatmap color scale
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+pub fn color_scale(t: f64) -> Rgb<u8> {
+    let t = if t.is_finite() {
+        t.clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let position = t * (SCALE_STOPS.len() - 1) as f64;
+    let i = (position.floor() as usize).min(SCALE_STOPS.len() - 2);
+    let fraction = position - i as f64;
+    let (low, high) = (SCALE_STOPS[i], SCALE_STOPS[i + 1]);
+    Rgb(std::array::from_fn(|c| {
+        (f64::from(low[c]) + (f64::from(high[c]) - f64::from(low[c])) * fraction).round() as u8
+    }))
+}
+
+// Draws a horizontal gradient of the color scale
+pub fn draw_scale_legend(image: &mut RgbImage, x: u32, y: u32, width: u32, height: u32) {
+    for i in 0..width {
+        let color = color_scale(f64::from(i) / f64::from(width.saturating_sub(1).max(1)));
+        fill_rect(image, x + i, y, 1, height, color);
+    }
+}
+
+// Truncates text so that it's at most max_width pixels wide when drawn at scale
+pub fn truncate_text(text: &str, max_width: u32, scale: u32) -> String {
+    let max_chars = usize::try_from(max_width / (GLYPH_SIZE * scale)).unwrap_or(usize::MAX);
+    if text.chars().count() <= max_chars {
+        return String::from(text);
+    }
+    let mut truncated: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    truncated.push('~');
+    truncated
+}
+
+// Draws a labelled horizontal bar for each (label, value), longest bar being the largest value
+// Each bar takes BAR_HEIGHT pixels of height
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+pub fn draw_bar_chart(
+    image: &mut RgbImage,
+    x: u32,
+    y: u32,
+    width: u32,
+    bars: &[(String, f64)],
+    value_suffix: &str,
+) {
+    let label_width = width / 4;
+    let value_width = 80;
+    let bar_area = width.saturating_sub(label_width + value_width + 8);
+    let max = bars.iter().map(|b| b.1).fold(0.0, f64::max);
+    for (i, (color, (label, value))) in (0..).zip(PALETTE.iter().cycle().zip(bars)) {
+        let bar_top = y + i * BAR_HEIGHT;
+        let text_top = bar_top + (BAR_HEIGHT - 4 - GLYPH_SIZE) / 2;
+        draw_text(
+            image,
+            x,
+            text_top,
+            &truncate_text(label, label_width - 8, 1),
+            TEXT,
+            1,
+        );
+        let bar_length = if max > 0.0 {
+            ((value / max) * f64::from(bar_area)).round().max(1.0) as u32
+        } else {
+            1
+        };
+        let bar_left = x + label_width;
+        fill_rect(image, bar_left, bar_top, bar_length, BAR_HEIGHT - 4, *color);
+        draw_text(
+            image,
+            bar_left + bar_length + 6,
+            text_top,
+            &format!("{value:.1}{value_suffix}"),
+            MUTED_TEXT,
+            1,
+        );
+    }
+}
+
+// Draws series stacked on top of each other, linearly interpolated between points
+// x_labels are spread evenly under the x axis, and should line up with the first, last, and evenly spaced points between
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss,
+    clippy::too_many_arguments
+)]
+pub fn draw_stacked_area_chart(
+    image: &mut RgbImage,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    series: &[Series],
+    x_labels: &[String],
+    value_suffix: &str,
+) {
+    let points = series.iter().map(|s| s.values.len()).max().unwrap_or(0);
+    let plot_left = x + AXIS_LABEL_WIDTH;
+    let plot_width = width.saturating_sub(AXIS_LABEL_WIDTH);
+    let plot_height = height.saturating_sub(GLYPH_SIZE + 8);
+    let baseline = y + plot_height;
+
+    let max = (0..points)
+        .map(|p| {
+            series
+                .iter()
+                .map(|s| s.values.get(p).copied().unwrap_or(0.0))
+                .sum::<f64>()
+        })
+        .fold(0.0, f64::max);
+    let max = if max > 0.0 { max * 1.1 } else { 1.0 };
+
+    // horizontal grid lines with value labels
+    for tick in 0..=4 {
+        let line_y = baseline - plot_height * tick / 4;
+        fill_rect(image, plot_left, line_y, plot_width, 1, GRID);
+        let label = format!("{:.0}{value_suffix}", max * f64::from(tick) / 4.0);
+        draw_text(
+            image,
+            plot_left.saturating_sub(text_width(&label, 1) + 6),
+            line_y.saturating_sub(GLYPH_SIZE / 2),
+            &label,
+            MUTED_TEXT,
+            1,
+        );
+    }
+
+    if points > 0 {
+        for px in 0..plot_width {
+            // position of this column between points
+            let position = if points > 1 {
+                f64::from(px) / f64::from(plot_width.saturating_sub(1).max(1)) * (points - 1) as f64
+            } else {
+                0.0
+            };
+            let i = (position.floor() as usize).min(points.saturating_sub(2));
+            let fraction = position - i as f64;
+            let mut stacked = 0.0;
+            for (s, color) in series.iter().zip(PALETTE.iter().cycle()) {
+                let low = s.values.get(i).copied().unwrap_or(0.0);
+                let high = s.values.get(i + 1).copied().unwrap_or(low);
+                let value = low + (high - low) * fraction;
+                let bottom = (stacked / max * f64::from(plot_height)).round() as u32;
+                stacked += value;
+                let top = (stacked / max * f64::from(plot_height)).round() as u32;
+                if top > bottom {
+                    fill_rect(
+                        image,
+                        plot_left + px,
+                        baseline - top,
+                        1,
+                        top - bottom,
+                        *color,
+                    );
+                }
+            }
+        }
+    }
+
+    let label_count = u32::try_from(x_labels.len()).unwrap_or(0);
+    for (i, label) in (0..).zip(x_labels) {
+        let center = if label_count > 1 {
+            plot_left + plot_width.saturating_sub(1) * i / (label_count - 1)
+        } else {
+            plot_left
+        };
+        let label_width = text_width(label, 1);
+        let label_x = center.saturating_sub(label_width / 2).clamp(
+            plot_left,
+            (plot_left + plot_width).saturating_sub(label_width),
+        );
+        draw_text(image, label_x, baseline + 6, label, MUTED_TEXT, 1);
+    }
+}
+
+// Draws values as a line, linearly interpolated between points, with the range of the y axis fitted to the values
+// x_labels are spread evenly under the x axis, and should line up with the first, last, and evenly spaced points between
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss,
+    clippy::too_many_arguments
+)]
+pub fn draw_line_chart(
+    image: &mut RgbImage,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    values: &[f64],
+    x_labels: &[String],
+    color: Rgb<u8>,
+) {
+    let points = values.len();
+    let plot_left = x + AXIS_LABEL_WIDTH;
+    let plot_width = width.saturating_sub(AXIS_LABEL_WIDTH);
+    let plot_height = height.saturating_sub(GLYPH_SIZE + 8);
+    let baseline = y + plot_height;
+
+    // always include zero so it's clear when values go negative
+    let min = values.iter().copied().fold(0.0, f64::min);
+    let max = values.iter().copied().fold(0.0, f64::max);
+    let padding = ((max - min) * 0.1).max(1.0);
+    let (min, max) = (if min < 0.0 { min - padding } else { 0.0 }, max + padding);
+    let to_y = |value: f64| -> u32 {
+        baseline - ((value - min) / (max - min) * f64::from(plot_height)).round() as u32
+    };
+
+    // horizontal grid lines with value labels
+    for tick in 0..=4 {
+        let value = min + (max - min) * f64::from(tick) / 4.0;
+        let line_y = baseline - plot_height * tick / 4;
+        fill_rect(image, plot_left, line_y, plot_width, 1, GRID);
+        let label = format!("{value:.0}");
+        draw_text(
+            image,
+            plot_left.saturating_sub(text_width(&label, 1) + 6),
+            line_y.saturating_sub(GLYPH_SIZE / 2),
+            &label,
+            MUTED_TEXT,
+            1,
+        );
+    }
+    if min < 0.0 {
+        fill_rect(image, plot_left, to_y(0.0), plot_width, 1, MUTED_TEXT);
+    }
+
+    if points > 0 {
+        let mut previous_y = None;
+        for px in 0..plot_width {
+            // position of this column between points
+            let position = if points > 1 {
+                f64::from(px) / f64::from(plot_width.saturating_sub(1).max(1)) * (points - 1) as f64
+            } else {
+                0.0
+            };
+            let i = (position.floor() as usize).min(points.saturating_sub(2));
+            let fraction = position - i as f64;
+            let low = values[i];
+            let high = values.get(i + 1).copied().unwrap_or(low);
+            let line_y = to_y(low + (high - low) * fraction);
+            // join to the previous column so steep changes stay connected
+            let (top, bottom) = match previous_y {
+                Some(p) => (line_y.min(p), line_y.max(p)),
+                None => (line_y, line_y),
+            };
+            fill_rect(
+                image,
+                plot_left + px,
+                top.saturating_sub(1),
+                2,
+                bottom - top + 2,
+                color,
+            );
+            previous_y = Some(line_y);
+        }
+    }
+
+    let label_count = u32::try_from(x_labels.len()).unwrap_or(0);
+    for (i, label) in (0..).zip(x_labels) {
+        let center = if label_count > 1 {
+            plot_left + plot_width.saturating_sub(1) * i / (label_count - 1)
+        } else {
+            plot_left
+        };
+        let label_width = text_width(label, 1);
+        let label_x = center.saturating_sub(label_width / 2).clamp(
+            plot_left,
+            (plot_left + plot_width).saturating_sub(label_width),
+        );
+        draw_text(image, label_x, baseline + 6, label, MUTED_TEXT, 1);
+    }
+}
+
+// Draws a color key for each name (matching the colors used by the charts) in rows of columns
+// Returns the height used
+pub fn draw_legend(image: &mut RgbImage, x: u32, y: u32, width: u32, names: &[&str]) -> u32 {
+    let columns = (width / LEGEND_COLUMN_WIDTH).max(1);
+    let mut rows = 0;
+    for (i, (name, color)) in (0..).zip(names.iter().zip(PALETTE.iter().cycle())) {
+        let item_x = x + (i % columns) * LEGEND_COLUMN_WIDTH;
+        let item_y = y + (i / columns) * LEGEND_ROW_HEIGHT;
+        fill_rect(image, item_x, item_y, GLYPH_SIZE, GLYPH_SIZE, *color);
+        draw_text(
+            image,
+            item_x + GLYPH_SIZE + 6,
+            item_y,
+            &truncate_text(name, LEGEND_COLUMN_WIDTH - GLYPH_SIZE - 12, 1),
+            TEXT,
+            1,
+        );
+        rows = i / columns + 1;
+    }
+    rows * LEGEND_ROW_HEIGHT
+}
+
+// Number of rows draw_legend will use for count names
+pub fn legend_rows(width: u32, count: u32) -> u32 {
+    count.div_ceil((width / LEGEND_COLUMN_WIDTH).max(1))
+}
+
+pub fn new_image(width: u32, height: u32) -> RgbImage {
+    RgbImage::from_pixel(width, height, BACKGROUND)
+}
+
+pub fn encode_png(image: &RgbImage) -> ImageResult<Vec<u8>> {
+    let mut png = Vec::new();
+    PngEncoder::new(&mut png).write_image(
+        image.as_raw(),
+        image.width(),
+        image.height(),
+        image::ExtendedColorType::Rgb8,
+    )?;
+    Ok(png)
+}
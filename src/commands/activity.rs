@@ -0,0 +1,274 @@
+use crate::commands::timezone::user_timezone;
+use crate::error::CommandResult;
+use crate::model::DB;
+use crate::util;
+use chrono::{Duration, prelude::*};
+use serenity::all::{CommandDataOptionValue, CommandInteraction};
+use serenity::builder::EditInteractionResponse;
+use serenity::client::Context;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+// How far back to look when no duration is given
+const DEFAULT_DAYS: i64 = 30;
+// Only the most recent days get a bar in the per day sparkline, so it fits on one line
+const MAX_SPARKLINE_DAYS: usize = 60;
+// Length of the window of hours reported as when the user is usually online
+const USUAL_HOURS: usize = 4;
+const SPARKS: [char; 8] = [
+    '\u{2581}', '\u{2582}', '\u{2583}', '\u{2584}', '\u{2585}', '\u{2586}', '\u{2587}', '\u{2588}',
+];
+
+// Replies with how much a user has been online, when they're usually online, their longest absence, and a sparkline of time online per day
+// Times are shown in the invoking user's timezone (see /timezone)
+// Takes one required and one optional argument
+// user: user to show activity for
+// duration: human readable time duration (2 weeks, 3 months, etc) to limit results to, defaults to 30 days
+pub async fn activity(ctx: &Context, interaction: &CommandInteraction) -> CommandResult {
+    let Some(guild_id) = interaction.guild_id else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Command can only be used in a server"),
+            )
+            .await?;
+        return Ok(());
+    };
+    let Some(user_id) = interaction
+        .data
+        .options
+        .iter()
+        .find(|o| o.name == "user")
+        .and_then(|o| {
+            if let CommandDataOptionValue::User(u) = o.value {
+                Some(u)
+            } else {
+                None
+            }
+        })
+    else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Unable to find user"),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    let now = Utc::now();
+    let mut start_date = now - Duration::days(DEFAULT_DAYS);
+    if let Some(o) = interaction
+        .data
+        .options
+        .iter()
+        .find(|o| o.name == "duration")
+        && let CommandDataOptionValue::String(d) = &o.value
+    {
+        let Some(date) = util::start_date_from_duration(d.trim(), now)? else {
+            interaction
+                .edit_response(
+                    &ctx.http,
+                    EditInteractionResponse::new().content("```Unable to parse time```"),
+                )
+                .await?;
+            return Ok(());
+        };
+        start_date = date;
+    }
+
+    let members = util::collect_members_guild_id(ctx, guild_id).await?;
+    let name = util::get_username_userid(&ctx.http, &members, user_id).await;
+    let tz = user_timezone(ctx, interaction.user.id).await?;
+    let db = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        data.get::<DB>().unwrap().clone()
+    };
+
+    // sessions are clipped to the range, sessions that haven't ended yet end now
+    #[allow(clippy::panic)]
+    let totals = sqlx::query!(
+        r#"
+SELECT status::text AS "status!",
+  (SUM(EXTRACT(EPOCH FROM LEAST(COALESCE(end_date, $3), $3) - GREATEST(start_date, $2))) / 3600)::float8 AS "hours!"
+FROM online_session
+WHERE user_id = $1
+AND COALESCE(end_date, $3) > $2
+GROUP BY 1"#,
+        i64::from(user_id),
+        start_date,
+        now
+    )
+    .fetch_all(&db)
+    .await?;
+    if totals.is_empty() {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content(format!(
+                    "```{name} hasn't been online since {}```",
+                    start_date.with_timezone(&tz).format("%b %d, %Y")
+                )),
+            )
+            .await?;
+        return Ok(());
+    }
+    let totals: HashMap<String, f64> = totals.into_iter().map(|r| (r.status, r.hours)).collect();
+
+    // time online is split across each hour it overlaps, then grouped by hour of day and by day
+    #[allow(clippy::panic)]
+    let hours = sqlx::query!(
+        r#"
+WITH online AS (
+  SELECT GREATEST(start_date, $2) AS start_date, LEAST(COALESCE(end_date, $3), $3) AS end_date
+  FROM online_session
+  WHERE user_id = $1
+  AND COALESCE(end_date, $3) > $2
+), hours AS (
+  SELECT h, GREATEST(o.start_date, h) AS s, LEAST(o.end_date, h + interval '1 hour') AS e
+  FROM online o, generate_series(date_trunc('hour', o.start_date), o.end_date, interval '1 hour') AS h
+)
+SELECT (h AT TIME ZONE $4)::date AS "day!",
+  EXTRACT(HOUR FROM h AT TIME ZONE $4)::int4 AS "hour!",
+  (SUM(EXTRACT(EPOCH FROM e - s)) / 3600)::float8 AS "hours!"
+FROM hours
+WHERE e > s
+GROUP BY 1, 2"#,
+        i64::from(user_id),
+        start_date,
+        now,
+        tz.name()
+    )
+    .fetch_all(&db)
+    .await?;
+    let mut by_hour = [0.0; 24];
+    let mut by_day: HashMap<NaiveDate, f64> = HashMap::new();
+    for row in hours {
+        if let Some(hour) = usize::try_from(row.hour)
+            .ok()
+            .and_then(|h| by_hour.get_mut(h))
+        {
+            *hour += row.hours;
+        }
+        *by_day.entry(row.day).or_default() += row.hours;
+    }
+
+    // the longest time between sessions, including the time since the last one if it's over
+    #[allow(clippy::panic)]
+    let absence = sqlx::query!(
+        r#"
+WITH gaps AS (
+  SELECT LAG(end_date) OVER (ORDER BY start_date) AS gap_start, start_date AS gap_end
+  FROM online_session
+  WHERE user_id = $1
+  UNION ALL
+  SELECT max(end_date), $3
+  FROM online_session
+  WHERE user_id = $1
+  HAVING bool_and(end_date IS NOT NULL)
+)
+SELECT GREATEST(gap_start, $2) AS "start!", gap_end AS "end!"
+FROM gaps
+WHERE gap_end > $2
+AND gap_end > gap_start
+ORDER BY gap_end - GREATEST(gap_start, $2) DESC
+LIMIT 1"#,
+        i64::from(user_id),
+        start_date,
+        now
+    )
+    .fetch_optional(&db)
+    .await?;
+
+    let mut content = format!(
+        "```Activity of {name} since {} ({})\n\n",
+        start_date.with_timezone(&tz).format("%b %d, %Y"),
+        tz.name()
+    );
+    for (status, label) in [
+        ("online", "Online"),
+        ("idle", "Idle"),
+        ("dnd", "Do not disturb"),
+    ] {
+        writeln!(
+            content,
+            "{label:>14} \u{2014} {:.1} hours",
+            totals.get(status).copied().unwrap_or_default()
+        )?;
+    }
+
+    // the window of hours of the day with the most time online, wrapping past midnight
+    let usual_start = (0..24)
+        .max_by(|a, b| {
+            let total = |start: usize| -> f64 {
+                (start..start + USUAL_HOURS).map(|h| by_hour[h % 24]).sum()
+            };
+            total(*a).total_cmp(&total(*b))
+        })
+        .unwrap_or_default();
+    writeln!(
+        content,
+        "\nUsually online {usual_start:02}:00\u{2013}{:02}:00",
+        (usual_start + USUAL_HOURS) % 24
+    )?;
+    writeln!(content, "Hour of day  {}", sparkline(&by_hour))?;
+    writeln!(content, "             0     6     12    18")?;
+
+    let today = now.with_timezone(&tz).date_naive();
+    let first_day = start_date.with_timezone(&tz).date_naive();
+    let days: Vec<f64> = first_day
+        .iter_days()
+        .take_while(|d| *d <= today)
+        .map(|d| by_day.get(&d).copied().unwrap_or_default())
+        .collect();
+    let shown_days = &days[days.len().saturating_sub(MAX_SPARKLINE_DAYS)..];
+    writeln!(
+        content,
+        "Last {} days {}",
+        shown_days.len(),
+        sparkline(shown_days)
+    )?;
+
+    if let Some(absence) = absence {
+        let length = absence.end - absence.start;
+        let length = if length.num_hours() < 48 {
+            format!("{} hours", length.num_hours())
+        } else {
+            format!("{} days", length.num_days())
+        };
+        writeln!(
+            content,
+            "\nLongest absence: {length}, from {}",
+            absence.start.with_timezone(&tz).format("%b %d, %Y")
+        )?;
+    }
+    content.push_str("```");
+
+    interaction
+        .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+        .await?;
+
+    Ok(())
+}
+
+// Draws values as a line of bars scaled to the largest value, values of zero are left blank
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+fn sparkline(values: &[f64]) -> String {
+    let max = values.iter().copied().fold(0.0, f64::max);
+    values
+        .iter()
+        .map(|v| {
+            if *v <= 0.0 || max <= 0.0 {
+                ' '
+            } else {
+                let i = ((v / max) * (SPARKS.len() - 1) as f64).round() as usize;
+                SPARKS[i.min(SPARKS.len() - 1)]
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,146 @@
+use crate::digest;
+use crate::error::CommandResult;
+use crate::model::DB;
+use crate::util;
+use chrono::Utc;
+use serenity::all::{CommandDataOption, CommandDataOptionValue, CommandInteraction};
+use serenity::builder::EditInteractionResponse;
+use serenity::client::Context;
+
+// Posts the weekly digest to a channel, admin only
+// Takes a single required argument of the channel to post in
+pub async fn channel(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    options: &[CommandDataOption],
+) -> CommandResult {
+    let Some(guild_id) = interaction.guild_id else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Command can only be used in a server"),
+            )
+            .await?;
+        return Ok(());
+    };
+    if !util::is_guild_admin(interaction) {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new()
+                    .content("Only server admins can change where the digest is posted"),
+            )
+            .await?;
+        return Ok(());
+    }
+    let Some(channel_id) = options.iter().find(|o| o.name == "channel").and_then(|o| {
+        if let CommandDataOptionValue::Channel(c) = o.value {
+            Some(c)
+        } else {
+            None
+        }
+    }) else {
+        return Err("Missing required arguments".into());
+    };
+
+    {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        let db = data.get::<DB>().unwrap();
+        // the digest of the week that just ended is posted right away, later ones once each week ends
+        #[allow(clippy::panic)]
+        sqlx::query!(
+            "INSERT INTO digest_config(guild_id, channel_id) VALUES ($1, $2)
+             ON CONFLICT ON CONSTRAINT digest_config_pkey DO UPDATE SET channel_id = $2",
+            i64::from(guild_id),
+            i64::from(channel_id)
+        )
+        .execute(db)
+        .await?;
+    }
+
+    interaction
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new().content(format!(
+                "The weekly digest will be posted in <#{channel_id}> every Monday"
+            )),
+        )
+        .await?;
+
+    Ok(())
+}
+
+// Stops posting the weekly digest, admin only
+pub async fn disable(ctx: &Context, interaction: &CommandInteraction) -> CommandResult {
+    let Some(guild_id) = interaction.guild_id else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Command can only be used in a server"),
+            )
+            .await?;
+        return Ok(());
+    };
+    if !util::is_guild_admin(interaction) {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Only server admins can disable the digest"),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let deleted = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        let db = data.get::<DB>().unwrap();
+        #[allow(clippy::panic)]
+        sqlx::query!(
+            "DELETE FROM digest_config WHERE guild_id = $1",
+            i64::from(guild_id)
+        )
+        .execute(db)
+        .await?
+        .rows_affected()
+    };
+
+    let content = if deleted > 0 {
+        "The weekly digest will no longer be posted"
+    } else {
+        "The weekly digest isn't being posted"
+    };
+    interaction
+        .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+        .await?;
+
+    Ok(())
+}
+
+// Replies with the digest of the last full week
+pub async fn preview(ctx: &Context, interaction: &CommandInteraction) -> CommandResult {
+    let Some(guild_id) = interaction.guild_id else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Command can only be used in a server"),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    let db = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        data.get::<DB>().unwrap().clone()
+    };
+    let embed =
+        digest::build_digest(&ctx.http, &db, guild_id, digest::week_start(Utc::now())).await?;
+
+    interaction
+        .edit_response(&ctx.http, EditInteractionResponse::new().embed(embed))
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,402 @@
+use crate::error::{CommandError, CommandResult};
+use crate::model::DB;
+use crate::paginate::{self, Page, Paginate};
+use crate::util;
+use serde::{Deserialize, Serialize};
+use serenity::all::{CommandDataOption, CommandInteraction, GuildId};
+use serenity::async_trait;
+use serenity::builder::EditInteractionResponse;
+use serenity::client::Context;
+use std::collections::HashSet;
+
+const PAGE_SIZE: i64 = 20;
+// Most suggestions shown by /gamealias suggest
+const MAX_SUGGESTIONS: usize = 15;
+// Only the most seen names are compared for suggestions, comparing every pair of names gets slow
+const MAX_SUGGESTION_NAMES: i64 = 500;
+// How similar two names have to be after normalizing to be suggested as duplicates, from 0 to 1
+const SIMILARITY_THRESHOLD: f64 = 0.85;
+
+#[derive(Serialize, Deserialize)]
+pub struct GameAliasPages {
+    guild_id: GuildId,
+}
+
+#[async_trait]
+impl Paginate for GameAliasPages {
+    const KIND: &'static str = "gamealias";
+
+    async fn render(&self, ctx: &Context, page: usize) -> Result<Page, CommandError> {
+        gen_alias_message(ctx, self.guild_id, i64::try_from(page)? * PAGE_SIZE).await
+    }
+}
+
+// Makes playtime of one game name count towards another in the guild, admin only
+// Takes two required arguments
+// alias: name of the game as reported by Discord that should be merged
+// game: canonical name to merge it into
+pub async fn add(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    options: &[CommandDataOption],
+) -> CommandResult {
+    let Some(guild_id) = interaction.guild_id else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Command can only be used in a server"),
+            )
+            .await?;
+        return Ok(());
+    };
+    if !util::is_guild_admin(interaction) {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Only server admins can merge games"),
+            )
+            .await?;
+        return Ok(());
+    }
+    let alias = util::get_string_option(options, "alias")
+        .unwrap_or_default()
+        .trim();
+    let game = util::get_string_option(options, "game")
+        .unwrap_or_default()
+        .trim();
+    if alias.is_empty() || game.is_empty() {
+        return Err("Missing required arguments".into());
+    }
+
+    let db = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        data.get::<DB>().unwrap().clone()
+    };
+    let mut tx = db.begin().await?;
+    // merge into the canonical name if game is itself an alias, so aliases never point at other aliases
+    #[allow(clippy::panic)]
+    let game = sqlx::query!(
+        "SELECT game FROM game_alias WHERE guild_id = $1 AND alias = $2",
+        i64::from(guild_id),
+        game
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .map_or_else(|| String::from(game), |r| r.game);
+    if game == alias {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new()
+                    .content(format!("`{alias}` is already the name used for that game")),
+            )
+            .await?;
+        return Ok(());
+    }
+    #[allow(clippy::panic)]
+    sqlx::query!(
+        "INSERT INTO game_alias(guild_id, alias, game, author_id) VALUES ($1, $2, $3, $4)
+         ON CONFLICT ON CONSTRAINT game_alias_pkey DO UPDATE SET game = $3, author_id = $4, create_date = now()",
+        i64::from(guild_id),
+        alias,
+        game,
+        i64::from(interaction.user.id)
+    )
+    .execute(&mut *tx)
+    .await?;
+    // anything that was merged into alias now belongs to game
+    #[allow(clippy::panic)]
+    sqlx::query!(
+        "UPDATE game_alias SET game = $3 WHERE guild_id = $1 AND game = $2",
+        i64::from(guild_id),
+        alias,
+        game
+    )
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    interaction
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new()
+                .content(format!("Playtime of `{alias}` now counts as `{game}`")),
+        )
+        .await?;
+
+    Ok(())
+}
+
+// Stops merging a game name into another in the guild, admin only
+// Takes a single required argument of the alias to remove
+pub async fn remove(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    options: &[CommandDataOption],
+) -> CommandResult {
+    let Some(guild_id) = interaction.guild_id else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Command can only be used in a server"),
+            )
+            .await?;
+        return Ok(());
+    };
+    if !util::is_guild_admin(interaction) {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Only server admins can unmerge games"),
+            )
+            .await?;
+        return Ok(());
+    }
+    let alias = util::get_string_option(options, "alias")
+        .unwrap_or_default()
+        .trim();
+
+    let row = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        let db = data.get::<DB>().unwrap();
+        #[allow(clippy::panic)]
+        sqlx::query!(
+            "DELETE FROM game_alias WHERE guild_id = $1 AND alias = $2 RETURNING game",
+            i64::from(guild_id),
+            alias
+        )
+        .fetch_optional(db)
+        .await?
+    };
+
+    let content = match row {
+        Some(row) => format!("Playtime of `{alias}` no longer counts as `{}`", row.game),
+        None => format!("`{alias}` isn't merged into any game"),
+    };
+    interaction
+        .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+        .await?;
+
+    Ok(())
+}
+
+// Replies with a paged list of every alias in the guild and the game it's merged into
+pub async fn list(ctx: &Context, interaction: &CommandInteraction) -> CommandResult {
+    let Some(guild_id) = interaction.guild_id else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Command can only be used in a server"),
+            )
+            .await?;
+        return Ok(());
+    };
+    paginate::send(ctx, interaction, &GameAliasPages { guild_id }, false).await
+}
+
+async fn gen_alias_message(
+    ctx: &Context,
+    guild_id: GuildId,
+    offset: i64,
+) -> Result<Page, CommandError> {
+    let rows = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        let db = data.get::<DB>().unwrap();
+        #[allow(clippy::panic)]
+        sqlx::query!(
+            r#"SELECT alias, game, count(*) OVER () AS "total!" FROM game_alias WHERE guild_id = $1 ORDER BY game, alias LIMIT $2 OFFSET $3"#,
+            i64::from(guild_id),
+            PAGE_SIZE,
+            offset
+        )
+        .fetch_all(db)
+        .await?
+    };
+
+    let Some(total) = rows.first().map(|r| r.total) else {
+        return Ok(Page {
+            content: String::from(
+                "No games have been merged yet, an admin can merge them with `/gamealias add`",
+            ),
+            page_count: 1,
+        });
+    };
+
+    let lines: Vec<String> = rows
+        .into_iter()
+        .map(|r| format!("{} \u{2192} {}\n", r.alias, r.game))
+        .collect();
+    let page_count = usize::try_from((total + PAGE_SIZE - 1) / PAGE_SIZE)?;
+    Ok(Page {
+        content: format!(
+            "```Merged games - Page {}/{page_count}\n\n{}```",
+            offset / PAGE_SIZE + 1,
+            lines.concat()
+        ),
+        page_count,
+    })
+}
+
+// Replies with pairs of game names members of the guild have played that are likely the same game
+// Names are compared after lowercasing and removing punctuation, bracketed text, and launcher names
+pub async fn suggest(ctx: &Context, interaction: &CommandInteraction) -> CommandResult {
+    let Some(guild_id) = interaction.guild_id else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Command can only be used in a server"),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    let members = util::collect_members_guild_id(ctx, guild_id).await?;
+    let user_ids: Vec<i64> = members.keys().map(|id| i64::from(*id)).collect();
+    let rows = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        let db = data.get::<DB>().unwrap();
+        #[allow(clippy::panic)]
+        sqlx::query!(
+            r#"
+SELECT COALESCE(a.game, p.game_name) AS "game!", count(*) AS "seen!"
+FROM user_presence p
+LEFT JOIN game_alias a ON a.guild_id = $3 AND a.alias = p.game_name
+WHERE p.user_id = ANY($1)
+AND p.game_name IS NOT NULL
+GROUP BY 1
+ORDER BY 2 DESC, 1
+LIMIT $2"#,
+            &user_ids,
+            MAX_SUGGESTION_NAMES,
+            i64::from(guild_id)
+        )
+        .fetch_all(db)
+        .await?
+    };
+
+    // names are ordered by how often they've been seen, so the later name of a pair is suggested as the alias
+    let names: Vec<(String, Vec<char>)> = rows
+        .into_iter()
+        .map(|r| {
+            let normalized = normalize_game_name(&r.game).chars().collect();
+            (r.game, normalized)
+        })
+        .collect();
+    let mut suggestions = vec![];
+    let mut suggested = HashSet::new();
+    'outer: for (i, (game, game_normalized)) in names.iter().enumerate() {
+        for (alias, alias_normalized) in &names[i + 1..] {
+            if suggested.contains(alias) {
+                continue;
+            }
+            if similarity(game_normalized, alias_normalized) >= SIMILARITY_THRESHOLD {
+                suggestions.push(format!("{alias} \u{2192} {game}\n"));
+                suggested.insert(alias);
+                if suggestions.len() >= MAX_SUGGESTIONS {
+                    break 'outer;
+                }
+            }
+        }
+    }
+
+    let content = if suggestions.is_empty() {
+        String::from("```No likely duplicate games found```")
+    } else {
+        format!(
+            "```Likely duplicate games, merge them with /gamealias add\n\n{}```",
+            suggestions.concat()
+        )
+    };
+    interaction
+        .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+        .await?;
+
+    Ok(())
+}
+
+// Lowercases name and removes anything that commonly differs between reports of the same game
+fn normalize_game_name(name: &str) -> String {
+    let mut normalized = String::with_capacity(name.len());
+    let mut depth = 0_u32;
+    for c in name.chars() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = depth.saturating_sub(1),
+            c if depth == 0 && c.is_alphanumeric() => normalized.extend(c.to_lowercase()),
+            _ => {}
+        }
+    }
+    for suffix in ["steam", "epicgames", "battlenet", "launcher", "beta"] {
+        if let Some(stripped) = normalized.strip_suffix(suffix)
+            && !stripped.is_empty()
+        {
+            normalized.truncate(stripped.len());
+        }
+    }
+    normalized
+}
+
+// Similarity of a and b from 0 (nothing in common) to 1 (equal), based on their Levenshtein distance
+#[allow(clippy::cast_precision_loss)]
+fn similarity(a: &[char], b: &[char]) -> f64 {
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    // skip the distance calculation when the lengths alone rule out a match
+    if (a.len().abs_diff(b.len()) as f64) > (1.0 - SIMILARITY_THRESHOLD) * longest as f64 {
+        return 0.0;
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    1.0 - previous[b.len()] as f64 / longest as f64
+}
+
+// Suggests game names as Discord reported them, along with existing aliases, as (name, value) pairs
+pub async fn alias_autocomplete(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    value: &str,
+) -> Result<Vec<(String, String)>, CommandError> {
+    let Some(guild_id) = interaction.guild_id else {
+        return Ok(vec![]);
+    };
+
+    let members = util::collect_members_guild_id(ctx, guild_id).await?;
+    let user_ids: Vec<i64> = members.keys().map(|id| i64::from(*id)).collect();
+    let data = ctx.data.read().await;
+    #[allow(clippy::unwrap_used)]
+    let db = data.get::<DB>().unwrap();
+    #[allow(clippy::panic)]
+    let rows = sqlx::query!(
+        r#"
+SELECT name AS "name!" FROM (
+  SELECT game_name AS name FROM user_presence WHERE user_id = ANY($1) AND game_name IS NOT NULL
+  UNION
+  SELECT alias AS name FROM game_alias WHERE guild_id = $3
+) AS names
+WHERE strpos(lower(name), $2) > 0
+ORDER BY 1
+LIMIT 25"#,
+        &user_ids,
+        value.to_lowercase(),
+        i64::from(guild_id)
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.into_iter().map(|r| (r.name.clone(), r.name)).collect())
+}
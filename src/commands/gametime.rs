@@ -0,0 +1,252 @@
+use crate::commands::timezone::user_timezone;
+use crate::error::{CommandError, CommandResult};
+use crate::model::DB;
+use crate::paginate::{self, Page, Paginate};
+use crate::util;
+use chrono::prelude::*;
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use serenity::all::{CommandDataOptionValue, CommandInteraction};
+use serenity::async_trait;
+use serenity::builder::EditInteractionResponse;
+use serenity::client::Context;
+use serenity::model::id::{GuildId, UserId};
+
+const PAGE_SIZE: i64 = 10;
+
+// Everything needed to generate a page of a game's leaderboard
+#[derive(Serialize, Deserialize)]
+pub struct GametimePages {
+    guild_id: GuildId,
+    user_ids: Vec<i64>,
+    game: String,
+    start_date: Option<DateTime<Utc>>,
+    end_date: DateTime<Utc>,
+    // name of the invoking user's timezone, used to show first and last played dates
+    timezone: String,
+}
+
+#[async_trait]
+impl Paginate for GametimePages {
+    const KIND: &'static str = "gametime";
+
+    async fn render(&self, ctx: &Context, page: usize) -> Result<Page, CommandError> {
+        gen_gametime_message(ctx, self, i64::try_from(page)? * PAGE_SIZE).await
+    }
+}
+
+// Replies with a paged list of members ranked by time spent playing a game
+// Takes two arguments
+// game (required): name of the game, as shown in the member's status
+// duration (optional): human readable time duration (2 days, 1 hour, 3 months, etc) to limit results to
+pub async fn gametime(ctx: &Context, interaction: &CommandInteraction) -> CommandResult {
+    let Some(guild_id) = interaction.guild_id else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Command can only be used in a server"),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    let Some(CommandDataOptionValue::String(game)) = interaction
+        .data
+        .options
+        .iter()
+        .find(|o| o.name == "game")
+        .map(|o| &o.value)
+    else {
+        return Err("Missing required arguments".into());
+    };
+
+    let now = Utc::now();
+    let mut start_date = None;
+    if let Some(o) = interaction
+        .data
+        .options
+        .iter()
+        .find(|o| o.name == "duration")
+        && let CommandDataOptionValue::String(d) = &o.value
+    {
+        start_date = util::start_date_from_duration(d.trim(), now)?;
+        if start_date.is_none() {
+            interaction
+                .edit_response(
+                    &ctx.http,
+                    EditInteractionResponse::new().content("```Unable to parse time```"),
+                )
+                .await?;
+            return Ok(());
+        }
+    }
+
+    let members = util::collect_members_guild_id(ctx, guild_id).await?;
+    let pages = GametimePages {
+        guild_id,
+        user_ids: members
+            .values()
+            .filter(|m| !m.user.bot)
+            .map(|m| i64::from(m.user.id))
+            .collect(),
+        game: String::from(game.trim()),
+        start_date,
+        end_date: now,
+        timezone: String::from(user_timezone(ctx, interaction.user.id).await?.name()),
+    };
+
+    paginate::send(ctx, interaction, &pages, false).await
+}
+
+// Generates the page of the game's leaderboard starting at offset
+async fn gen_gametime_message(
+    ctx: &Context,
+    pages: &GametimePages,
+    offset: i64,
+) -> Result<Page, CommandError> {
+    let db = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        data.get::<DB>().unwrap().clone()
+    };
+
+    // a game_session is recorded for each name Discord reports, so switching to another name of the same game ends one and starts another at the same time
+    // those are merged into one session when counting sessions, along with sessions that started before the window
+    #[allow(clippy::panic)]
+    let rows = sqlx::query!(
+        r#"
+WITH sessions AS (
+  SELECT s.user_id, COALESCE(a.game, s.game_name) AS game, s.start_date, s.end_date,
+    LAG(COALESCE(a.game, s.game_name)) OVER w AS prev_game,
+    LAG(s.end_date) OVER w AS prev_end_date
+  FROM game_session s
+  LEFT JOIN game_alias a ON a.guild_id = $7 AND a.alias = s.game_name
+  WHERE s.user_id = ANY($1)
+  WINDOW w AS (PARTITION BY s.user_id ORDER BY s.start_date)
+), spans AS (
+  SELECT user_id, GREATEST(start_date, $3) AS start_date, LEAST(COALESCE(end_date, $4), $4) AS end_date,
+    prev_game IS DISTINCT FROM game OR prev_end_date IS DISTINCT FROM start_date OR (start_date < $3) IS TRUE AS new_session
+  FROM sessions
+  WHERE game = $2
+  AND (COALESCE(end_date, $4) > $3) IS NOT FALSE
+  AND start_date < $4
+)
+SELECT user_id,
+  (SUM(EXTRACT(EPOCH FROM end_date - start_date)) / 3600)::float8 AS "hours!",
+  min(start_date) AS "first_played!",
+  max(end_date) AS "last_played!",
+  count(*) FILTER (WHERE new_session) AS "sessions!",
+  count(*) OVER () AS "total!"
+FROM spans
+GROUP BY user_id
+ORDER BY 2 DESC, 1
+LIMIT $5 OFFSET $6"#,
+        &pages.user_ids,
+        pages.game,
+        pages.start_date,
+        pages.end_date,
+        PAGE_SIZE,
+        offset,
+        i64::from(pages.guild_id)
+    )
+    .fetch_all(&db)
+    .await?;
+
+    let tz: Tz = pages.timezone.parse().unwrap_or(chrono_tz::UTC);
+    let window = match pages.start_date {
+        Some(start_date) => format!(
+            "since {}",
+            start_date.with_timezone(&tz).format("%b %d, %Y")
+        ),
+        None => String::from("all time"),
+    };
+    let heading = format!("Time played in {}, {window}", pages.game);
+
+    let Some(total) = rows.first().map(|r| r.total) else {
+        return Ok(Page {
+            content: format!("```No recorded playtime for {}, {window}```", pages.game),
+            page_count: 1,
+        });
+    };
+
+    let members = util::collect_members_guild_id(ctx, pages.guild_id).await?;
+    let mut entries = Vec::with_capacity(rows.len());
+    for row in rows {
+        let name = util::get_username_userid(
+            &ctx.http,
+            &members,
+            UserId::new(u64::try_from(row.user_id)?),
+        )
+        .await;
+        entries.push((
+            name,
+            format!("{:.1} hours", row.hours),
+            format!(
+                "{} session{}",
+                row.sessions,
+                if row.sessions == 1 { "" } else { "s" }
+            ),
+            format!(
+                "{} - {}",
+                row.first_played.with_timezone(&tz).format("%b %d, %Y"),
+                row.last_played.with_timezone(&tz).format("%b %d, %Y")
+            ),
+        ));
+    }
+    let longest_name = entries.iter().map(|e| e.0.len()).max().unwrap_or(0);
+    let longest_hours = entries.iter().map(|e| e.1.len()).max().unwrap_or(0);
+    let longest_sessions = entries.iter().map(|e| e.2.len()).max().unwrap_or(0);
+    let rank_width = (offset + PAGE_SIZE).to_string().len();
+    let lines: Vec<String> = entries
+        .iter()
+        .zip(offset + 1..)
+        .map(|((name, hours, sessions, dates), rank)| {
+            format!(
+                "{rank:>rank_width$}. {name:<longest_name$} \u{2014} {hours:>longest_hours$}, {sessions:<longest_sessions$}  {dates}\n"
+            )
+        })
+        .collect();
+
+    let page_count = usize::try_from((total + PAGE_SIZE - 1) / PAGE_SIZE)?;
+    let content = format!(
+        "```{heading} - Page {}/{page_count}\n\n{}```",
+        offset / PAGE_SIZE + 1,
+        lines.concat()
+    );
+
+    Ok(Page {
+        content,
+        page_count,
+    })
+}
+
+// Suggests canonical names of games that members of the guild have been seen playing, as (name, value) pairs
+pub async fn autocomplete(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    value: &str,
+) -> Result<Vec<(String, String)>, CommandError> {
+    let Some(guild_id) = interaction.guild_id else {
+        return Ok(vec![]);
+    };
+
+    let members = util::collect_members_guild_id(ctx, guild_id).await?;
+    let user_ids: Vec<i64> = members.keys().map(|id| i64::from(*id)).collect();
+    let data = ctx.data.read().await;
+    #[allow(clippy::unwrap_used)]
+    let db = data.get::<DB>().unwrap();
+    #[allow(clippy::panic)]
+    let rows = sqlx::query!(
+        r#"SELECT DISTINCT COALESCE(a.game, s.game_name) AS "game_name!" FROM game_session s LEFT JOIN game_alias a ON a.guild_id = $3 AND a.alias = s.game_name WHERE s.user_id = ANY($1) AND strpos(lower(COALESCE(a.game, s.game_name)), $2) > 0 ORDER BY 1 LIMIT 25"#,
+        &user_ids,
+        value.to_lowercase(),
+        i64::from(guild_id)
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| (r.game_name.clone(), r.game_name))
+        .collect())
+}
@@ -0,0 +1,267 @@
+use crate::chart;
+use crate::commands::timezone::user_timezone;
+use crate::error::CommandResult;
+use crate::model::DB;
+use crate::util;
+use chrono::{Duration, prelude::*};
+use image::RgbImage;
+use serenity::all::{CommandDataOptionValue, CommandInteraction, CreateAttachment};
+use serenity::builder::EditInteractionResponse;
+use serenity::client::Context;
+use std::borrow::Cow;
+
+const CELL_WIDTH: u32 = 24;
+const CELL_HEIGHT: u32 = 20;
+const LEFT_MARGIN: u32 = 48;
+const RIGHT_MARGIN: u32 = 16;
+const HEADER_HEIGHT: u32 = 40;
+const PANEL_TITLE_HEIGHT: u32 = 28;
+const PANEL_HEIGHT: u32 = PANEL_TITLE_HEIGHT + CELL_HEIGHT * 7 + 16 + 28;
+const LEGEND_WIDTH: u32 = 160;
+const DAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+// How far back to look when no duration is given
+const DEFAULT_WEEKS: i64 = 4;
+
+// Counts indexed by [day of week (Monday first)][hour of day]
+type Grid = [[f64; 24]; 7];
+
+// Replies with an image of when a user or everyone in the guild is active, by hour of day and day of week
+// Times are shown in the invoking user's timezone (see /timezone)
+// Takes two optional arguments
+// user: user to show activity for
+// duration: human readable time duration (2 weeks, 3 months, etc) to limit results to, defaults to 4 weeks
+pub async fn heatmap(ctx: &Context, interaction: &CommandInteraction) -> CommandResult {
+    let Some(guild_id) = interaction.guild_id else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Command can only be used in a server"),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    let now = Utc::now();
+    let mut start_date = now - Duration::weeks(DEFAULT_WEEKS);
+    if let Some(o) = interaction
+        .data
+        .options
+        .iter()
+        .find(|o| o.name == "duration")
+        && let CommandDataOptionValue::String(d) = &o.value
+    {
+        let Some(date) = util::start_date_from_duration(d.trim(), now)? else {
+            interaction
+                .edit_response(
+                    &ctx.http,
+                    EditInteractionResponse::new().content("```Unable to parse time```"),
+                )
+                .await?;
+            return Ok(());
+        };
+        start_date = date;
+    }
+
+    let members = util::collect_members_guild_id(ctx, guild_id).await?;
+    let (user_ids, name): (Vec<i64>, String) = if let Some(o) =
+        interaction.data.options.iter().find(|o| o.name == "user")
+        && let CommandDataOptionValue::User(user_id) = o.value
+    {
+        (
+            vec![i64::from(user_id)],
+            util::get_username_userid(&ctx.http, &members, user_id).await,
+        )
+    } else {
+        (
+            members
+                .values()
+                .filter(|m| !m.user.bot)
+                .map(|m| i64::from(m.user.id))
+                .collect(),
+            ctx.cache
+                .guild(guild_id)
+                .map_or_else(|| String::from("Server"), |g| g.name.clone()),
+        )
+    };
+
+    let tz = user_timezone(ctx, interaction.user.id).await?;
+
+    let db = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        data.get::<DB>().unwrap().clone()
+    };
+
+    let mut messages: Grid = [[0.0; 24]; 7];
+    #[allow(clippy::panic)]
+    let message_rows = sqlx::query!(
+        r#"
+SELECT EXTRACT(ISODOW FROM create_date AT TIME ZONE $4)::int4 AS "dow!",
+  EXTRACT(HOUR FROM create_date AT TIME ZONE $4)::int4 AS "hour!",
+  count(*)::float8 AS "count!"
+FROM message
+WHERE guild_id = $1
+AND author_id = ANY($2)
+AND create_date > $3
+GROUP BY 1, 2"#,
+        i64::from(guild_id),
+        &user_ids,
+        start_date,
+        tz.name()
+    )
+    .fetch_all(&db)
+    .await?;
+    for row in message_rows {
+        if let Some(cell) = cell(&mut messages, row.dow, row.hour) {
+            *cell = row.count;
+        }
+    }
+
+    // sessions are clipped to the range, sessions that haven't ended yet end now
+    // time spent online is then split across each hour it overlaps
+    let mut online: Grid = [[0.0; 24]; 7];
+    #[allow(clippy::panic)]
+    let online_rows = sqlx::query!(
+        r#"
+WITH online AS (
+  SELECT GREATEST(start_date, $2) AS start_date, LEAST(COALESCE(end_date, $3), $3) AS end_date
+  FROM online_session
+  WHERE user_id = ANY($1)
+  AND start_date < $3
+  AND COALESCE(end_date, $3) > $2
+), hours AS (
+  SELECT h, GREATEST(o.start_date, h) AS s, LEAST(o.end_date, h + interval '1 hour') AS e
+  FROM online o, generate_series(date_trunc('hour', o.start_date), o.end_date, interval '1 hour') AS h
+)
+SELECT EXTRACT(ISODOW FROM h AT TIME ZONE $4)::int4 AS "dow!",
+  EXTRACT(HOUR FROM h AT TIME ZONE $4)::int4 AS "hour!",
+  (SUM(EXTRACT(EPOCH FROM e - s)) / 3600)::float8 AS "hours!"
+FROM hours
+WHERE e > s
+GROUP BY 1, 2"#,
+        &user_ids,
+        start_date,
+        now,
+        tz.name()
+    )
+    .fetch_all(&db)
+    .await?;
+    for row in online_rows {
+        if let Some(cell) = cell(&mut online, row.dow, row.hour) {
+            *cell = row.hours;
+        }
+    }
+
+    let width = LEFT_MARGIN + CELL_WIDTH * 24 + RIGHT_MARGIN;
+    let mut image = chart::new_image(width, HEADER_HEIGHT + PANEL_HEIGHT * 2);
+    chart::draw_text(&mut image, LEFT_MARGIN, 10, &name, chart::TEXT, 2);
+    chart::draw_text(
+        &mut image,
+        LEFT_MARGIN,
+        28,
+        &format!(
+            "{} to {} ({})",
+            start_date.with_timezone(&tz).format("%b %d, %Y"),
+            now.with_timezone(&tz).format("%b %d, %Y"),
+            tz.name()
+        ),
+        chart::MUTED_TEXT,
+        1,
+    );
+    draw_panel(&mut image, HEADER_HEIGHT, "Messages sent", &messages, 0);
+    draw_panel(
+        &mut image,
+        HEADER_HEIGHT + PANEL_HEIGHT,
+        "Hours online",
+        &online,
+        1,
+    );
+
+    interaction
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new().new_attachment(CreateAttachment::bytes(
+                Cow::from(chart::encode_png(&image)?),
+                "heatmap.png",
+            )),
+        )
+        .await?;
+
+    Ok(())
+}
+
+// Returns the cell for an ISO day of week (1 is Monday) and hour, or None if either is out of range
+fn cell(grid: &mut Grid, dow: i32, hour: i32) -> Option<&mut f64> {
+    let day = usize::try_from(dow - 1).ok()?;
+    let hour = usize::try_from(hour).ok()?;
+    grid.get_mut(day)?.get_mut(hour)
+}
+
+// Draws a titled grid of days by hours, with hour labels below and a legend of the color scale
+fn draw_panel(image: &mut RgbImage, top: u32, title: &str, grid: &Grid, precision: usize) {
+    chart::draw_text(image, LEFT_MARGIN, top + 8, title, chart::TEXT, 1);
+
+    let max = grid.iter().flatten().copied().fold(0.0, f64::max);
+    let grid_top = top + PANEL_TITLE_HEIGHT;
+    for (y, (day, row)) in (0..).zip(DAYS.iter().zip(grid)) {
+        let cell_top = grid_top + y * CELL_HEIGHT;
+        chart::draw_text(
+            image,
+            8,
+            cell_top + (CELL_HEIGHT - chart::GLYPH_SIZE) / 2,
+            day,
+            chart::MUTED_TEXT,
+            1,
+        );
+        for (x, value) in (0..).zip(row) {
+            let color = if max > 0.0 {
+                chart::color_scale(value / max)
+            } else {
+                chart::color_scale(0.0)
+            };
+            // leave a 1px gap between cells
+            chart::fill_rect(
+                image,
+                LEFT_MARGIN + x * CELL_WIDTH,
+                cell_top,
+                CELL_WIDTH - 1,
+                CELL_HEIGHT - 1,
+                color,
+            );
+        }
+    }
+
+    let labels_top = grid_top + CELL_HEIGHT * 7 + 4;
+    for hour in (0..24).step_by(3) {
+        chart::draw_text(
+            image,
+            LEFT_MARGIN + hour * CELL_WIDTH + (CELL_WIDTH - chart::text_width("00", 1)) / 2,
+            labels_top,
+            &format!("{hour:02}"),
+            chart::MUTED_TEXT,
+            1,
+        );
+    }
+
+    // legend is right aligned under the grid, with the scale's min and max below each end
+    let legend_top = labels_top + 16;
+    let legend_left = LEFT_MARGIN + CELL_WIDTH * 24 - LEGEND_WIDTH;
+    chart::draw_scale_legend(image, legend_left, legend_top, LEGEND_WIDTH, 8);
+    chart::draw_text(
+        image,
+        legend_left,
+        legend_top + 10,
+        "0",
+        chart::MUTED_TEXT,
+        1,
+    );
+    let max_label = format!("{max:.precision$}");
+    chart::draw_text(
+        image,
+        legend_left + LEGEND_WIDTH - chart::text_width(&max_label, 1).min(LEGEND_WIDTH),
+        legend_top + 10,
+        &max_label,
+        chart::MUTED_TEXT,
+        1,
+    );
+}
@@ -1,61 +1,239 @@
+use crate::chart;
+use crate::commands::leaderboard::{self, Metric};
+use crate::commands::timezone::user_timezone;
 use crate::error::CommandResult;
 use crate::model::DB;
+use crate::season;
 use crate::util;
-use serenity::all::{CommandDataOptionValue, CommandInteraction};
+use chrono::prelude::*;
+use serenity::all::{
+    CommandDataOptionValue, CommandInteraction, CreateAttachment, CreateEmbed, GuildId, UserId,
+};
 use serenity::builder::EditInteractionResponse;
 use serenity::client::Context;
-use serenity::model::id::UserId;
+use std::borrow::Cow;
+
+// How many voters for and against are listed
+const TOP_VOTERS: usize = 3;
+// How many of the most recent reasons are listed
+const RECENT_REASONS: i64 = 5;
+// Discord's limit on the length of an embed field value
+const MAX_FIELD_LENGTH: usize = 1024;
+const CHART_WIDTH: u32 = 800;
+const CHART_HEIGHT: u32 = 300;
+const CHART_LABELS: usize = 5;
 
 // Replies with the top users in guild sorted by highest karma (vote count)
-// Allows a single optional arg of how many users to list, defaults to 5
+// See leaderboard::leaderboard for the window and filtering options
+// If a user is given, replies with their karma history instead
 pub async fn karma(ctx: &Context, interaction: &CommandInteraction) -> CommandResult {
-    let Some(guild_id) = interaction.guild_id else {
-        return Ok(());
-    };
-    let members = util::collect_members_guild_id(ctx, guild_id).await?;
-    let limit: i64 = interaction.data.options.first().map_or(5, |o| {
-        if let CommandDataOptionValue::Integer(l) = o.value {
-            l
-        } else {
-            5
-        }
-    });
+    if let Some(guild_id) = interaction.guild_id
+        && let Some(o) = interaction.data.options.iter().find(|o| o.name == "user")
+        && let CommandDataOptionValue::User(user_id) = o.value
+    {
+        return profile(ctx, interaction, guild_id, user_id).await;
+    }
+    leaderboard::leaderboard(ctx, interaction, Metric::Karma).await
+}
 
-    let rows = {
+// Replies with a user's karma, rank, who votes for and against them, recent reasons, and a chart of their karma over time
+async fn profile(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    guild_id: GuildId,
+    user_id: UserId,
+) -> CommandResult {
+    let members = util::collect_members_guild_id(ctx, guild_id).await?;
+    let name = util::get_username_userid(&ctx.http, &members, user_id).await;
+    let tz = user_timezone(ctx, interaction.user.id).await?;
+    let db = {
         let data = ctx.data.read().await;
         #[allow(clippy::unwrap_used)]
-        let db = data.get::<DB>().unwrap();
+        data.get::<DB>().unwrap().clone()
+    };
+    let (guild_id, user_id) = (i64::from(guild_id), i64::from(user_id));
+
+    #[allow(clippy::panic)]
+    let Some(standing) = sqlx::query!(
+        r#"SELECT karma, (SELECT count(*) + 1 FROM user_karma r WHERE r.guild_id = k.guild_id AND r.karma > k.karma) AS "rank!" FROM user_karma k WHERE guild_id = $1 AND user_id = $2"#,
+        guild_id,
+        user_id
+    )
+    .fetch_optional(&db)
+    .await?
+    else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new()
+                    .content(format!("{name} hasn't been voted on in this server")),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    // karma and rank since the current season started, ranked the same way as the /karma leaderboard, only shown once a season has ended
+    let season = season::current_season(&db, guild_id).await?;
+    let season_standing = if season.start_date.is_some() {
         #[allow(clippy::panic)]
-        sqlx::query!(
-            r"
-SELECT user_id, karma
-FROM user_karma
-WHERE guild_id = $1
-ORDER BY karma DESC
-LIMIT $2",
-            i64::from(guild_id),
-            limit
+        let standing = sqlx::query!(
+            r#"
+WITH karma AS (
+  SELECT votee_id, SUM(CASE WHEN is_upvote THEN 1 ELSE -1 END) AS karma
+  FROM vote
+  WHERE guild_id = $1
+  AND create_date >= $3
+  GROUP BY votee_id
+), own AS (
+  SELECT COALESCE((SELECT karma FROM karma WHERE votee_id = $2), 0) AS karma
+)
+SELECT own.karma AS "karma!", (SELECT count(*) + 1 FROM karma WHERE karma.karma > own.karma) AS "rank!"
+FROM own"#,
+            guild_id,
+            user_id,
+            season.start_date
         )
-        .fetch_all(db)
-        .await?
+        .fetch_one(&db)
+        .await?;
+        Some(standing)
+    } else {
+        None
+    };
+
+    #[allow(clippy::panic)]
+    let voters = sqlx::query!(
+        r#"SELECT voter_id, count(*) FILTER (WHERE is_upvote) AS "up!", count(*) FILTER (WHERE NOT is_upvote) AS "down!" FROM vote WHERE guild_id = $1 AND votee_id = $2 AND voter_id <> votee_id GROUP BY 1"#,
+        guild_id,
+        user_id
+    )
+    .fetch_all(&db)
+    .await?;
+    let mut supporters: Vec<(i64, i64)> = voters
+        .iter()
+        .filter(|v| v.up > 0)
+        .map(|v| (v.voter_id, v.up))
+        .collect();
+    supporters.sort_by_key(|v| std::cmp::Reverse(v.1));
+    let mut detractors: Vec<(i64, i64)> = voters
+        .iter()
+        .filter(|v| v.down > 0)
+        .map(|v| (v.voter_id, v.down))
+        .collect();
+    detractors.sort_by_key(|v| std::cmp::Reverse(v.1));
+    let list_voters = |voters: &[(i64, i64)]| -> String {
+        if voters.is_empty() {
+            return String::from("Nobody");
+        }
+        voters
+            .iter()
+            .take(TOP_VOTERS)
+            .map(|(voter_id, count)| format!("<@{voter_id}> ({count})"))
+            .collect::<Vec<String>>()
+            .join("\n")
     };
 
-    let mut lines = Vec::with_capacity(usize::try_from(limit)?);
+    #[allow(clippy::panic)]
+    let reasons = sqlx::query!(
+        r#"SELECT voter_id, is_upvote, reason AS "reason!", create_date FROM vote WHERE guild_id = $1 AND votee_id = $2 AND reason IS NOT NULL ORDER BY create_date DESC LIMIT $3"#,
+        guild_id,
+        user_id,
+        RECENT_REASONS
+    )
+    .fetch_all(&db)
+    .await?;
+    // reasons can be up to 200 characters, so older ones are left out once they wouldn't fit
+    let mut reasons_field = String::new();
+    for reason in &reasons {
+        let line = format!(
+            "{} from <@{}> for {} ({})\n",
+            if reason.is_upvote { "+1" } else { "-1" },
+            reason.voter_id,
+            reason.reason,
+            reason.create_date.with_timezone(&tz).format("%b %d, %Y")
+        );
+        if reasons_field.chars().count() + line.chars().count() > MAX_FIELD_LENGTH {
+            break;
+        }
+        reasons_field.push_str(&line);
+    }
+    if reasons_field.is_empty() {
+        reasons_field = String::from("No votes with a reason yet");
+    }
+
+    // karma at the end of each day since the first vote
+    #[allow(clippy::panic)]
+    let days = sqlx::query!(
+        r#"SELECT (create_date AT TIME ZONE $3)::date AS "day!", SUM(CASE WHEN is_upvote THEN 1 ELSE -1 END) AS "change!" FROM vote WHERE guild_id = $1 AND votee_id = $2 GROUP BY 1 ORDER BY 1"#,
+        guild_id,
+        user_id,
+        tz.name()
+    )
+    .fetch_all(&db)
+    .await?;
+
+    let mut embed = CreateEmbed::new().title(format!("Karma of {name}")).field(
+        "Karma",
+        standing.karma.to_string(),
+        true,
+    );
+    // user_karma is all time, so its rank only matches the leaderboard until a season ends
+    if let Some(season_standing) = season_standing {
+        embed = embed
+            .field("All-time rank", format!("#{}", standing.rank), true)
+            .field(
+                format!("Season {}", season.number),
+                format!("{} (#{})", season_standing.karma, season_standing.rank),
+                true,
+            );
+    } else {
+        embed = embed.field("Rank", format!("#{}", standing.rank), true);
+    }
+    embed = embed
+        .field("Top supporters", list_voters(&supporters), false)
+        .field("Top detractors", list_voters(&detractors), false)
+        .field("Recent reasons", reasons_field, false);
+    let mut response = EditInteractionResponse::new();
+    if let Some(first_day) = days.first().map(|d| d.day) {
+        let today = Utc::now().with_timezone(&tz).date_naive();
+        let mut changes = days.iter().map(|d| (d.day, d.change)).peekable();
+        let mut karma = 0;
+        let mut dates = vec![];
+        let mut values = vec![];
+        for day in first_day.iter_days().take_while(|d| *d <= today) {
+            while let Some((_, change)) = changes.next_if(|(d, _)| *d <= day) {
+                karma += change;
+            }
+            dates.push(day);
+            #[allow(clippy::cast_precision_loss)]
+            values.push(karma as f64);
+        }
+        let labels: Vec<String> = (0..CHART_LABELS)
+            .map(|i| {
+                let index = (dates.len() - 1) * i / (CHART_LABELS - 1);
+                dates[index].format("%b %d, %Y").to_string()
+            })
+            .collect();
 
-    for row in &rows {
-        let user_id = row.user_id;
-        let karma: i32 = row.karma;
-        #[allow(clippy::cast_sign_loss)]
-        let username =
-            util::get_username_userid(&ctx.http, &members, UserId::new(user_id as u64)).await;
-        lines.push(format!("{username} \u{2014} {karma}\n"));
+        let mut image = chart::new_image(CHART_WIDTH, CHART_HEIGHT);
+        chart::draw_line_chart(
+            &mut image,
+            16,
+            16,
+            CHART_WIDTH - 48,
+            CHART_HEIGHT - 32,
+            &values,
+            &labels,
+            chart::PALETTE[0],
+        );
+        embed = embed.image("attachment://karma.png");
+        response = response.new_attachment(CreateAttachment::bytes(
+            Cow::from(chart::encode_png(&image)?),
+            "karma.png",
+        ));
     }
 
     interaction
-        .edit_response(
-            &ctx.http,
-            EditInteractionResponse::new().content(lines.concat()),
-        )
+        .edit_response(&ctx.http, response.embed(embed))
         .await?;
 
     Ok(())
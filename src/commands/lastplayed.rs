@@ -68,7 +68,7 @@ pub async fn lastplayed(ctx: &Context, interaction: &CommandInteraction) -> Comm
         data.get::<DB>().unwrap().clone()
     };
     #[allow(clippy::panic)]
-    let Some(row) = sqlx::query!(r"SELECT create_date, game_name FROM user_presence WHERE user_id = $1 AND status <> 'offline' AND status <> 'invisible' AND game_name IS NOT NULL ORDER BY create_date DESC LIMIT 1", i64::from(user_id)).fetch_optional(&db).await? else {
+    let Some(row) = sqlx::query!(r#"SELECT s.end_date, COALESCE(a.game, s.game_name) AS "game_name!" FROM game_session s LEFT JOIN game_alias a ON a.guild_id = $2 AND a.alias = s.game_name WHERE s.user_id = $1 ORDER BY s.start_date DESC LIMIT 1"#, i64::from(user_id), i64::from(guild_id)).fetch_optional(&db).await? else {
         interaction
             .edit_response(&ctx.http, EditInteractionResponse::new()
                            .content(
@@ -84,11 +84,9 @@ pub async fn lastplayed(ctx: &Context, interaction: &CommandInteraction) -> Comm
             .await?;
         return Ok(());
     };
-    let start = row.create_date;
-    let game_name = row.game_name.unwrap_or_default();
-    // get row without game_name inserted after the game row to determine when user stopped playing
-    #[allow(clippy::panic)]
-    let Some(end_row) = sqlx::query!(r"SELECT create_date FROM user_presence WHERE user_id = $1 AND game_name IS NULL AND create_date > $2 ORDER BY create_date ASC LIMIT 1", i64::from(user_id), start).fetch_optional(&db).await? else {
+    let game_name = row.game_name;
+    // an open session means the user hasn't stopped playing
+    let Some(stopped_playing) = row.end_date else {
         let content = if let Some(username) = username {
             format!("{username} is currently playing {game_name}")
         } else {
@@ -100,7 +98,6 @@ pub async fn lastplayed(ctx: &Context, interaction: &CommandInteraction) -> Comm
         return Ok(());
     };
     drop(db);
-    let stopped_playing = end_row.create_date;
 
     let now = Local::now().with_timezone(Local::now().offset());
     let since = now.signed_duration_since(stopped_playing);
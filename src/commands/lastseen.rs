@@ -65,8 +65,9 @@ pub async fn lastseen(ctx: &Context, interaction: &CommandInteraction) -> Comman
         let data = ctx.data.read().await;
         #[allow(clippy::unwrap_used)]
         let db = data.get::<DB>().unwrap();
+        // idle doesn't count as being seen, so this is when the user's last online or dnd session ended
         #[allow(clippy::panic)]
-        sqlx::query!(r"SELECT create_date FROM user_presence WHERE user_id = $1 AND (status = 'offline' OR status = 'invisible' OR status = 'idle') AND NOT is_startup ORDER BY create_date DESC LIMIT 1", i64::from(user_id)).fetch_optional(db).await?
+        sqlx::query!(r#"SELECT bool_or(end_date IS NULL) AS "online!", max(end_date) AS last_seen FROM online_session WHERE user_id = $1 AND status IN ('online', 'dnd') HAVING count(*) > 0"#, i64::from(user_id)).fetch_optional(db).await?
     }) else {
         let content = if let Some(username) = username {
             format!("I've never seen {username}")
@@ -78,9 +79,19 @@ pub async fn lastseen(ctx: &Context, interaction: &CommandInteraction) -> Comman
             .await?;
         return Ok(());
     };
+    let (false, Some(last_seen)) = (row.online, row.last_seen) else {
+        let content = if let Some(username) = username {
+            format!("{username} is currently online")
+        } else {
+            "They're currently online".to_string()
+        };
+        interaction
+            .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+            .await?;
+        return Ok(());
+    };
 
     let now = Local::now().with_timezone(Local::now().offset());
-    let last_seen = row.create_date;
     let since = now.signed_duration_since(last_seen);
 
     let since_str = if since.num_seconds() < 1 {
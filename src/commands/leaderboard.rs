@@ -0,0 +1,420 @@
+use crate::error::{CommandError, CommandResult};
+use crate::model::DB;
+use crate::paginate::{self, Page, Paginate};
+use crate::season;
+use crate::util;
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+use serenity::all::{CommandDataOptionValue, CommandInteraction};
+use serenity::async_trait;
+use serenity::builder::EditInteractionResponse;
+use serenity::client::Context;
+use serenity::model::id::{ChannelId, GuildId, UserId};
+
+const DEFAULT_PAGE_SIZE: i16 = 10;
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Metric {
+    Messages,
+    Length,
+    Karma,
+}
+
+impl Metric {
+    fn title(self) -> &'static str {
+        match self {
+            Self::Messages => "Messages sent",
+            Self::Length => "Average words per message",
+            Self::Karma => "Karma",
+        }
+    }
+}
+
+// Everything needed to generate a page of a leaderboard
+#[derive(Serialize, Deserialize)]
+pub struct Leaderboard {
+    pub metric: Metric,
+    pub guild_id: GuildId,
+    // None when ranking the whole guild
+    pub channel_id: Option<ChannelId>,
+    pub start_date: Option<DateTime<Utc>>,
+    pub end_date: DateTime<Utc>,
+    pub include_bots: bool,
+    pub include_commands: bool,
+    pub page_size: i16,
+    // ended karma season to rank, None for the current season
+    #[serde(default)]
+    pub season: Option<i32>,
+}
+
+// The karma season a leaderboard ranks
+struct Season {
+    // None for the current season
+    id: Option<i64>,
+    number: i32,
+    start_date: Option<DateTime<Utc>>,
+    end_date: Option<DateTime<Utc>>,
+}
+
+#[async_trait]
+impl Paginate for Leaderboard {
+    const KIND: &'static str = "leaderboard";
+
+    async fn render(&self, ctx: &Context, page: usize) -> Result<Page, CommandError> {
+        gen_leaderboard_message(ctx, self, i64::try_from(page)? * i64::from(self.page_size)).await
+    }
+}
+
+// Replies with a paged list of members ranked by the given metric
+// Takes optional arguments
+// scope: "channel" (default) or "server", ignored for karma which is always server wide
+// channel: specific channel to rank, overrides scope
+// window: human readable time duration (last 30 days, past week, 3 months, etc) to limit results to
+// season: ended karma season to rank, karma is otherwise ranked within the current season unless a window is given
+// include_bots: whether to rank bots, defaults to false
+// include_commands: whether to count messages that are commands, defaults to false
+// count: how many members to list per page, defaults to 10
+pub async fn leaderboard(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    metric: Metric,
+) -> CommandResult {
+    let Some(guild_id) = interaction.guild_id else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Command can only be used in a server"),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    let now = Utc::now();
+    let mut board = Leaderboard {
+        metric,
+        guild_id,
+        channel_id: (metric != Metric::Karma).then_some(interaction.channel_id),
+        start_date: None,
+        end_date: now,
+        include_bots: false,
+        include_commands: false,
+        page_size: DEFAULT_PAGE_SIZE,
+        season: None,
+    };
+    for option in &interaction.data.options {
+        match (option.name.as_str(), &option.value) {
+            ("count", CommandDataOptionValue::Integer(c)) => {
+                board.page_size = i16::try_from(*c)?;
+            }
+            ("scope", CommandDataOptionValue::String(s))
+                if s == "server"
+                    && !interaction.data.options.iter().any(|o| o.name == "channel") =>
+            {
+                board.channel_id = None;
+            }
+            ("channel", CommandDataOptionValue::Channel(c)) if metric != Metric::Karma => {
+                board.channel_id = Some(*c);
+            }
+            ("window", CommandDataOptionValue::String(w)) => {
+                let w = w.trim();
+                if w.eq_ignore_ascii_case("all") || w.eq_ignore_ascii_case("all time") {
+                    continue;
+                }
+                board.start_date = util::start_date_from_duration(w, now)?;
+                if board.start_date.is_none() {
+                    interaction
+                        .edit_response(
+                            &ctx.http,
+                            EditInteractionResponse::new().content("```Unable to parse time```"),
+                        )
+                        .await?;
+                    return Ok(());
+                }
+            }
+            ("season", CommandDataOptionValue::Integer(n)) if metric == Metric::Karma => {
+                board.season = Some(i32::try_from(*n)?);
+            }
+            ("include_bots", CommandDataOptionValue::Boolean(b)) => board.include_bots = *b,
+            ("include_commands", CommandDataOptionValue::Boolean(b)) => {
+                board.include_commands = *b;
+            }
+            _ => {}
+        }
+    }
+
+    paginate::send(ctx, interaction, &board, false).await
+}
+
+// Generates the page of the leaderboard starting at offset
+// Ranking, filtering, and paging are all done by the database so only the rows on the page are loaded
+async fn gen_leaderboard_message(
+    ctx: &Context,
+    board: &Leaderboard,
+    offset: i64,
+) -> Result<Page, CommandError> {
+    let db = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        data.get::<DB>().unwrap().clone()
+    };
+    let members = util::collect_members_guild_id(ctx, board.guild_id).await?;
+    let excluded_ids: Vec<i64> = if board.include_bots {
+        vec![]
+    } else {
+        members
+            .values()
+            .filter(|m| m.user.bot)
+            .map(|m| i64::from(m.user.id))
+            .collect()
+    };
+    let guild_id = i64::from(board.guild_id);
+    let channel_id = board.channel_id.map(i64::from);
+    let limit = i64::from(board.page_size);
+
+    // karma is ranked within a season unless a window is given
+    let season = if board.metric == Metric::Karma && board.start_date.is_none() {
+        if let Some(number) = board.season {
+            #[allow(clippy::panic)]
+            let Some(season) = sqlx::query!(
+                "SELECT id, start_date, end_date FROM karma_season WHERE guild_id = $1 AND number = $2",
+                guild_id,
+                number
+            )
+            .fetch_optional(&db)
+            .await?
+            else {
+                return Ok(Page {
+                    content: format!("```Season {number} hasn't ended yet```"),
+                    page_count: 1,
+                });
+            };
+            Some(Season {
+                id: Some(season.id),
+                number,
+                start_date: Some(season.start_date),
+                end_date: Some(season.end_date),
+            })
+        } else {
+            let current = season::current_season(&db, guild_id).await?;
+            Some(Season {
+                id: None,
+                number: current.number,
+                start_date: current.start_date,
+                end_date: None,
+            })
+        }
+    } else {
+        None
+    };
+
+    // each row is a user_id, their score, and the total number of ranked users
+    #[allow(clippy::panic)]
+    let rows: Vec<(i64, f64, i64)> = match board.metric {
+        Metric::Messages => sqlx::query!(
+            r#"
+SELECT author_id AS "user_id!", count(*)::float8 AS "value!", count(*) OVER () AS "total!"
+FROM message
+WHERE (($2::bigint IS NULL AND guild_id = $1) OR channel_id = $2)
+AND (create_date > $3) IS NOT FALSE
+AND create_date <= $4
+AND ($5 OR content NOT LIKE '/%')
+AND author_id <> ALL($6)
+GROUP BY author_id
+ORDER BY 2 DESC, 1
+LIMIT $7 OFFSET $8"#,
+            guild_id,
+            channel_id,
+            board.start_date,
+            board.end_date,
+            board.include_commands,
+            &excluded_ids,
+            limit,
+            offset
+        )
+        .fetch_all(&db)
+        .await?
+        .into_iter()
+        .map(|r| (r.user_id, r.value, r.total))
+        .collect(),
+        Metric::Length => sqlx::query!(
+            r#"
+SELECT author_id AS "user_id!", avg(cardinality(string_to_array(content, ' ')))::float8 AS "value!", count(*) OVER () AS "total!"
+FROM message
+WHERE (($2::bigint IS NULL AND guild_id = $1) OR channel_id = $2)
+AND (create_date > $3) IS NOT FALSE
+AND create_date <= $4
+AND ($5 OR content NOT LIKE '/%')
+AND author_id <> ALL($6)
+GROUP BY author_id
+ORDER BY 2 DESC, 1
+LIMIT $7 OFFSET $8"#,
+            guild_id,
+            channel_id,
+            board.start_date,
+            board.end_date,
+            board.include_commands,
+            &excluded_ids,
+            limit,
+            offset
+        )
+        .fetch_all(&db)
+        .await?
+        .into_iter()
+        .map(|r| (r.user_id, r.value, r.total))
+        .collect(),
+        Metric::Karma => match (board.start_date, &season) {
+            // karma within a window has to be summed from individual votes
+            (Some(start_date), _) => sqlx::query!(
+                r#"
+SELECT votee_id AS "user_id!", sum(CASE WHEN is_upvote THEN 1 ELSE -1 END)::float8 AS "value!", count(*) OVER () AS "total!"
+FROM vote
+WHERE guild_id = $1
+AND create_date > $2
+AND create_date <= $3
+AND votee_id <> ALL($4)
+GROUP BY votee_id
+ORDER BY 2 DESC, 1
+LIMIT $5 OFFSET $6"#,
+                guild_id,
+                start_date,
+                board.end_date,
+                &excluded_ids,
+                limit,
+                offset
+            )
+            .fetch_all(&db)
+            .await?
+            .into_iter()
+            .map(|r| (r.user_id, r.value, r.total))
+            .collect(),
+            (None, Some(Season { id: Some(id), .. })) => sqlx::query!(
+                r#"
+SELECT user_id, karma::float8 AS "value!", count(*) OVER () AS "total!"
+FROM karma_season_standing
+WHERE season_id = $1
+AND user_id <> ALL($2)
+ORDER BY 2 DESC, 1
+LIMIT $3 OFFSET $4"#,
+                id,
+                &excluded_ids,
+                limit,
+                offset
+            )
+            .fetch_all(&db)
+            .await?
+            .into_iter()
+            .map(|r| (r.user_id, r.value, r.total))
+            .collect(),
+            (None, season) => sqlx::query!(
+                r#"
+SELECT votee_id AS "user_id!", sum(CASE WHEN is_upvote THEN 1 ELSE -1 END)::float8 AS "value!", count(*) OVER () AS "total!"
+FROM vote
+WHERE guild_id = $1
+AND (create_date >= $2) IS NOT FALSE
+AND votee_id <> ALL($3)
+GROUP BY votee_id
+ORDER BY 2 DESC, 1
+LIMIT $4 OFFSET $5"#,
+                guild_id,
+                season.as_ref().and_then(|s| s.start_date),
+                &excluded_ids,
+                limit,
+                offset
+            )
+            .fetch_all(&db)
+            .await?
+            .into_iter()
+            .map(|r| (r.user_id, r.value, r.total))
+            .collect(),
+        },
+    };
+
+    let scope = match board.channel_id {
+        Some(channel_id) => format!(
+            "#{}",
+            ctx.cache
+                .guild(board.guild_id)
+                .and_then(|g| g.channels.get(&channel_id).map(|c| c.name.clone()))
+                .unwrap_or_else(|| channel_id.to_string())
+        ),
+        None => ctx
+            .cache
+            .guild(board.guild_id)
+            .map_or_else(|| String::from("this server"), |g| g.name.clone()),
+    };
+    let window = match (board.start_date, &season) {
+        (None, Some(season)) if season.start_date.is_some() => {
+            let format_date = |d: Option<DateTime<Utc>>| {
+                d.map(|d| d.with_timezone(&Local).format("%b %d, %Y").to_string())
+                    .unwrap_or_default()
+            };
+            match season.end_date {
+                Some(end_date) => format!(
+                    "season {} ({} \u{2013} {})",
+                    season.number,
+                    format_date(season.start_date),
+                    format_date(Some(end_date))
+                ),
+                None => format!(
+                    "season {}, since {}",
+                    season.number,
+                    format_date(season.start_date)
+                ),
+            }
+        }
+        (Some(start_date), _) => {
+            let time_format_string = if (board.end_date - start_date).num_days() < 1 {
+                "%l:%M%p"
+            } else {
+                "%b %d, %Y"
+            };
+            format!(
+                "since {}",
+                start_date.with_timezone(&Local).format(time_format_string)
+            )
+        }
+        _ => String::from("all time"),
+    };
+    let heading = format!("{} in {scope}, {window}", board.metric.title());
+
+    let Some(total) = rows.first().map(|r| r.2) else {
+        return Ok(Page {
+            content: format!("```No results for {heading}```"),
+            page_count: 1,
+        });
+    };
+
+    let mut entries = Vec::with_capacity(rows.len());
+    for (user_id, value) in rows.into_iter().map(|r| (r.0, r.1)) {
+        let name =
+            util::get_username_userid(&ctx.http, &members, UserId::new(u64::try_from(user_id)?))
+                .await;
+        let value = if board.metric == Metric::Length {
+            format!("{value:.2}")
+        } else {
+            format!("{value:.0}")
+        };
+        entries.push((name, value));
+    }
+    let longest_name = entries.iter().map(|e| e.0.len()).max().unwrap_or(0);
+    let rank_width = (offset + i64::from(board.page_size)).to_string().len();
+    let lines: Vec<String> = entries
+        .iter()
+        .zip(offset + 1..)
+        .map(|((name, value), rank)| {
+            format!("{rank:>rank_width$}. {name:<longest_name$} \u{2014} {value}\n")
+        })
+        .collect();
+
+    let page_count = usize::try_from((total + limit - 1) / limit)?;
+    let content = format!(
+        "```{heading} - Page {}/{page_count}\n\n{}```",
+        offset / limit + 1,
+        lines.concat()
+    );
+
+    Ok(Page {
+        content,
+        page_count,
+    })
+}
@@ -0,0 +1,184 @@
+use crate::error::CommandResult;
+use crate::model::DB;
+use crate::util;
+use chrono::prelude::*;
+use serenity::all::{CommandDataOptionValue, CommandInteraction};
+use serenity::builder::EditInteractionResponse;
+use serenity::client::Context;
+use std::fmt::Write as _;
+
+// How many artists and tracks are listed
+const TOP_COUNT: i64 = 10;
+// Longer artist and track names are cut off to keep the reply under Discord's message length limit
+const MAX_NAME_LENGTH: usize = 40;
+
+// Replies with the most listened to Spotify artists and tracks of a user or everyone in the guild
+// Takes two optional arguments
+// user: user to show listening for
+// duration: human readable time duration (2 days, 1 hour, 3 months, etc) to limit results to
+pub async fn listening(ctx: &Context, interaction: &CommandInteraction) -> CommandResult {
+    let Some(guild_id) = interaction.guild_id else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Command can only be used in a server"),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    let now = Utc::now();
+    let mut start_date = None;
+    if let Some(o) = interaction
+        .data
+        .options
+        .iter()
+        .find(|o| o.name == "duration")
+        && let CommandDataOptionValue::String(d) = &o.value
+    {
+        start_date = util::start_date_from_duration(d.trim(), now)?;
+        if start_date.is_none() {
+            interaction
+                .edit_response(
+                    &ctx.http,
+                    EditInteractionResponse::new().content("```Unable to parse time```"),
+                )
+                .await?;
+            return Ok(());
+        }
+    }
+
+    let members = util::collect_members_guild_id(ctx, guild_id).await?;
+    let (user_ids, name): (Vec<i64>, String) = if let Some(o) =
+        interaction.data.options.iter().find(|o| o.name == "user")
+        && let CommandDataOptionValue::User(user_id) = o.value
+    {
+        (
+            vec![i64::from(user_id)],
+            util::get_username_userid(&ctx.http, &members, user_id).await,
+        )
+    } else {
+        (
+            members
+                .values()
+                .filter(|m| !m.user.bot)
+                .map(|m| i64::from(m.user.id))
+                .collect(),
+            ctx.cache
+                .guild(guild_id)
+                .map_or_else(|| String::from("this server"), |g| g.name.clone()),
+        )
+    };
+
+    let db = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        data.get::<DB>().unwrap().clone()
+    };
+
+    // Spotify reports the track as details and its artists, separated by "; ", as state
+    #[allow(clippy::panic)]
+    let artists = sqlx::query!(
+        r#"
+SELECT artist AS "artist!",
+  (SUM(EXTRACT(EPOCH FROM COALESCE(end_date, $3) - GREATEST(start_date, $2))) / 3600)::float8 AS "hours!",
+  count(*) AS "plays!"
+FROM user_activity, regexp_split_to_table(state, '; ') AS artist
+WHERE user_id = ANY($1)
+AND kind = 'listening'
+AND name = 'Spotify'
+AND (COALESCE(end_date, $3) > $2) IS NOT FALSE
+GROUP BY 1
+ORDER BY 2 DESC, 1
+LIMIT $4"#,
+        &user_ids,
+        start_date,
+        now,
+        TOP_COUNT
+    )
+    .fetch_all(&db)
+    .await?;
+
+    #[allow(clippy::panic)]
+    let tracks = sqlx::query!(
+        r#"
+SELECT details AS "track!", state AS "artist!", count(*) AS "plays!"
+FROM user_activity
+WHERE user_id = ANY($1)
+AND kind = 'listening'
+AND name = 'Spotify'
+AND details IS NOT NULL
+AND state IS NOT NULL
+AND (COALESCE(end_date, $3) > $2) IS NOT FALSE
+GROUP BY 1, 2
+ORDER BY 3 DESC, 1
+LIMIT $4"#,
+        &user_ids,
+        start_date,
+        now,
+        TOP_COUNT
+    )
+    .fetch_all(&db)
+    .await?;
+
+    let window = match start_date {
+        Some(start_date) => format!(
+            "since {}",
+            start_date.with_timezone(&Local).format("%b %d, %Y")
+        ),
+        None => String::from("all time"),
+    };
+    if artists.is_empty() {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content(format!(
+                    "```No recorded Spotify listening for {name}, {window}```"
+                )),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let mut content = format!("```Spotify listening for {name}, {window}\n\nTop artists\n");
+    let longest_artist = artists
+        .iter()
+        .map(|a| shorten(&a.artist).chars().count())
+        .max()
+        .unwrap_or(0);
+    for (rank, artist) in (1..).zip(&artists) {
+        writeln!(
+            content,
+            "{rank:>2}. {:<longest_artist$} \u{2014} {:.1} hours, {} plays",
+            shorten(&artist.artist),
+            artist.hours,
+            artist.plays
+        )?;
+    }
+    content.push_str("\nTop tracks\n");
+    for (rank, track) in (1..).zip(&tracks) {
+        writeln!(
+            content,
+            "{rank:>2}. {} - {} \u{2014} {} plays",
+            shorten(&track.track),
+            shorten(&track.artist),
+            track.plays
+        )?;
+    }
+    content.push_str("```");
+
+    interaction
+        .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+        .await?;
+
+    Ok(())
+}
+
+fn shorten(name: &str) -> String {
+    if name.chars().count() <= MAX_NAME_LENGTH {
+        return String::from(name);
+    }
+    let mut shortened: String = name.chars().take(MAX_NAME_LENGTH - 1).collect();
+    shortened.push('\u{2026}');
+    shortened
+}
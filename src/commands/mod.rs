@@ -1,26 +1,39 @@
+pub mod activity;
 pub mod affixes;
-//pub mod asuh;
 pub mod botinfo;
+pub mod digest;
 pub mod fortune;
+pub mod gamealias;
+pub mod gametime;
+pub mod heatmap;
 pub mod invite;
 pub mod jpg;
 pub mod karma;
 pub mod lastplayed;
 pub mod lastseen;
+pub mod leaderboard;
+pub mod listening;
+pub mod nowplaying;
 pub mod ping;
 pub mod playtime;
 pub mod raiderio;
 pub mod roll;
 pub mod serverinfo;
 pub mod shipping;
+pub mod sound;
 pub mod source;
+pub mod statushistory;
+pub mod subscribe;
 pub mod tarkov;
 pub mod time;
+pub mod timezone;
 pub mod top;
 pub mod topcommand;
 pub mod toplength;
 pub mod userinfo;
+pub mod voicetime;
 pub mod vote;
+pub mod voteconfig;
 pub mod weather;
 pub mod whois;
 pub mod wolframalpha;
@@ -0,0 +1,161 @@
+use crate::error::CommandResult;
+use crate::model::{DB, LastUserPresence};
+use crate::util;
+use chrono::prelude::*;
+use serenity::all::CommandInteraction;
+use serenity::builder::EditInteractionResponse;
+use serenity::client::Context;
+use serenity::model::id::UserId;
+use serenity::model::user::OnlineStatus;
+use serenity::utils::MessageBuilder;
+use std::collections::HashMap;
+
+// Stop listing players before hitting Discord's 2000 character message limit
+const MAX_CONTENT_LENGTH: usize = 1900;
+
+// A member currently playing a game
+struct Player {
+    name: String,
+    since: Option<DateTime<Utc>>,
+    voice: Option<String>,
+}
+
+// Replies with every member of the guild currently playing a game, grouped by game
+// Shows how long each has been playing and the voice channel they're in, if any
+pub async fn nowplaying(ctx: &Context, interaction: &CommandInteraction) -> CommandResult {
+    let Some(guild_id) = interaction.guild_id else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Command can only be used in a server"),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    let members = util::collect_members_guild_id(ctx, guild_id).await?;
+    let last_presence = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        data.get::<LastUserPresence>().unwrap().clone()
+    };
+    let playing: Vec<(UserId, String)> = {
+        let last_presence = last_presence.read().await;
+        members
+            .values()
+            .filter(|m| !m.user.bot)
+            .filter_map(|m| {
+                let presence = last_presence.get(&m.user.id)?;
+                if presence.status == OnlineStatus::Offline
+                    || presence.status == OnlineStatus::Invisible
+                {
+                    return None;
+                }
+                Some((m.user.id, presence.game_name.clone()?))
+            })
+            .collect()
+    };
+    if playing.is_empty() {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Nobody is playing anything right now"),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    // open sessions give when each member started playing and the game's canonical name
+    let user_ids: Vec<i64> = playing.iter().map(|(id, _)| i64::from(*id)).collect();
+    let sessions: HashMap<i64, (String, DateTime<Utc>)> = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        let db = data.get::<DB>().unwrap();
+        #[allow(clippy::panic)]
+        sqlx::query!(
+            r#"SELECT s.user_id, COALESCE(a.game, s.game_name) AS "game!", s.start_date FROM game_session s LEFT JOIN game_alias a ON a.guild_id = $2 AND a.alias = s.game_name WHERE s.user_id = ANY($1) AND s.end_date IS NULL"#,
+            &user_ids,
+            i64::from(guild_id)
+        )
+        .fetch_all(db)
+        .await?
+        .into_iter()
+        .map(|r| (r.user_id, (r.game, r.start_date)))
+        .collect()
+    };
+    let voice_channels: HashMap<UserId, String> = match ctx.cache.guild(guild_id) {
+        Some(g) => g
+            .voice_states
+            .iter()
+            .filter_map(|(user_id, voice_state)| {
+                let channel_id = voice_state.channel_id?;
+                let name = g.channels.get(&channel_id).map_or_else(
+                    || String::from("a voice channel"),
+                    |c| format!("\u{1F50A} {}", c.name),
+                );
+                Some((*user_id, name))
+            })
+            .collect(),
+        None => HashMap::new(),
+    };
+
+    let mut games: HashMap<String, Vec<Player>> = HashMap::new();
+    for (user_id, game_name) in playing {
+        let session = sessions.get(&i64::from(user_id));
+        let game = session.map_or(game_name, |(game, _)| game.clone());
+        games.entry(game).or_default().push(Player {
+            name: util::get_username_userid(&ctx.http, &members, user_id).await,
+            since: session.map(|(_, start_date)| *start_date),
+            voice: voice_channels.get(&user_id).cloned(),
+        });
+    }
+    // games with the most players first, longest playing members first within a game
+    let mut games: Vec<(String, Vec<Player>)> = games.into_iter().collect();
+    games.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then_with(|| a.0.cmp(&b.0)));
+
+    // names are escaped so markdown in them doesn't break the bold game headings
+    // lines are only added while they fit, a game with many players can fill the message on its own
+    let now = Utc::now();
+    let mut content = String::from("**Now playing**\n");
+    'games: for (game, mut players) in games {
+        let heading = MessageBuilder::new()
+            .push("\n")
+            .push_bold_safe(game)
+            .push_line(format!(" ({})", players.len()))
+            .build();
+        if content.len() + heading.len() > MAX_CONTENT_LENGTH {
+            content.push_str("\n\u{2026}");
+            break;
+        }
+        content.push_str(&heading);
+
+        players.sort_by_key(|p| p.since.unwrap_or(now));
+        for player in players {
+            let mut line = MessageBuilder::new();
+            line.push_safe(player.name);
+            if let Some(since) = player.since {
+                let playing_for = now.signed_duration_since(since);
+                line.push(format!(
+                    " \u{2014} {}h {:02}m",
+                    playing_for.num_hours(),
+                    playing_for.num_minutes() % 60
+                ));
+            }
+            if let Some(voice) = player.voice {
+                line.push(" \u{2014} ").push_safe(voice);
+            }
+            let line = line.push("\n").build();
+            if content.len() + line.len() > MAX_CONTENT_LENGTH {
+                content.push('\u{2026}');
+                break 'games;
+            }
+            content.push_str(&line);
+        }
+    }
+
+    interaction
+        .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+        .await?;
+
+    Ok(())
+}
@@ -1,30 +1,62 @@
+use crate::chart;
+use crate::commands::timezone::user_timezone;
 use crate::error::{CommandError, CommandResult};
 use crate::model::DB;
+use crate::paginate::{self, Page, Paginate};
 use crate::util;
 use chrono::{Duration, prelude::*};
-use regex::{Match, Regex};
-use serenity::all::{ButtonStyle, CommandDataOption, CommandDataOptionValue, CommandInteraction};
-use serenity::builder::CreateActionRow;
-use serenity::builder::{CreateButton, EditInteractionResponse};
+use serde::{Deserialize, Serialize};
+use serenity::all::{
+    CommandDataOption, CommandDataOptionValue, CommandInteraction, CreateAttachment,
+};
+use serenity::async_trait;
+use serenity::builder::EditInteractionResponse;
 use serenity::client::Context;
 use serenity::model::id::GuildId;
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::str::FromStr;
 
-struct GameDate {
-    date: DateTime<Utc>,
-    game: String,
+pub const OFFSET_INC: u16 = 15;
+// Number of games shown in the bar chart
+const CHART_BARS: usize = 10;
+// Number of series in the weekly area chart, including one for all other games
+const CHART_SERIES: usize = 8;
+const CHART_WIDTH: u32 = 800;
+const CHART_MARGIN: u32 = 16;
+const AREA_HEIGHT: u32 = 240;
+
+#[derive(Serialize, Deserialize)]
+pub struct PlaytimePages {
+    // aliases of the guild are used to merge games
+    guild_id: GuildId,
+    user_ids: Vec<i64>,
+    username: Option<String>,
+    start_date: Option<DateTime<Utc>>,
+    end_date: DateTime<Utc>,
 }
 
-struct GameTime {
-    time: Duration,
-    game: String,
+#[async_trait]
+impl Paginate for PlaytimePages {
+    const KIND: &'static str = "playtime";
+
+    async fn render(&self, ctx: &Context, page: usize) -> Result<Page, CommandError> {
+        gen_playtime_message(
+            ctx,
+            self.guild_id,
+            &self.user_ids,
+            self.username.as_ref(),
+            self.start_date,
+            self.end_date,
+            page * usize::from(OFFSET_INC),
+        )
+        .await
+    }
 }
 
-pub const OFFSET_INC: u16 = 15;
-
 // Replies to msg with the cumulative playtime of all users in the guild
-// Takes a single optional argument of a username to filter playtime for
+// Takes two optional arguments
+// user: user to filter playtime for
+// chart: if true, replies with an image of charts instead of a table
 pub async fn playtime(ctx: &Context, interaction: &CommandInteraction) -> CommandResult {
     let Some(guild_id) = interaction.guild_id else {
         interaction
@@ -36,22 +68,45 @@ pub async fn playtime(ctx: &Context, interaction: &CommandInteraction) -> Comman
         return Ok(());
     };
 
-    let (user_ids, username): (Vec<i64>, Option<String>) =
-        match user_ids_and_name_from_option(ctx, guild_id, interaction.data.options.first()).await?
-        {
-            Some(u) => (u.0, u.1),
-            None => return Ok(()),
-        };
+    let (user_ids, username): (Vec<i64>, Option<String>) = match user_ids_and_name_from_option(
+        ctx,
+        guild_id,
+        interaction.data.options.iter().find(|o| o.name == "user"),
+    )
+    .await?
+    {
+        Some(u) => (u.0, u.1),
+        None => return Ok(()),
+    };
 
-    send_message_with_buttons(ctx, interaction, &user_ids, username.as_ref(), None).await?;
+    if chart_option(interaction) {
+        return send_chart(
+            ctx,
+            interaction,
+            guild_id,
+            &user_ids,
+            username.as_ref(),
+            None,
+            Utc::now(),
+        )
+        .await;
+    }
 
-    Ok(())
+    let pages = PlaytimePages {
+        guild_id,
+        user_ids,
+        username,
+        start_date: None,
+        end_date: Utc::now(),
+    };
+    paginate::send(ctx, interaction, &pages, false).await
 }
 
 // Replies to msg with the cumulative playtime since the given time period of all users in the guild
-// Takes two arguments
-// First (required): human readable time duration (2 days, 1 hour, 3 months, etc)
-// Second (optional): username to filter playtime for
+// Takes three arguments
+// duration (required): human readable time duration (2 days, 1 hour, 3 months, etc)
+// user (optional): user to filter playtime for
+// chart (optional): if true, replies with an image of charts instead of a table
 pub async fn recent_playtime(ctx: &Context, interaction: &CommandInteraction) -> CommandResult {
     let Some(guild_id) = interaction.guild_id else {
         interaction
@@ -63,41 +118,19 @@ pub async fn recent_playtime(ctx: &Context, interaction: &CommandInteraction) ->
         return Ok(());
     };
 
-    let arg = if let CommandDataOptionValue::String(c) = &interaction.data.options[0].value {
+    let arg = if let Some(CommandDataOptionValue::String(c)) = interaction
+        .data
+        .options
+        .iter()
+        .find(|o| o.name == "duration")
+        .map(|o| &o.value)
+    {
         String::from(c.trim())
     } else {
         return Err("Missing required arguments".into());
     };
-    let duration_regex = Regex::new(
-        r"(?i)(?:(?:(?:(\d+)\s+years?)|(?:(\d+)\s+months?)|(?:(\d+)\s+weeks?)|(?:(\d+)\s+days?)|(?:(\d+)\s+hours?)|(?:(\d+)\s+minutes?)|(?:(\d+)\s+seconds?))\s?)+",
-    )?;
     let now = Utc::now();
-    let start_date: DateTime<Utc> = if let Some(captures) = duration_regex.captures(&arg) {
-        let years = get_digit_from_match(captures.get(1))?;
-        let months = get_digit_from_match(captures.get(2))?;
-        let weeks = get_digit_from_match(captures.get(3))?;
-        let days = get_digit_from_match(captures.get(4))?;
-        let hours = get_digit_from_match(captures.get(5))?;
-        let minutes = get_digit_from_match(captures.get(6))?;
-        let seconds = get_digit_from_match(captures.get(7))?;
-        let Some(month_days) = months_to_days(now, months) else {
-            return Err("months overflow".into());
-        };
-        now - Duration::try_days(years * 365)
-            .ok_or_else(|| CommandError::from("invalid number of years"))?
-            - Duration::try_days(month_days)
-                .ok_or_else(|| CommandError::from("invalid number of months"))?
-            - Duration::try_days(weeks * 7)
-                .ok_or_else(|| CommandError::from("invalid number of weeks"))?
-            - Duration::try_days(days)
-                .ok_or_else(|| CommandError::from("invalid number of days"))?
-            - Duration::try_hours(hours)
-                .ok_or_else(|| CommandError::from("invalid number of hours"))?
-            - Duration::try_minutes(minutes)
-                .ok_or_else(|| CommandError::from("invalid number of minutes"))?
-            - Duration::try_seconds(seconds)
-                .ok_or_else(|| CommandError::from("invalid number of seconds"))?
-    } else {
+    let Some(start_date) = util::start_date_from_duration(&arg, now)? else {
         interaction
             .edit_response(
                 &ctx.http,
@@ -109,7 +142,7 @@ pub async fn recent_playtime(ctx: &Context, interaction: &CommandInteraction) ->
     let (user_ids, username) = match user_ids_and_name_from_option(
         ctx,
         guild_id,
-        interaction.data.options.get(1),
+        interaction.data.options.iter().find(|o| o.name == "user"),
     )
     .await?
     {
@@ -117,56 +150,38 @@ pub async fn recent_playtime(ctx: &Context, interaction: &CommandInteraction) ->
         None => return Ok(()),
     };
 
-    send_message_with_buttons(
-        ctx,
-        interaction,
-        &user_ids,
-        username.as_ref(),
-        Some(start_date),
-    )
-    .await?;
-
-    Ok(())
-}
-
-fn get_digit_from_match(mat: Option<Match>) -> Result<i64, std::num::ParseIntError> {
-    match mat {
-        None => Ok(0),
-        Some(mat) => i64::from_str(mat.as_str()),
+    if chart_option(interaction) {
+        return send_chart(
+            ctx,
+            interaction,
+            guild_id,
+            &user_ids,
+            username.as_ref(),
+            Some(start_date),
+            now,
+        )
+        .await;
     }
+
+    let pages = PlaytimePages {
+        guild_id,
+        user_ids,
+        username,
+        start_date: Some(start_date),
+        end_date: now,
+    };
+    paginate::send(ctx, interaction, &pages, false).await
 }
 
-// takes months and turns it to days by counting the days of each month, supports + or - months
-fn months_to_days(now: DateTime<Utc>, mut months: i64) -> Option<i64> {
-    let mut end = now;
-    loop {
-        if months == 0 {
-            break Some((end - now).num_days());
-        }
-        end = end.checked_add_signed(Duration::try_days(
-            NaiveDate::from_ymd_opt(
-                match end.month() {
-                    12 => end.year() + 1,
-                    _ => end.year(),
-                },
-                match end.month() {
-                    12 => 1,
-                    _ => end.month() + 1,
-                },
-                1,
-            )?
-            .signed_duration_since(NaiveDate::from_ymd_opt(end.year(), end.month(), 1)?)
-            .num_days(),
-        )?)?;
-        if months > 0 {
-            months -= 1;
-        } else {
-            months += 1;
-        }
-    }
+fn chart_option(interaction: &CommandInteraction) -> bool {
+    interaction
+        .data
+        .options
+        .iter()
+        .any(|o| o.name == "chart" && matches!(o.value, CommandDataOptionValue::Boolean(true)))
 }
 
-async fn user_ids_and_name_from_option(
+pub async fn user_ids_and_name_from_option(
     ctx: &Context,
     guild_id: GuildId,
     option: Option<&CommandDataOption>,
@@ -213,132 +228,127 @@ async fn user_ids_and_name_from_option(
     Ok(Some((user_ids, username)))
 }
 
-pub async fn gen_playtime_message(
+// A continuous period of time a user spent playing a game
+struct GameSpan {
+    game: String,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+}
+
+// Returns every span of time the users spent playing games between start_date and end_date, along with the start of the first span
+// Games are identified by their canonical name, so time spent under any of a game's aliases is merged
+// Spans are clipped to the range, sessions that haven't ended yet end at end_date
+// Returns None if nothing was played in the range
+async fn game_spans(
     ctx: &Context,
+    guild_id: GuildId,
     user_ids: &[i64],
-    username: Option<&String>,
     start_date: Option<DateTime<Utc>>,
     end_date: DateTime<Utc>,
-    offset: usize,
-) -> Result<String, CommandError> {
-    // get all rows with a user id in the channel
+) -> Result<Option<(Vec<GameSpan>, DateTime<Utc>)>, CommandError> {
     let rows = {
         let data = ctx.data.read().await;
         #[allow(clippy::unwrap_used)]
         let db = data.get::<DB>().unwrap();
         #[allow(clippy::panic)]
-        sqlx::query!(r#"SELECT create_date, user_id, game_name FROM user_presence WHERE user_id = any($1) AND (create_date > $2) IS NOT FALSE AND create_date <= $3 ORDER BY create_date"#, user_ids, start_date, end_date).fetch_all(db).await?
+        sqlx::query!(
+            r#"
+SELECT COALESCE(a.game, s.game_name) AS "game!",
+  GREATEST(s.start_date, $2) AS "start!",
+  LEAST(COALESCE(s.end_date, $3), $3) AS "end!"
+FROM game_session s
+LEFT JOIN game_alias a ON a.guild_id = $4 AND a.alias = s.game_name
+WHERE s.user_id = ANY($1)
+AND (COALESCE(s.end_date, $3) > $2) IS NOT FALSE
+AND s.start_date < $3
+ORDER BY 2"#,
+            user_ids,
+            start_date,
+            end_date,
+            i64::from(guild_id)
+        )
+        .fetch_all(db)
+        .await?
+    };
+    let Some(first_time) = rows.first().map(|r| r.start) else {
+        return Ok(None);
     };
-    if rows.is_empty() {
-        return Ok(format!(
-            "```No recorded playtime{}```",
-            if let Some(username) = username {
-                format!(" for {username}")
-            } else {
-                String::new()
-            }
-        ));
-    }
-
-    let mut gametimes: HashMap<String, Duration> = HashMap::new(); // stores how long each game has been played
-    let mut last_user_game: HashMap<i64, GameDate> = HashMap::new(); // tracks the last game a user was "seen" playing as we iterate through the rows
-    let first_time: DateTime<Utc> = rows[0].create_date; // used to display in message how long players have been tracked
-    for row in rows {
-        let date: DateTime<Utc> = row.create_date;
-        let user_id: i64 = row.user_id;
-        let game: Option<String> = row.game_name;
-
-        let Some(last) = last_user_game.get(&user_id) else {
-            // user wasn't playing anything, record new entry if user is playing something now, otherwise just continue
-            if let Some(game) = game {
-                last_user_game.insert(user_id, GameDate { date, game });
-            }
-            continue;
-        };
-
-        // user is still playing the same thing
-        if let Some(game) = &game
-            && game == &last.game
-        {
-            continue;
-        }
-
-        // user is playing something different (or nothing), record how long they played last game
-        if let Some(gametime) = gametimes.get_mut(&last.game) {
-            // increment existing game time
-            *gametime += date - last.date;
-        } else {
-            // or insert new entry for first-seen game
-            gametimes.insert(last.game.clone(), date - last.date);
-        }
-
-        // record what is now playing, if anything
-        match game {
-            Some(game) => last_user_game.insert(user_id, GameDate { date, game }),
-            None => last_user_game.remove(&user_id),
-        };
-    }
-
-    // users are currently playing game at the time of this command so we have no row for them stopping
-    for last in last_user_game.values() {
-        if let Some(gametime) = gametimes.get_mut(&last.game) {
-            // increment existing game time
-            *gametime += end_date - last.date;
-        } else {
-            // or insert new entry for first-seen game
-            gametimes.insert(last.game.clone(), end_date - last.date);
-        }
-    }
 
-    // convert HashMap to Vec so we can sort it by time in descending order
-    let mut total_time = Duration::zero();
-    let mut gametimes: Vec<GameTime> = match gametimes
-        .iter()
-        .map(|(game, time)| {
-            total_time = match total_time.checked_add(time) {
-                Some(t) => t,
-                None => return None,
-            };
-            Some(GameTime {
-                time: *time,
-                game: game.clone(),
-            })
+    let spans = rows
+        .into_iter()
+        .map(|r| GameSpan {
+            game: r.game,
+            start: r.start,
+            end: r.end,
         })
-        .collect()
-    {
-        Some(g) => g,
-        None => return Err("time overflow".into()),
+        .collect();
+    Ok(Some((spans, first_time)))
+}
+
+async fn gen_playtime_message(
+    ctx: &Context,
+    guild_id: GuildId,
+    user_ids: &[i64],
+    username: Option<&String>,
+    start_date: Option<DateTime<Utc>>,
+    end_date: DateTime<Utc>,
+    offset: usize,
+) -> Result<Page, CommandError> {
+    // each row is a game or the total of all games, along with how many rows there are and when the first session in range started
+    let rows = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        let db = data.get::<DB>().unwrap();
+        #[allow(clippy::panic)]
+        sqlx::query!(
+            r#"
+WITH spans AS (
+  SELECT COALESCE(a.game, s.game_name) AS game,
+    GREATEST(s.start_date, $2) AS start_date,
+    LEAST(COALESCE(s.end_date, $3), $3) AS end_date
+  FROM game_session s
+  LEFT JOIN game_alias a ON a.guild_id = $6 AND a.alias = s.game_name
+  WHERE s.user_id = ANY($1)
+  AND (COALESCE(s.end_date, $3) > $2) IS NOT FALSE
+  AND s.start_date < $3
+), totals AS (
+  SELECT game, SUM(EXTRACT(EPOCH FROM end_date - start_date))::float8 AS seconds
+  FROM spans
+  GROUP BY game
+)
+SELECT game AS "game!", seconds AS "seconds!", count(*) OVER () AS "total!", (SELECT min(start_date) FROM spans) AS "first_time!"
+FROM (
+  SELECT game, seconds FROM totals
+  UNION ALL
+  SELECT 'All Games', SUM(seconds) FROM totals HAVING count(*) > 0
+) AS games
+ORDER BY 2 DESC, 1
+LIMIT $4 OFFSET $5"#,
+            user_ids,
+            start_date,
+            end_date,
+            i64::from(OFFSET_INC),
+            i64::try_from(offset)?,
+            i64::from(guild_id)
+        )
+        .fetch_all(db)
+        .await?
     };
 
-    if gametimes.is_empty() {
-        return Ok(format!(
-            "```No recorded playtime{}```",
-            if let Some(username) = username {
-                format!(" for {username}")
-            } else {
-                String::new()
-            }
-        ));
-    }
+    let Some((total_lines, first_time)) = rows.first().map(|r| (r.total, r.first_time)) else {
+        return Ok(Page {
+            content: no_playtime_message(username),
+            page_count: 1,
+        });
+    };
+    let longest_game_name = rows.iter().map(|r| r.game.len()).max().unwrap_or(0); // get longest game name so we can pad shorter game names and lineup times
 
-    gametimes.push(GameTime {
-        time: total_time,
-        game: String::from("All Games"),
-    });
-    gametimes.sort_by_key(|b| std::cmp::Reverse(b.time));
-    let min_offset = offset;
-    let max_offset = (offset + usize::from(OFFSET_INC)).min(gametimes.len());
-    let total_lines = gametimes.len();
-    let gametimes = &gametimes[min_offset..max_offset];
-    let longest_game_name = gametimes.iter().map(|g| g.game.len()).max().unwrap_or(0); // get longest game name so we can pad shorter game names and lineup times
-
-    let mut lines = Vec::with_capacity(gametimes.len());
-    #[allow(clippy::cast_precision_loss)]
-    for gametime in gametimes {
+    let mut lines = Vec::with_capacity(rows.len());
+    for row in rows {
         lines.push(format!(
             "{:>width$} \u{2014} {:.2}\n",
-            gametime.game,
-            (gametime.time.num_seconds()) as f64 / 3600_f64,
+            row.game,
+            row.seconds / 3600_f64,
             width = longest_game_name
         ));
     }
@@ -350,7 +360,8 @@ pub async fn gen_playtime_message(
         time_format_string = "%l:%M%p";
     }
 
-    Ok(format!(
+    let page_count = usize::try_from(total_lines)?.div_ceil(usize::from(OFFSET_INC));
+    let content = format!(
         "```{} {} - Page {}/{}\n\n{}```",
         if let Some(username) = username {
             format!("{username} since")
@@ -359,78 +370,223 @@ pub async fn gen_playtime_message(
         },
         first_time.with_timezone(&Local).format(time_format_string),
         (offset / usize::from(OFFSET_INC)) + 1,
-        (total_lines / usize::from(OFFSET_INC)) + 1,
+        page_count,
         lines.concat()
-    ))
+    );
+
+    Ok(Page {
+        content,
+        page_count,
+    })
 }
 
-async fn send_message_with_buttons(
+fn no_playtime_message(username: Option<&String>) -> String {
+    format!(
+        "```No recorded playtime{}```",
+        if let Some(username) = username {
+            format!(" for {username}")
+        } else {
+            String::new()
+        }
+    )
+}
+
+// Replies with an image of a bar chart of the most played games and a stacked area chart of playtime per week
+async fn send_chart(
     ctx: &Context,
     interaction: &CommandInteraction,
+    guild_id: GuildId,
     user_ids: &[i64],
     username: Option<&String>,
     start_date: Option<DateTime<Utc>>,
+    end_date: DateTime<Utc>,
 ) -> CommandResult {
-    let now = Utc::now();
-    let content = gen_playtime_message(ctx, user_ids, username, start_date, now, 0).await?;
-
-    let newlines =
-        u16::try_from(content.chars().filter(|c| *c == '\n').count()).unwrap_or(u16::MAX);
-    if newlines <= OFFSET_INC {
-        interaction
-            .edit_response(&ctx.http, EditInteractionResponse::new().content(&content))
-            .await?;
-    } else {
-        let button_id = {
-            let data = ctx.data.read().await;
-            #[allow(clippy::unwrap_used)]
-            let db = data.get::<DB>().unwrap();
-            #[allow(clippy::panic)]
-            sqlx::query!(r#"INSERT INTO playtime_button(author_id, user_ids, username, start_date, end_date, start_offset) VALUES ($1, $2, $3, $4, $5, 0) RETURNING id"#, i64::try_from(interaction.user.id)?, user_ids, username as _, start_date, now).fetch_one(db).await?.id
-        };
-
+    let spans = game_spans(ctx, guild_id, user_ids, start_date, end_date).await?;
+    let Some((spans, first_time)) = spans.filter(|s| !s.0.is_empty()) else {
         interaction
             .edit_response(
                 &ctx.http,
-                EditInteractionResponse::new()
-                    .content(&content)
-                    .components(create_components(0, &content, button_id, true)),
+                EditInteractionResponse::new().content(no_playtime_message(username)),
             )
             .await?;
+        return Ok(());
+    };
+    let tz = user_timezone(ctx, interaction.user.id).await?;
 
-        // leave buttons disabled for 2 seconds, then send the message again with buttons enabled
-        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    let mut totals: HashMap<&str, f64> = HashMap::new();
+    for span in &spans {
+        *totals.entry(&span.game).or_default() += hours(span.end - span.start);
+    }
+    let mut totals: Vec<(String, f64)> = totals
+        .into_iter()
+        .map(|(game, hours)| (String::from(game), hours))
+        .collect();
+    totals.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    // the most played games get their own series, anything else is grouped together
+    let mut series: Vec<chart::Series> = totals
+        .iter()
+        .take(if totals.len() > CHART_SERIES {
+            CHART_SERIES - 1
+        } else {
+            CHART_SERIES
+        })
+        .map(|(game, _)| chart::Series {
+            name: game.clone(),
+            values: vec![],
+        })
+        .collect();
+    if totals.len() > series.len() {
+        series.push(chart::Series {
+            name: String::from("Other"),
+            values: vec![],
+        });
+    }
 
-        interaction
-            .edit_response(
-                &ctx.http,
-                EditInteractionResponse::new()
-                    .components(create_components(0, &content, button_id, false)),
-            )
-            .await?;
+    // weeks start on Monday in the invoking user's timezone
+    let mut week_starts = vec![];
+    let mut monday = {
+        let first_day = first_time.with_timezone(&tz).date_naive();
+        first_day - Duration::days(i64::from(first_day.weekday().num_days_from_monday()))
+    };
+    loop {
+        let week_start = tz
+            .from_local_datetime(&monday.and_time(NaiveTime::MIN))
+            .earliest()
+            .map_or_else(
+                || monday.and_time(NaiveTime::MIN).and_utc(),
+                |d| d.with_timezone(&Utc),
+            );
+        if week_start > end_date {
+            break;
+        }
+        week_starts.push(week_start);
+        monday += Duration::weeks(1);
+    }
+    for s in &mut series {
+        s.values = vec![0.0; week_starts.len()];
+    }
+
+    // split each span across the weeks it overlaps
+    for span in &spans {
+        let index = series
+            .iter()
+            .position(|s| s.name == span.game)
+            .unwrap_or(series.len() - 1);
+        let mut current = span.start;
+        while current < span.end {
+            let week = week_starts
+                .partition_point(|w| *w <= current)
+                .saturating_sub(1);
+            let week_end = week_starts
+                .get(week + 1)
+                .map_or(span.end, |w| (*w).min(span.end));
+            if week_end <= current {
+                break;
+            }
+            series[index].values[week] += hours(week_end - current);
+            current = week_end;
+        }
     }
 
+    let label_count = week_starts.len().min(6);
+    let x_labels: Vec<String> = (0..label_count)
+        .map(|i| {
+            let week = if label_count > 1 {
+                i * (week_starts.len() - 1) / (label_count - 1)
+            } else {
+                0
+            };
+            week_starts[week]
+                .with_timezone(&tz)
+                .format("%b %d")
+                .to_string()
+        })
+        .collect();
+
+    let bars: Vec<(String, f64)> = totals.into_iter().take(CHART_BARS).collect();
+    let plot_width = CHART_WIDTH - CHART_MARGIN * 2;
+    let legend_names: Vec<&str> = series.iter().map(|s| s.name.as_str()).collect();
+    let bars_top = 56;
+    let bars_height = u32::try_from(bars.len())? * chart::BAR_HEIGHT;
+    let area_top = bars_top + bars_height + 40;
+    let legend_top = area_top + AREA_HEIGHT + 12;
+    let height = legend_top
+        + chart::legend_rows(plot_width, u32::try_from(legend_names.len())?)
+            * chart::LEGEND_ROW_HEIGHT
+        + CHART_MARGIN;
+
+    let mut image = chart::new_image(CHART_WIDTH, height);
+    chart::draw_text(
+        &mut image,
+        CHART_MARGIN,
+        10,
+        &format!("{} playtime", username.map_or("Everyone", String::as_str)),
+        chart::TEXT,
+        2,
+    );
+    chart::draw_text(
+        &mut image,
+        CHART_MARGIN,
+        30,
+        &format!(
+            "{} to {} ({})",
+            first_time.with_timezone(&tz).format("%b %d, %Y"),
+            end_date.with_timezone(&tz).format("%b %d, %Y"),
+            tz.name()
+        ),
+        chart::MUTED_TEXT,
+        1,
+    );
+    chart::draw_text(
+        &mut image,
+        CHART_MARGIN,
+        bars_top - 14,
+        "Most played",
+        chart::TEXT,
+        1,
+    );
+    chart::draw_bar_chart(&mut image, CHART_MARGIN, bars_top, plot_width, &bars, "h");
+    chart::draw_text(
+        &mut image,
+        CHART_MARGIN,
+        area_top - 18,
+        "Hours per week",
+        chart::TEXT,
+        1,
+    );
+    chart::draw_stacked_area_chart(
+        &mut image,
+        CHART_MARGIN,
+        area_top,
+        plot_width,
+        AREA_HEIGHT,
+        &series,
+        &x_labels,
+        "h",
+    );
+    chart::draw_legend(
+        &mut image,
+        CHART_MARGIN,
+        legend_top,
+        plot_width,
+        &legend_names,
+    );
+
+    interaction
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new().new_attachment(CreateAttachment::bytes(
+                Cow::from(chart::encode_png(&image)?),
+                "playtime.png",
+            )),
+        )
+        .await?;
+
     Ok(())
 }
 
-pub fn create_components(
-    offset: i32,
-    content: &str,
-    button_id: i32,
-    disabled: bool,
-) -> Vec<CreateActionRow> {
-    vec![CreateActionRow::Buttons(vec![
-        CreateButton::new(format!("playtime:first:{button_id}"))
-            .style(ButtonStyle::Primary)
-            .label("First")
-            .disabled(disabled || offset < 1),
-        CreateButton::new(format!("playtime:prev:{button_id}"))
-            .style(ButtonStyle::Primary)
-            .label("Prev")
-            .disabled(disabled || offset < 1),
-        CreateButton::new(format!("playtime:next:{button_id}"))
-            .style(ButtonStyle::Primary)
-            .label("Next")
-            .disabled(disabled || content.matches('\n').count() < usize::from(OFFSET_INC) + 2),
-    ])]
+#[allow(clippy::cast_precision_loss)]
+fn hours(duration: Duration) -> f64 {
+    duration.num_seconds() as f64 / 3600_f64
 }
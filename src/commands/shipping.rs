@@ -1,10 +1,19 @@
-use crate::error::CommandResult;
-use crate::model::DB;
-use crate::shippo::{Status, TrackingNumber::*};
-use crate::{config, shippo};
-use serenity::all::{CommandDataOptionValue, CommandInteraction};
+use crate::error::{CommandError, CommandResult};
+use crate::model::{DB, Tracker};
+use crate::tracking::{self, Shipment, Status, TrackingNumber};
+use crate::util;
+use serenity::all::{
+    ButtonStyle, CommandDataOption, CommandDataOptionValue, CommandInteraction,
+    ComponentInteraction, CreateActionRow, CreateButton, CreateEmbed, CreateInteractionResponse,
+    CreateInteractionResponseMessage, UserId,
+};
 use serenity::builder::EditInteractionResponse;
 use serenity::client::Context;
+use sqlx::{Pool, Postgres};
+
+// Most shipments listed by /shipments, each gets a refresh button and a message can have at most 25
+const MAX_LISTED_SHIPMENTS: i64 = 25;
+const BUTTONS_PER_ROW: usize = 5;
 
 pub async fn track(ctx: &Context, interaction: &CommandInteraction) -> CommandResult {
     let mut number = "";
@@ -30,38 +39,63 @@ pub async fn track(ctx: &Context, interaction: &CommandInteraction) -> CommandRe
             _ => {}
         }
     }
-    let tracking_number = match carrier {
-        "fedex" => FedEx(number.to_string()),
-        "ups" => Ups(number.to_string()),
-        "usps" => Usps(number.to_string()),
-        &_ => return Err(format!("Unrecognized carrier: {carrier}").into()),
+    let number = TrackingNumber::normalize(number);
+    let tracking_number = if carrier.is_empty() {
+        let Some(tracking_number) = TrackingNumber::detect(&number) else {
+            interaction
+                .edit_response(
+                    &ctx.http,
+                    EditInteractionResponse::new().content(format!(
+                        "Unable to tell which carrier `{number}` is from, choose a carrier to track it"
+                    )),
+                )
+                .await?;
+            return Ok(());
+        };
+        tracking_number
+    } else {
+        let Some(tracking_number) = TrackingNumber::from_carrier(carrier, number) else {
+            return Err(format!("Unrecognized carrier: {carrier}").into());
+        };
+        tracking_number
     };
 
-    let shippo_api_key = {
+    let tracker = {
         let data = ctx.data.read().await;
         #[allow(clippy::unwrap_used)]
-        data.get::<config::Shippo>().unwrap().api_key.clone()
+        data.get::<Tracker>().unwrap().clone()
     };
-    let shipment = shippo::get_tracking_status(&tracking_number, &shippo_api_key).await?;
+    let tracking = tracker.track(&tracking_number).await?;
 
-    let eta_string = if let Some(eta) = shipment.eta {
+    let eta_string = if let Some(eta) = tracking.eta {
         format!("\nETA: {}", eta.format("%A, %b %d"))
     } else {
         String::new()
     };
 
-    let status_string = if let Some(status) = shipment.tracking_status {
-        if status.status != Status::Delivered {
+    let Some(status) = &tracking.status else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content(format!("Status Unknown{eta_string}")),
+            )
+            .await?;
+        return Ok(());
+    };
+    let status_string = status.details.clone();
+    if status.status != Status::Delivered {
+        let db = {
             let data = ctx.data.read().await;
             #[allow(clippy::unwrap_used)]
-            let db = data.get::<DB>().unwrap();
-            #[allow(clippy::panic)]
-            sqlx::query!("INSERT INTO shipment(carrier, tracking_number, author_id, channel_id, status, comment) VALUES ($1::shipment_carrier, $2, $3, $4, $5::shipment_tracking_status, $6) ON CONFLICT ON CONSTRAINT shipment_uk_carrier_number DO NOTHING", tracking_number.carrier() as _, tracking_number.number(), i64::try_from(interaction.user.id)?, i64::try_from(interaction.channel_id)?, format!("{}", status.status) as _, comment).execute(db).await?;
+            data.get::<DB>().unwrap().clone()
+        };
+        #[allow(clippy::panic)]
+        let inserted = sqlx::query_as!(Shipment, r#"INSERT INTO shipment(carrier, tracking_number, author_id, channel_id, status, substatus, comment, eta) VALUES ($1::shipment_carrier, $2, $3, $4, $5::shipment_tracking_status, $6, $7, $8) ON CONFLICT ON CONSTRAINT shipment_uk_carrier_number DO NOTHING RETURNING id, carrier::text AS "carrier!", tracking_number, author_id, channel_id, status::text AS "status!", substatus, eta, comment"#, tracking_number.carrier() as _, tracking_number.number(), i64::try_from(interaction.user.id)?, i64::try_from(interaction.channel_id)?, format!("{}", status.status) as _, status.substatus.as_deref(), comment, tracking.eta).fetch_optional(&db).await?;
+        // nothing has changed since the shipment was inserted, so this only records its tracking history
+        if let Some(inserted) = inserted {
+            tracking::update_shipment(&ctx.http, &db, &inserted, tracking).await?;
         }
-        status.status_details
-    } else {
-        String::from("Status Unknown")
-    };
+    }
 
     interaction
         .edit_response(
@@ -72,3 +106,365 @@ pub async fn track(ctx: &Context, interaction: &CommandInteraction) -> CommandRe
 
     Ok(())
 }
+
+// Replies with the invoking user's shipments that haven't been delivered yet, with a button to refresh each one
+pub async fn shipments(ctx: &Context, interaction: &CommandInteraction) -> CommandResult {
+    let db = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        data.get::<DB>().unwrap().clone()
+    };
+    let (embed, components) = shipment_list(&db, interaction.user.id).await?;
+
+    interaction
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new()
+                .embed(embed)
+                .components(components),
+        )
+        .await?;
+
+    Ok(())
+}
+
+// Refreshes a shipment from a /shipments message right away and updates the list (custom_id "shipment:refresh:<id>")
+// Only the user tracking the shipment can refresh it
+pub async fn refresh(
+    ctx: &Context,
+    interaction: &ComponentInteraction,
+    fields: &[&str],
+) -> CommandResult {
+    let Some(id) = fields.get(2).and_then(|f| f.parse::<i32>().ok()) else {
+        return Err(format!(
+            "Malformed shipment interaction: {}",
+            interaction.data.custom_id
+        )
+        .into());
+    };
+    let db = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        data.get::<DB>().unwrap().clone()
+    };
+
+    #[allow(clippy::panic)]
+    let shipment = sqlx::query_as!(
+        Shipment,
+        r#"SELECT id, carrier::text AS "carrier!", tracking_number, author_id, channel_id, status::text AS "status!", substatus, eta, comment FROM shipment WHERE id = $1"#,
+        id
+    )
+    .fetch_optional(&db)
+    .await?;
+    let content = match &shipment {
+        None => Some(String::from("That shipment is no longer being tracked")),
+        Some(s) if s.author_id != i64::from(interaction.user.id) => Some(format!(
+            "Only <@{}> can refresh their shipments",
+            s.author_id
+        )),
+        Some(_) => None,
+    };
+    let (Some(shipment), None) = (shipment, content.as_ref()) else {
+        interaction
+            .create_response(
+                &ctx.http,
+                CreateInteractionResponse::Message(
+                    CreateInteractionResponseMessage::new()
+                        .content(content.unwrap_or_default())
+                        .ephemeral(true),
+                ),
+            )
+            .await?;
+        return Ok(());
+    };
+    interaction.defer(&ctx.http).await?;
+
+    let Some(tracking_number) =
+        TrackingNumber::from_carrier(&shipment.carrier, shipment.tracking_number.clone())
+    else {
+        return Err(format!("Unrecognized carrier: {}", shipment.carrier).into());
+    };
+    let tracker = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        data.get::<Tracker>().unwrap().clone()
+    };
+    let tracking = tracker.track(&tracking_number).await?;
+    tracking::update_shipment(&ctx.http, &db, &shipment, tracking).await?;
+
+    let (embed, components) = shipment_list(&db, interaction.user.id).await?;
+    interaction
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new()
+                .embed(embed)
+                .components(components),
+        )
+        .await?;
+
+    Ok(())
+}
+
+// Stops tracking one of the invoking user's shipments
+// Takes a single required argument of the tracking number
+pub async fn untrack(ctx: &Context, interaction: &CommandInteraction) -> CommandResult {
+    let Some(number) = util::get_string_option(&interaction.data.options, "number") else {
+        return Err("Missing required arguments".into());
+    };
+    let number = TrackingNumber::normalize(number);
+
+    let deleted = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        let db = data.get::<DB>().unwrap();
+        #[allow(clippy::panic)]
+        sqlx::query!(
+            r#"
+WITH shipment AS (
+  DELETE FROM shipment WHERE author_id = $1 AND tracking_number = $2 RETURNING id, carrier
+), event AS (
+  DELETE FROM shipment_event WHERE shipment_id IN (SELECT id FROM shipment)
+)
+SELECT carrier::text AS "carrier!" FROM shipment"#,
+            i64::from(interaction.user.id),
+            number
+        )
+        .fetch_optional(db)
+        .await?
+    };
+
+    let content = match deleted {
+        Some(shipment) => format!(
+            "Stopped tracking {} shipment `{number}`",
+            carrier_name(&shipment.carrier)
+        ),
+        None => format!("You aren't tracking a shipment with the number `{number}`"),
+    };
+    interaction
+        .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+        .await?;
+
+    Ok(())
+}
+
+// Changes the comment of one of the invoking user's shipments
+// Takes one required and one optional argument
+// number: tracking number of the shipment
+// comment: new comment, the comment is removed if not given
+pub async fn comment(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    options: &[CommandDataOption],
+) -> CommandResult {
+    let Some(number) = util::get_string_option(options, "number") else {
+        return Err("Missing required arguments".into());
+    };
+    let number = TrackingNumber::normalize(number);
+    let comment = util::get_string_option(options, "comment")
+        .map(str::trim)
+        .filter(|c| !c.is_empty());
+
+    let updated = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        let db = data.get::<DB>().unwrap();
+        #[allow(clippy::panic)]
+        sqlx::query!(
+            "UPDATE shipment SET comment = $3 WHERE author_id = $1 AND tracking_number = $2",
+            i64::from(interaction.user.id),
+            number,
+            comment
+        )
+        .execute(db)
+        .await?
+        .rows_affected()
+    };
+
+    let content = match (updated, comment) {
+        (0, _) => format!("You aren't tracking a shipment with the number `{number}`"),
+        (_, Some(comment)) => format!("Shipment `{number}` is now labeled \"{comment}\""),
+        (_, None) => format!("Removed the comment from shipment `{number}`"),
+    };
+    interaction
+        .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+        .await?;
+
+    Ok(())
+}
+
+// Changes which shipment status changes the invoking user is notified about, deliveries are always notified
+// Every argument is optional, settings that aren't given are left as they are
+// transit, out_for_delivery, eta, returned, failure: whether to be notified of each change
+pub async fn notify(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    options: &[CommandDataOption],
+) -> CommandResult {
+    let bool_option = |name: &str| {
+        options.iter().find(|o| o.name == name).and_then(|o| {
+            if let CommandDataOptionValue::Boolean(b) = o.value {
+                Some(b)
+            } else {
+                None
+            }
+        })
+    };
+
+    let config = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        let db = data.get::<DB>().unwrap();
+        #[allow(clippy::panic)]
+        sqlx::query!(
+            "INSERT INTO shipment_notify_config(user_id) VALUES ($1) ON CONFLICT ON CONSTRAINT shipment_notify_config_pkey DO NOTHING",
+            i64::from(interaction.user.id)
+        )
+        .execute(db)
+        .await?;
+        #[allow(clippy::panic)]
+        sqlx::query!(
+            "UPDATE shipment_notify_config SET transit = COALESCE($2, transit), out_for_delivery = COALESCE($3, out_for_delivery), eta = COALESCE($4, eta), returned = COALESCE($5, returned), failure = COALESCE($6, failure) WHERE user_id = $1 RETURNING transit, out_for_delivery, eta, returned, failure",
+            i64::from(interaction.user.id),
+            bool_option("transit"),
+            bool_option("out_for_delivery"),
+            bool_option("eta"),
+            bool_option("returned"),
+            bool_option("failure")
+        )
+        .fetch_one(db)
+        .await?
+    };
+
+    let on_off = |b: bool| if b { "on" } else { "off" };
+    let content = format!(
+        "```Shipment notifications\n\nIn transit:       {}\nOut for delivery: {}\nETA changes:      {}\nReturned:         {}\nFailed delivery:  {}\nDelivered:        always```",
+        on_off(config.transit),
+        on_off(config.out_for_delivery),
+        on_off(config.eta),
+        on_off(config.returned),
+        on_off(config.failure)
+    );
+    interaction
+        .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+        .await?;
+
+    Ok(())
+}
+
+// Builds the list of a user's undelivered shipments and a refresh button for each
+async fn shipment_list(
+    db: &Pool<Postgres>,
+    user_id: UserId,
+) -> Result<(CreateEmbed, Vec<CreateActionRow>), CommandError> {
+    #[allow(clippy::panic)]
+    let rows = sqlx::query!(
+        r#"SELECT id, carrier::text AS "carrier!", tracking_number, status::text AS "status!", eta, comment, update_date FROM shipment WHERE author_id = $1 AND status = ANY('{transit, pre_transit, unknown}') ORDER BY id LIMIT $2"#,
+        i64::from(user_id),
+        MAX_LISTED_SHIPMENTS
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut embed = CreateEmbed::new().title("Your shipments");
+    if rows.is_empty() {
+        embed = embed.description("You aren't tracking any shipments, add one with `/track`");
+    }
+    let mut buttons = Vec::with_capacity(rows.len());
+    for (n, row) in (1..).zip(&rows) {
+        let name = match &row.comment {
+            Some(comment) => format!(
+                "{n}. {} {} \u{2014} {comment}",
+                carrier_name(&row.carrier),
+                row.tracking_number
+            ),
+            None => format!(
+                "{n}. {} {}",
+                carrier_name(&row.carrier),
+                row.tracking_number
+            ),
+        };
+        let eta = row.eta.map_or_else(
+            || String::from("Unknown"),
+            |eta| format!("<t:{}:D>", eta.timestamp()),
+        );
+        embed = embed.field(
+            name,
+            format!(
+                "{}\nETA: {eta}\nUpdated <t:{}:R>",
+                status_name(&row.status),
+                row.update_date.timestamp()
+            ),
+            false,
+        );
+        buttons.push(
+            CreateButton::new(format!("shipment:refresh:{}", row.id))
+                .label(format!("Refresh {n}"))
+                .style(ButtonStyle::Secondary),
+        );
+    }
+    let components = buttons
+        .chunks(BUTTONS_PER_ROW)
+        .map(|b| CreateActionRow::Buttons(b.to_vec()))
+        .collect();
+
+    Ok((embed, components))
+}
+
+fn carrier_name(carrier: &str) -> &str {
+    match carrier {
+        "australia_post" => "Australia Post",
+        "canada_post" => "Canada Post",
+        "dhl_ecommerce" => "DHL eCommerce",
+        "dhl_express" => "DHL Express",
+        "fedex" => "FedEx",
+        "lasership" => "LaserShip",
+        "ontrac" => "OnTrac",
+        "purolator" => "Purolator",
+        "royal_mail" => "Royal Mail",
+        "ups" => "UPS",
+        "usps" => "USPS",
+        c => c,
+    }
+}
+
+fn status_name(status: &str) -> &str {
+    match status {
+        "pre_transit" => "Label created",
+        "transit" => "In transit",
+        "delivered" => "Delivered",
+        "returned" => "Returned to sender",
+        "failure" => "Delivery failed",
+        _ => "Status unknown",
+    }
+}
+
+// Suggests tracking numbers of shipments the user is tracking, as (name, value) pairs
+// Names include the carrier and comment so shipments are easier to tell apart
+pub async fn autocomplete(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    value: &str,
+) -> Result<Vec<(String, String)>, CommandError> {
+    let data = ctx.data.read().await;
+    #[allow(clippy::unwrap_used)]
+    let db = data.get::<DB>().unwrap();
+    #[allow(clippy::panic)]
+    let rows = sqlx::query!(
+        r#"SELECT carrier::text AS "carrier!", tracking_number, comment FROM shipment WHERE author_id = $1 AND strpos(lower(tracking_number), $2) > 0 ORDER BY id DESC LIMIT 25"#,
+        i64::from(interaction.user.id),
+        value.to_lowercase()
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| {
+            let name = match r.comment {
+                Some(comment) => format!("{} ({}) - {comment}", r.tracking_number, r.carrier),
+                None => format!("{} ({})", r.tracking_number, r.carrier),
+            };
+            (name.chars().take(100).collect(), r.tracking_number)
+        })
+        .collect())
+}
@@ -0,0 +1,720 @@
+use crate::config;
+use crate::error::{CommandError, CommandResult};
+use crate::model::{DB, GuildVoiceLocks};
+use crate::util;
+use regex::Regex;
+use serenity::all::{CommandDataOption, CommandDataOptionValue, CommandInteraction};
+use serenity::builder::EditInteractionResponse;
+use serenity::client::Context;
+use serenity::model::id::GuildId;
+use songbird::input::File;
+use songbird::tracks::{ControlError, Track};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicI16, Ordering};
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+use tokio::process::Command;
+use tokio::sync::Mutex;
+use tokio::time::{sleep, timeout};
+use tracing::{error, warn};
+
+const ONE_SECOND: Duration = Duration::from_secs(1);
+const MAX_UPLOAD_BYTES: u32 = 8 * 1024 * 1024;
+const MAX_DURATION_MS: i32 = 30_000;
+const MAX_SOUNDS: i64 = 100;
+
+#[allow(clippy::unwrap_used)]
+static NAME_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[a-z0-9_-]{1,32}$").unwrap());
+
+async fn sound_path(ctx: &Context, guild_id: GuildId) -> PathBuf {
+    let data = ctx.data.read().await;
+    #[allow(clippy::unwrap_used)]
+    let directory = &data.get::<config::Sound>().unwrap().directory;
+    PathBuf::from(directory).join(guild_id.to_string())
+}
+
+// Joins the invoking user's voice channel and plays the named sound
+pub async fn play(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    options: &[CommandDataOption],
+) -> CommandResult {
+    let Some(guild_id) = interaction.guild_id else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Command can only be used in a server"),
+            )
+            .await?;
+        return Ok(());
+    };
+    let name = util::get_string_option(options, "name")
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
+
+    let row = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        let db = data.get::<DB>().unwrap();
+        #[allow(clippy::panic)]
+        sqlx::query!(
+            "SELECT id, name FROM sound WHERE guild_id = $1 AND name = $2",
+            i64::from(guild_id),
+            name
+        )
+        .fetch_optional(db)
+        .await?
+    };
+    let Some(row) = row else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content(format!("No sound named `{name}`")),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    play_sound(ctx, interaction, guild_id, row.id, &row.name).await
+}
+
+// Joins the invoking user's voice channel and plays a random sound from the guild's soundboard
+pub async fn random(ctx: &Context, interaction: &CommandInteraction) -> CommandResult {
+    let Some(guild_id) = interaction.guild_id else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Command can only be used in a server"),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    let row = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        let db = data.get::<DB>().unwrap();
+        #[allow(clippy::panic)]
+        sqlx::query!(
+            "SELECT id, name FROM sound WHERE guild_id = $1 ORDER BY random() LIMIT 1",
+            i64::from(guild_id)
+        )
+        .fetch_optional(db)
+        .await?
+    };
+    let Some(row) = row else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content(
+                    "This server has no sounds yet, an admin can add some with `/sound add`",
+                ),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    play_sound(ctx, interaction, guild_id, row.id, &row.name).await
+}
+
+// Plays a sound in the invoking user's voice channel
+// Only one sound plays per guild at a time, others wait on the guild's voice lock in the order they were requested
+async fn play_sound(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    guild_id: GuildId,
+    sound_id: i32,
+    name: &str,
+) -> CommandResult {
+    let voice_states = match ctx.cache.guild(guild_id) {
+        Some(g) => g.voice_states.clone(),
+        None => return Err(format!("Unable to find guild {guild_id}").into()),
+    };
+
+    if voice_states
+        .get(&interaction.user.id)
+        .and_then(|voice_state| voice_state.channel_id)
+        .is_none()
+    {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new()
+                    .content("Command can only be used if you're in a voice channel"),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let path = sound_path(ctx, guild_id)
+        .await
+        .join(format!("{sound_id}.ogg"));
+    let volume = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        let db = data.get::<DB>().unwrap();
+        #[allow(clippy::panic)]
+        sqlx::query!(
+            "SELECT volume FROM sound_config WHERE guild_id = $1",
+            i64::from(guild_id)
+        )
+        .fetch_optional(db)
+        .await?
+        .map_or(100, |r| r.volume)
+    };
+    let track = Track::from(File::new(path)).volume(f32::from(volume) / 100.0);
+
+    let Some(manager) = songbird::get(ctx).await else {
+        return Err("Missing songbird".into());
+    };
+
+    let voice_lock = {
+        let map_mutex = {
+            let data = ctx.data.read().await;
+            #[allow(clippy::unwrap_used)]
+            data.get::<GuildVoiceLocks>().unwrap().clone()
+        };
+        let mut voice_locks = map_mutex.lock().await;
+        let lock = voice_locks
+            .entry(guild_id)
+            .or_insert_with(|| Arc::new((Mutex::new(()), AtomicI16::new(0))));
+        lock.clone()
+    };
+    let waiting = voice_lock.1.fetch_add(1, Ordering::Relaxed); //indicate we're about to be waiting on this lock
+    let _voice_mutex = if let Ok(guard) = voice_lock.0.try_lock() {
+        guard
+    } else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content(format!(
+                    "Queued `{name}` behind {} other sound{}",
+                    waiting + 1,
+                    if waiting == 0 { "" } else { "s" }
+                )),
+            )
+            .await?;
+        voice_lock.0.lock().await
+    };
+    voice_lock.1.fetch_sub(1, Ordering::Relaxed);
+
+    // refresh voice_channel_id
+    let voice_states = match ctx.cache.guild(guild_id) {
+        Some(g) => g.voice_states.clone(),
+        None => return Err(format!("Unable to find guild {guild_id}").into()),
+    };
+    let Some(voice_channel_id) = voice_states
+        .get(&interaction.user.id)
+        .and_then(|voice_state| voice_state.channel_id)
+    else {
+        let leave_res = if manager.get(guild_id).is_some() {
+            // check if we need to leave a call now
+            if voice_lock.1.load(Ordering::Relaxed) < 1 {
+                // only leave the channel if we dont think anyone is waiting on the lock
+                if let Err(e) = manager.remove(guild_id).await {
+                    Err(format!("Unable to leave after playback: {e}").into())
+                } else {
+                    Ok(())
+                }
+            } else {
+                Ok(())
+            }
+        } else {
+            Ok(())
+        };
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new()
+                    .content("Command can only be used if you're in a voice channel"),
+            )
+            .await?;
+        return leave_res;
+    };
+
+    let handler = manager.join(guild_id, voice_channel_id).await?;
+
+    interaction
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new().content(format!("\u{1F50A} `{name}`")),
+        )
+        .await?;
+
+    let play_result = {
+        let mut call = handler.lock().await;
+        let audio_handle = call.play_only(track);
+        loop {
+            sleep(ONE_SECOND).await;
+            let Ok(info) = timeout(ONE_SECOND, audio_handle.get_info()).await else {
+                // this appears to hapeen when bot is kicked/disconnect from channel
+                warn!("get_info took too long");
+                break Ok(());
+            };
+            match info {
+                Ok(info) => {
+                    if info.playing.is_done() {
+                        break Ok(());
+                    }
+                }
+                Err(e) => {
+                    if let ControlError::Finished = e {
+                        break Ok(());
+                    }
+                    break Err(CommandError::from(format!(
+                        "Unexpected error during playback: {e}"
+                    )));
+                }
+            }
+        }
+    };
+
+    sleep(ONE_SECOND).await;
+
+    if voice_lock.1.load(Ordering::Relaxed) < 1 {
+        // only leave the channel if we dont think anyone is waiting on the lock
+        if let Err(e) = manager.remove(guild_id).await {
+            return Err(format!("Unable to leave after playback: {e}").into());
+        }
+    }
+
+    interaction.delete_response(&ctx.http).await?;
+
+    play_result
+}
+
+// Lists the names of all sounds in the guild's soundboard
+pub async fn list(ctx: &Context, interaction: &CommandInteraction) -> CommandResult {
+    let Some(guild_id) = interaction.guild_id else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Command can only be used in a server"),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    let rows = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        let db = data.get::<DB>().unwrap();
+        #[allow(clippy::panic)]
+        sqlx::query!(
+            "SELECT name FROM sound WHERE guild_id = $1 ORDER BY name",
+            i64::from(guild_id)
+        )
+        .fetch_all(db)
+        .await?
+    };
+
+    let content = if rows.is_empty() {
+        String::from("This server has no sounds yet, an admin can add some with `/sound add`")
+    } else {
+        format!(
+            "```{}```",
+            rows.into_iter()
+                .map(|r| r.name)
+                .collect::<Vec<String>>()
+                .join(", ")
+        )
+    };
+
+    interaction
+        .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+        .await?;
+
+    Ok(())
+}
+
+// Validates an uploaded audio clip, transcodes it to Opus, and adds it to the guild's soundboard
+pub async fn add(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    options: &[CommandDataOption],
+) -> CommandResult {
+    let Some(guild_id) = interaction.guild_id else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Command can only be used in a server"),
+            )
+            .await?;
+        return Ok(());
+    };
+    if !util::is_guild_admin(interaction) {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Only server admins can add sounds"),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let name = util::get_string_option(options, "name")
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
+    if !NAME_REGEX.is_match(&name) {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content(
+                    "Sound names can only contain lowercase letters, numbers, `-`, and `_`",
+                ),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let Some(attachment) = options
+        .iter()
+        .find_map(|o| {
+            if let CommandDataOptionValue::Attachment(a) = o.value {
+                Some(a)
+            } else {
+                None
+            }
+        })
+        .and_then(|a| interaction.data.resolved.attachments.get(&a))
+    else {
+        return Err("Missing attachment".into());
+    };
+    if attachment.size > MAX_UPLOAD_BYTES {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content(format!(
+                    "Sounds can be at most {} MB",
+                    MAX_UPLOAD_BYTES / 1024 / 1024
+                )),
+            )
+            .await?;
+        return Ok(());
+    }
+    if !attachment
+        .content_type
+        .as_ref()
+        .is_some_and(|t| t.starts_with("audio/") || t.starts_with("video/"))
+    {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Attachment isn't an audio file"),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let db = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        data.get::<DB>().unwrap().clone()
+    };
+    #[allow(clippy::panic)]
+    let existing = sqlx::query!(
+        r#"SELECT count(*) AS "count!", bool_or(name = $2) AS "exists!" FROM sound WHERE guild_id = $1"#,
+        i64::from(guild_id),
+        name
+    )
+    .fetch_one(&db)
+    .await?;
+    if existing.exists {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new()
+                    .content(format!("A sound named `{name}` already exists")),
+            )
+            .await?;
+        return Ok(());
+    }
+    if existing.count >= MAX_SOUNDS {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new()
+                    .content(format!("Servers can have at most {MAX_SOUNDS} sounds")),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let directory = sound_path(ctx, guild_id).await;
+    tokio::fs::create_dir_all(&directory).await?;
+    let upload_path = directory.join(format!("upload-{}", attachment.id));
+    let transcoded_path = directory.join(format!("upload-{}.ogg", attachment.id));
+    tokio::fs::write(&upload_path, attachment.download().await?).await?;
+
+    let transcoded = transcode(&upload_path, &transcoded_path).await;
+    if let Err(e) = tokio::fs::remove_file(&upload_path).await {
+        warn!(%e, "unable to remove sound upload");
+    }
+    let duration_ms = match transcoded {
+        Ok(d) => d,
+        Err(e) => {
+            warn!(%e, "unable to transcode sound");
+            if let Err(e) = tokio::fs::remove_file(&transcoded_path).await {
+                warn!(%e, "unable to remove transcoded sound");
+            }
+            interaction
+                .edit_response(
+                    &ctx.http,
+                    EditInteractionResponse::new().content("Unable to read audio from attachment"),
+                )
+                .await?;
+            return Ok(());
+        }
+    };
+    if duration_ms > MAX_DURATION_MS {
+        tokio::fs::remove_file(&transcoded_path).await?;
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content(format!(
+                    "Sounds can be at most {} seconds long",
+                    MAX_DURATION_MS / 1000
+                )),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    #[allow(clippy::panic)]
+    let sound_id = sqlx::query!(
+        "INSERT INTO sound(guild_id, author_id, name, duration_ms) VALUES ($1, $2, $3, $4) RETURNING id",
+        i64::from(guild_id),
+        i64::from(interaction.user.id),
+        name,
+        duration_ms
+    )
+    .fetch_one(&db)
+    .await?
+    .id;
+    tokio::fs::rename(&transcoded_path, directory.join(format!("{sound_id}.ogg"))).await?;
+
+    interaction
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new().content(format!(
+                "Added `{name}` ({:.1}s)",
+                f64::from(duration_ms) / 1000.0
+            )),
+        )
+        .await?;
+
+    Ok(())
+}
+
+// Transcodes input to 48kHz stereo Opus (what Discord voice uses) and returns the duration of the result in milliseconds
+async fn transcode(input: &PathBuf, output: &PathBuf) -> Result<i32, CommandError> {
+    let ffmpeg = Command::new("ffmpeg")
+        .args(["-hide_banner", "-loglevel", "error", "-y", "-i"])
+        .arg(input)
+        // normalize loudness so that clips play at similar volumes
+        .args([
+            "-vn",
+            "-map_metadata",
+            "-1",
+            "-af",
+            "loudnorm=I=-16:TP=-1.5:LRA=11",
+            "-ac",
+            "2",
+            "-ar",
+            "48000",
+            "-c:a",
+            "libopus",
+            "-b:a",
+            "96k",
+            "-f",
+            "ogg",
+        ])
+        .arg(output)
+        .output()
+        .await?;
+    if !ffmpeg.status.success() {
+        return Err(format!(
+            "ffmpeg failed: {}",
+            String::from_utf8_lossy(&ffmpeg.stderr).trim()
+        )
+        .into());
+    }
+
+    let ffprobe = Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-show_entries",
+            "format=duration",
+            "-of",
+            "csv=p=0",
+        ])
+        .arg(output)
+        .output()
+        .await?;
+    if !ffprobe.status.success() {
+        return Err(format!(
+            "ffprobe failed: {}",
+            String::from_utf8_lossy(&ffprobe.stderr).trim()
+        )
+        .into());
+    }
+    let seconds: f64 = std::str::from_utf8(&ffprobe.stdout)?.trim().parse()?;
+    #[allow(clippy::cast_possible_truncation)]
+    Ok((seconds * 1000.0).round() as i32)
+}
+
+// Removes a sound from the guild's soundboard
+pub async fn remove(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    options: &[CommandDataOption],
+) -> CommandResult {
+    let Some(guild_id) = interaction.guild_id else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Command can only be used in a server"),
+            )
+            .await?;
+        return Ok(());
+    };
+    if !util::is_guild_admin(interaction) {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Only server admins can remove sounds"),
+            )
+            .await?;
+        return Ok(());
+    }
+    let name = util::get_string_option(options, "name")
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
+
+    let row = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        let db = data.get::<DB>().unwrap();
+        #[allow(clippy::panic)]
+        sqlx::query!(
+            "DELETE FROM sound WHERE guild_id = $1 AND name = $2 RETURNING id",
+            i64::from(guild_id),
+            name
+        )
+        .fetch_optional(db)
+        .await?
+    };
+    let Some(row) = row else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content(format!("No sound named `{name}`")),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    let path = sound_path(ctx, guild_id)
+        .await
+        .join(format!("{}.ogg", row.id));
+    if let Err(e) = tokio::fs::remove_file(&path).await {
+        error!(%e, ?path, "unable to remove sound file");
+    }
+
+    interaction
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new().content(format!("Removed `{name}`")),
+        )
+        .await?;
+
+    Ok(())
+}
+
+// Sets the playback volume of the guild's soundboard
+pub async fn volume(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    options: &[CommandDataOption],
+) -> CommandResult {
+    let Some(guild_id) = interaction.guild_id else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Command can only be used in a server"),
+            )
+            .await?;
+        return Ok(());
+    };
+    if !util::is_guild_admin(interaction) {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Only server admins can change the volume"),
+            )
+            .await?;
+        return Ok(());
+    }
+    let Some(percent) = options.iter().find_map(|o| {
+        if let CommandDataOptionValue::Integer(i) = o.value {
+            i16::try_from(i).ok()
+        } else {
+            None
+        }
+    }) else {
+        return Err("Missing volume".into());
+    };
+
+    {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        let db = data.get::<DB>().unwrap();
+        #[allow(clippy::panic)]
+        sqlx::query!(
+            "INSERT INTO sound_config(guild_id, volume) VALUES ($1, $2)
+             ON CONFLICT ON CONSTRAINT sound_config_pkey DO UPDATE SET volume = $2",
+            i64::from(guild_id),
+            percent
+        )
+        .execute(db)
+        .await?;
+    }
+
+    interaction
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new().content(format!("Soundboard volume set to {percent}%")),
+        )
+        .await?;
+
+    Ok(())
+}
+
+// Suggests sound names from the guild's soundboard that contain what's been typed so far, as (name, value) pairs
+pub async fn autocomplete(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    value: &str,
+) -> Result<Vec<(String, String)>, CommandError> {
+    let Some(guild_id) = interaction.guild_id else {
+        return Ok(vec![]);
+    };
+
+    let data = ctx.data.read().await;
+    #[allow(clippy::unwrap_used)]
+    let db = data.get::<DB>().unwrap();
+    #[allow(clippy::panic)]
+    let rows = sqlx::query!(
+        "SELECT name FROM sound WHERE guild_id = $1 AND strpos(name, $2) > 0 ORDER BY name LIMIT 25",
+        i64::from(guild_id),
+        value.to_lowercase()
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.into_iter().map(|r| (r.name.clone(), r.name)).collect())
+}
@@ -0,0 +1,145 @@
+use crate::commands::timezone::user_timezone;
+use crate::error::{CommandError, CommandResult};
+use crate::model::DB;
+use crate::paginate::{self, Page, Paginate};
+use crate::util;
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use serenity::all::{CommandDataOptionValue, CommandInteraction};
+use serenity::async_trait;
+use serenity::builder::EditInteractionResponse;
+use serenity::client::Context;
+use serenity::utils::MessageBuilder;
+
+const PAGE_SIZE: i64 = 10;
+
+#[derive(Serialize, Deserialize)]
+pub struct StatusHistoryPages {
+    user_id: i64,
+    username: String,
+    // name of the invoking user's timezone, used to show when statuses were set
+    timezone: String,
+}
+
+#[async_trait]
+impl Paginate for StatusHistoryPages {
+    const KIND: &'static str = "statushistory";
+
+    async fn render(&self, ctx: &Context, page: usize) -> Result<Page, CommandError> {
+        gen_status_message(ctx, self, i64::try_from(page)? * PAGE_SIZE).await
+    }
+}
+
+// Replies with a paged list of the custom statuses a user has set, most recent first
+// Takes a single optional argument of the user to show statuses for, defaults to the invoking user
+pub async fn statushistory(ctx: &Context, interaction: &CommandInteraction) -> CommandResult {
+    let Some(guild_id) = interaction.guild_id else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Command can only be used in a server"),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    let user_id = match interaction.data.options.iter().find(|o| o.name == "user") {
+        Some(o) => match o.value {
+            CommandDataOptionValue::User(u) => u,
+            _ => interaction.user.id,
+        },
+        None => interaction.user.id,
+    };
+    let members = util::collect_members_guild_id(ctx, guild_id).await?;
+
+    let pages = StatusHistoryPages {
+        user_id: i64::from(user_id),
+        username: util::get_username_userid(&ctx.http, &members, user_id).await,
+        timezone: String::from(user_timezone(ctx, interaction.user.id).await?.name()),
+    };
+
+    paginate::send(ctx, interaction, &pages, false).await
+}
+
+async fn gen_status_message(
+    ctx: &Context,
+    pages: &StatusHistoryPages,
+    offset: i64,
+) -> Result<Page, CommandError> {
+    let rows = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        let db = data.get::<DB>().unwrap();
+        // the same status is recorded again every time the user comes back online, so consecutive rows with the same status are merged
+        #[allow(clippy::panic)]
+        sqlx::query!(
+            r#"
+WITH flagged AS (
+  SELECT emoji, state, start_date, end_date,
+    CASE WHEN (emoji, state) IS NOT DISTINCT FROM (LAG(emoji) OVER w, LAG(state) OVER w) THEN 0 ELSE 1 END AS changed
+  FROM user_activity
+  WHERE user_id = $1
+  AND kind = 'custom'
+  WINDOW w AS (ORDER BY start_date)
+), grouped AS (
+  SELECT *, SUM(changed) OVER (ORDER BY start_date) AS status_group
+  FROM flagged
+)
+SELECT emoji, state, min(start_date) AS "set_date!", bool_or(end_date IS NULL) AS "current!", max(end_date) AS last_seen, count(*) OVER () AS "total!"
+FROM grouped
+GROUP BY status_group, emoji, state
+ORDER BY 3 DESC
+LIMIT $2 OFFSET $3"#,
+            pages.user_id,
+            PAGE_SIZE,
+            offset
+        )
+        .fetch_all(db)
+        .await?
+    };
+
+    let Some(total) = rows.first().map(|r| r.total) else {
+        return Ok(Page {
+            content: format!("I've never seen {} set a custom status", pages.username),
+            page_count: 1,
+        });
+    };
+
+    let tz: Tz = pages.timezone.parse().unwrap_or(chrono_tz::UTC);
+    let lines: Vec<String> = rows
+        .into_iter()
+        .map(|r| {
+            let until = match (r.current, r.last_seen) {
+                (false, Some(last_seen)) => {
+                    last_seen.with_timezone(&tz).format("%b %d, %Y").to_string()
+                }
+                _ => String::from("now"),
+            };
+            let mut line = MessageBuilder::new();
+            line.push_bold_safe(format!(
+                "{} \u{2013} {until}",
+                r.set_date.with_timezone(&tz).format("%b %d, %Y")
+            ))
+            .push(" ");
+            // emoji are either unicode or built from a custom emoji's name and id, so they're pushed as is to still render
+            if let Some(emoji) = r.emoji {
+                line.push(" ").push(emoji);
+            }
+            if let Some(state) = r.state {
+                line.push(" ").push_safe(state);
+            }
+            line.push("\n").build()
+        })
+        .collect();
+
+    let page_count = usize::try_from((total + PAGE_SIZE - 1) / PAGE_SIZE)?;
+    Ok(Page {
+        content: format!(
+            "Custom statuses of {} - Page {}/{page_count}\n\n{}",
+            pages.username,
+            offset / PAGE_SIZE + 1,
+            lines.concat()
+        ),
+        page_count,
+    })
+}
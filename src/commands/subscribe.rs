@@ -0,0 +1,253 @@
+use crate::error::{CommandError, CommandResult};
+use crate::model::DB;
+use crate::util;
+use serenity::all::{CommandDataOptionValue, CommandInteraction};
+use serenity::builder::EditInteractionResponse;
+use serenity::client::Context;
+use std::fmt::Write as _;
+
+// Cooldown used when none is given, in minutes
+const DEFAULT_COOLDOWN: i32 = 60;
+
+// Subscribes the invoking user to be notified when members of the guild start playing a game
+// Takes one required and five optional arguments
+// game: game to be notified about, aliases are merged into the game they're an alias of
+// players: only notify once at least this many members, not counting the subscriber, are playing
+// channel: ping in this channel instead of sending a DM
+// quiet_start, quiet_end: hours of the day in the subscriber's timezone to not notify during
+// cooldown: minutes to wait after a notification before notifying about the game again
+pub async fn subscribe(ctx: &Context, interaction: &CommandInteraction) -> CommandResult {
+    let Some(guild_id) = interaction.guild_id else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Command can only be used in a server"),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    let options = &interaction.data.options;
+    let game = util::get_string_option(options, "game")
+        .unwrap_or_default()
+        .trim();
+    if game.is_empty() {
+        return Err("Missing required arguments".into());
+    }
+    let integer_option = |name: &str| {
+        options.iter().find(|o| o.name == name).and_then(|o| {
+            if let CommandDataOptionValue::Integer(i) = o.value {
+                Some(i)
+            } else {
+                None
+            }
+        })
+    };
+    let min_players = i32::try_from(integer_option("players").unwrap_or(1))?;
+    let cooldown = integer_option("cooldown").map_or(Ok(DEFAULT_COOLDOWN), i32::try_from)?;
+    let quiet_hours = match (integer_option("quiet_start"), integer_option("quiet_end")) {
+        (Some(start), Some(end)) => Some((i16::try_from(start)?, i16::try_from(end)?)),
+        (None, None) => None,
+        _ => {
+            interaction
+                .edit_response(
+                    &ctx.http,
+                    EditInteractionResponse::new()
+                        .content("Quiet hours need both a `quiet_start` and a `quiet_end`"),
+                )
+                .await?;
+            return Ok(());
+        }
+    };
+    let channel_id = options.iter().find(|o| o.name == "channel").and_then(|o| {
+        if let CommandDataOptionValue::Channel(c) = o.value {
+            Some(c)
+        } else {
+            None
+        }
+    });
+
+    let game = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        let db = data.get::<DB>().unwrap();
+        // subscriptions are to the canonical name, so they match the game no matter which alias is reported
+        #[allow(clippy::panic)]
+        let game = sqlx::query!(
+            "SELECT game FROM game_alias WHERE guild_id = $1 AND alias = $2",
+            i64::from(guild_id),
+            game
+        )
+        .fetch_optional(db)
+        .await?
+        .map_or_else(|| String::from(game), |r| r.game);
+        #[allow(clippy::panic)]
+        sqlx::query!(
+            "INSERT INTO game_subscription (guild_id, user_id, game, min_players, channel_id, quiet_start, quiet_end, cooldown_minutes) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+             ON CONFLICT ON CONSTRAINT game_subscription_guild_id_user_id_game_key DO UPDATE SET min_players = $4, channel_id = $5, quiet_start = $6, quiet_end = $7, cooldown_minutes = $8",
+            i64::from(guild_id),
+            i64::from(interaction.user.id),
+            game,
+            min_players,
+            channel_id.map(i64::from),
+            quiet_hours.map(|(start, _)| start),
+            quiet_hours.map(|(_, end)| end),
+            cooldown
+        )
+        .execute(db)
+        .await?;
+        game
+    };
+
+    let mut content = String::from("You'll be notified when ");
+    if min_players > 1 {
+        write!(
+            content,
+            "{min_players} or more members are playing `{game}`"
+        )?;
+    } else {
+        write!(content, "someone starts playing `{game}`")?;
+    }
+    match channel_id {
+        Some(channel_id) => write!(content, " in <#{channel_id}>")?,
+        None => content.push_str(" by DM"),
+    }
+    if let Some((start, end)) = quiet_hours {
+        write!(
+            content,
+            ", except between {start}:00 and {end}:00 in your timezone"
+        )?;
+    }
+    write!(
+        content,
+        ". You won't be notified about it again for {cooldown} minutes after each notification"
+    )?;
+
+    interaction
+        .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+        .await?;
+
+    Ok(())
+}
+
+// Removes one of the invoking user's game subscriptions in the guild
+// Takes a single required argument of the game to unsubscribe from
+pub async fn unsubscribe(ctx: &Context, interaction: &CommandInteraction) -> CommandResult {
+    let Some(guild_id) = interaction.guild_id else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Command can only be used in a server"),
+            )
+            .await?;
+        return Ok(());
+    };
+    let game = util::get_string_option(&interaction.data.options, "game")
+        .unwrap_or_default()
+        .trim();
+
+    let deleted = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        let db = data.get::<DB>().unwrap();
+        #[allow(clippy::panic)]
+        sqlx::query!(
+            "DELETE FROM game_subscription WHERE guild_id = $1 AND user_id = $2 AND game = COALESCE((SELECT game FROM game_alias WHERE guild_id = $1 AND alias = $3), $3) RETURNING game",
+            i64::from(guild_id),
+            i64::from(interaction.user.id),
+            game
+        )
+        .fetch_optional(db)
+        .await?
+    };
+
+    let content = match deleted {
+        Some(row) => format!("You'll no longer be notified about `{}`", row.game),
+        None => format!("You aren't subscribed to `{game}`"),
+    };
+    interaction
+        .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+        .await?;
+
+    Ok(())
+}
+
+// Replies with the invoking user's game subscriptions in the guild
+pub async fn subscriptions(ctx: &Context, interaction: &CommandInteraction) -> CommandResult {
+    let Some(guild_id) = interaction.guild_id else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Command can only be used in a server"),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    let rows = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        let db = data.get::<DB>().unwrap();
+        #[allow(clippy::panic)]
+        sqlx::query!(
+            "SELECT game, min_players, channel_id, quiet_start, quiet_end, cooldown_minutes FROM game_subscription WHERE guild_id = $1 AND user_id = $2 ORDER BY game",
+            i64::from(guild_id),
+            i64::from(interaction.user.id)
+        )
+        .fetch_all(db)
+        .await?
+    };
+
+    let content = if rows.is_empty() {
+        String::from("You aren't subscribed to any games, subscribe to one with `/subscribe`")
+    } else {
+        let mut content = String::from("Your game subscriptions\n\n");
+        for row in rows {
+            write!(
+                content,
+                "**{}** \u{2014} {}+ players, {}, {} minute cooldown",
+                row.game,
+                row.min_players,
+                row.channel_id
+                    .map_or_else(|| String::from("by DM"), |c| format!("in <#{c}>")),
+                row.cooldown_minutes
+            )?;
+            if let (Some(start), Some(end)) = (row.quiet_start, row.quiet_end) {
+                write!(content, ", quiet {start}:00\u{2013}{end}:00")?;
+            }
+            content.push('\n');
+        }
+        content
+    };
+    interaction
+        .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+        .await?;
+
+    Ok(())
+}
+
+// Suggests the games the invoking user is subscribed to in the guild, as (name, value) pairs
+pub async fn autocomplete(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    value: &str,
+) -> Result<Vec<(String, String)>, CommandError> {
+    let Some(guild_id) = interaction.guild_id else {
+        return Ok(vec![]);
+    };
+
+    let data = ctx.data.read().await;
+    #[allow(clippy::unwrap_used)]
+    let db = data.get::<DB>().unwrap();
+    #[allow(clippy::panic)]
+    let rows = sqlx::query!(
+        "SELECT game FROM game_subscription WHERE guild_id = $1 AND user_id = $2 AND strpos(lower(game), $3) > 0 ORDER BY game LIMIT 25",
+        i64::from(guild_id),
+        i64::from(interaction.user.id),
+        value.to_lowercase()
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.into_iter().map(|r| (r.game.clone(), r.game)).collect())
+}
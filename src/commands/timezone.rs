@@ -0,0 +1,91 @@
+use crate::error::{CommandError, CommandResult};
+use crate::model::DB;
+use chrono::prelude::*;
+use chrono_tz::Tz;
+use serenity::all::{CommandDataOptionValue, CommandInteraction};
+use serenity::builder::EditInteractionResponse;
+use serenity::client::Context;
+use serenity::model::id::UserId;
+
+// Used for users that haven't set a timezone
+pub const DEFAULT_TIMEZONE: Tz = chrono_tz::America::Chicago;
+
+// Sets the timezone used when showing times to the invoking user, or replies with their current timezone
+// Takes a single optional argument of an IANA timezone name (America/New_York, Europe/Oslo, etc)
+pub async fn timezone(ctx: &Context, interaction: &CommandInteraction) -> CommandResult {
+    let Some(CommandDataOptionValue::String(name)) =
+        interaction.data.options.first().map(|o| &o.value)
+    else {
+        let tz = user_timezone(ctx, interaction.user.id).await?;
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content(format!(
+                    "Your timezone is {} (currently {})",
+                    tz.name(),
+                    Utc::now().with_timezone(&tz).format("%H:%M")
+                )),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    let Ok(tz) = name.trim().parse::<Tz>() else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content(format!(
+                    "Unknown timezone `{}`, use a name like `America/New_York` or `Europe/Oslo`",
+                    name.trim()
+                )),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        let db = data.get::<DB>().unwrap();
+        #[allow(clippy::panic)]
+        sqlx::query!(
+            "INSERT INTO user_timezone(user_id, timezone) VALUES ($1, $2)
+             ON CONFLICT ON CONSTRAINT user_timezone_pkey DO UPDATE SET timezone = $2",
+            i64::from(interaction.user.id),
+            tz.name()
+        )
+        .execute(db)
+        .await?;
+    }
+
+    interaction
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new().content(format!(
+                "Timezone set to {} (currently {})",
+                tz.name(),
+                Utc::now().with_timezone(&tz).format("%H:%M")
+            )),
+        )
+        .await?;
+
+    Ok(())
+}
+
+// Returns the timezone the user has set, or DEFAULT_TIMEZONE if they haven't set one
+pub async fn user_timezone(ctx: &Context, user_id: UserId) -> Result<Tz, CommandError> {
+    let data = ctx.data.read().await;
+    #[allow(clippy::unwrap_used)]
+    let db = data.get::<DB>().unwrap();
+    #[allow(clippy::panic)]
+    let row = sqlx::query!(
+        "SELECT timezone FROM user_timezone WHERE user_id = $1",
+        i64::from(user_id)
+    )
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row
+        .and_then(|r| r.timezone.parse().ok())
+        .unwrap_or(DEFAULT_TIMEZONE))
+}
@@ -1,4 +1,4 @@
-use crate::error::CommandResult;
+use crate::error::{CommandError, CommandResult};
 use crate::model::DB;
 use crate::util;
 use serenity::all::{CommandDataOptionValue, CommandInteraction};
@@ -63,3 +63,22 @@ LIMIT 10"#,
 
     Ok(())
 }
+
+// Suggests names of commands that have been used, most used first, as (name, value) pairs
+pub async fn autocomplete(
+    ctx: &Context,
+    value: &str,
+) -> Result<Vec<(String, String)>, CommandError> {
+    let data = ctx.data.read().await;
+    #[allow(clippy::unwrap_used)]
+    let db = data.get::<DB>().unwrap();
+    #[allow(clippy::panic)]
+    let rows = sqlx::query!(
+        "SELECT name FROM command WHERE strpos(name, $1) > 0 GROUP BY name ORDER BY count(*) DESC, name LIMIT 25",
+        value.to_lowercase()
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.into_iter().map(|r| (r.name.clone(), r.name)).collect())
+}
@@ -0,0 +1,295 @@
+use crate::commands::playtime::{OFFSET_INC, user_ids_and_name_from_option};
+use crate::error::{CommandError, CommandResult};
+use crate::model::DB;
+use crate::paginate::{self, Page, Paginate};
+use crate::util;
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+use serenity::all::{CommandDataOptionValue, CommandInteraction};
+use serenity::async_trait;
+use serenity::builder::EditInteractionResponse;
+use serenity::client::Context;
+use serenity::model::id::{ChannelId, GuildId, UserId};
+
+#[derive(Serialize, Deserialize)]
+pub struct VoicetimePages {
+    guild_id: GuildId,
+    user_ids: Vec<i64>,
+    username: Option<String>,
+    start_date: Option<DateTime<Utc>>,
+    end_date: DateTime<Utc>,
+}
+
+#[async_trait]
+impl Paginate for VoicetimePages {
+    const KIND: &'static str = "voicetime";
+
+    async fn render(&self, ctx: &Context, page: usize) -> Result<Page, CommandError> {
+        gen_voicetime_message(
+            ctx,
+            self.guild_id,
+            &self.user_ids,
+            self.username.as_ref(),
+            self.start_date,
+            self.end_date,
+            page * usize::from(OFFSET_INC),
+        )
+        .await
+    }
+}
+
+// Replies with time spent in voice channels by a user or everyone in the guild
+// Takes two optional arguments
+// user: user to show voice time for, along with who they spend the most time in voice with
+// duration: human readable time duration (2 days, 1 hour, 3 months, etc) to limit results to
+pub async fn voicetime(ctx: &Context, interaction: &CommandInteraction) -> CommandResult {
+    let Some(guild_id) = interaction.guild_id else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Command can only be used in a server"),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    let now = Utc::now();
+    let mut start_date = None;
+    if let Some(o) = interaction
+        .data
+        .options
+        .iter()
+        .find(|o| o.name == "duration")
+        && let CommandDataOptionValue::String(d) = &o.value
+    {
+        start_date = util::start_date_from_duration(d.trim(), now)?;
+        if start_date.is_none() {
+            interaction
+                .edit_response(
+                    &ctx.http,
+                    EditInteractionResponse::new().content("```Unable to parse time```"),
+                )
+                .await?;
+            return Ok(());
+        }
+    }
+
+    let (user_ids, username) = match user_ids_and_name_from_option(
+        ctx,
+        guild_id,
+        interaction.data.options.iter().find(|o| o.name == "user"),
+    )
+    .await?
+    {
+        Some(u) => (u.0, u.1),
+        None => return Ok(()),
+    };
+
+    let pages = VoicetimePages {
+        guild_id,
+        user_ids,
+        username,
+        start_date,
+        end_date: now,
+    };
+    paginate::send(ctx, interaction, &pages, false).await
+}
+
+async fn gen_voicetime_message(
+    ctx: &Context,
+    guild_id: GuildId,
+    user_ids: &[i64],
+    username: Option<&String>,
+    start_date: Option<DateTime<Utc>>,
+    end_date: DateTime<Utc>,
+    offset: usize,
+) -> Result<Page, CommandError> {
+    let db = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        data.get::<DB>().unwrap().clone()
+    };
+
+    // sessions are clamped to start_date and end_date so that only time within the range is counted
+    #[allow(clippy::panic)]
+    let channel_rows = sqlx::query!(
+        r#"
+SELECT channel_id,
+  MIN(GREATEST(join_date, COALESCE($3, join_date))) AS "first!",
+  SUM(EXTRACT(EPOCH FROM LEAST(COALESCE(leave_date, $4), $4) - GREATEST(join_date, COALESCE($3, join_date))))::float8 AS "seconds!"
+FROM voice_session
+WHERE guild_id = $1
+AND user_id = ANY($2)
+AND (COALESCE(leave_date, $4) > $3) IS NOT FALSE
+AND join_date <= $4
+GROUP BY channel_id
+ORDER BY 3 DESC"#,
+        i64::from(guild_id),
+        user_ids,
+        start_date,
+        end_date
+    )
+    .fetch_all(&db)
+    .await?;
+
+    if channel_rows.is_empty() {
+        return Ok(Page {
+            content: format!(
+                "```No recorded voice time{}```",
+                if let Some(username) = username {
+                    format!(" for {username}")
+                } else {
+                    String::new()
+                }
+            ),
+            page_count: 1,
+        });
+    }
+
+    // with a single user list who they've shared a channel with the most, otherwise list each member's total
+    #[allow(clippy::panic)]
+    let user_rows: Vec<(i64, f64)> = if let [user_id] = user_ids {
+        sqlx::query!(
+            r#"
+SELECT o.user_id,
+  SUM(GREATEST(0, EXTRACT(EPOCH FROM
+    LEAST(COALESCE(s.leave_date, $3), COALESCE(o.leave_date, $3), $3)
+    - GREATEST(s.join_date, o.join_date, COALESCE($4, s.join_date)))))::float8 AS "seconds!"
+FROM voice_session s
+JOIN voice_session o ON o.guild_id = s.guild_id
+  AND o.channel_id = s.channel_id
+  AND o.user_id <> s.user_id
+  AND o.join_date < COALESCE(s.leave_date, $3)
+  AND COALESCE(o.leave_date, $3) > s.join_date
+WHERE s.guild_id = $1
+AND s.user_id = $2
+AND (COALESCE(s.leave_date, $3) > $4) IS NOT FALSE
+AND s.join_date <= $3
+GROUP BY o.user_id
+ORDER BY 2 DESC"#,
+            i64::from(guild_id),
+            user_id,
+            end_date,
+            start_date
+        )
+        .fetch_all(&db)
+        .await?
+        .into_iter()
+        .map(|r| (r.user_id, r.seconds))
+        .collect()
+    } else {
+        sqlx::query!(
+            r#"
+SELECT user_id,
+  SUM(EXTRACT(EPOCH FROM LEAST(COALESCE(leave_date, $4), $4) - GREATEST(join_date, COALESCE($3, join_date))))::float8 AS "seconds!"
+FROM voice_session
+WHERE guild_id = $1
+AND user_id = ANY($2)
+AND (COALESCE(leave_date, $4) > $3) IS NOT FALSE
+AND join_date <= $4
+GROUP BY user_id
+ORDER BY 2 DESC"#,
+            i64::from(guild_id),
+            user_ids,
+            start_date,
+            end_date
+        )
+        .fetch_all(&db)
+        .await?
+        .into_iter()
+        .map(|r| (r.user_id, r.seconds))
+        .collect()
+    };
+
+    let first_time = channel_rows
+        .iter()
+        .map(|r| r.first)
+        .min()
+        .unwrap_or(end_date);
+    let total_seconds: f64 = channel_rows.iter().map(|r| r.seconds).sum();
+
+    // each entry is a name and hours, entries without hours are section headings
+    let mut entries: Vec<(String, Option<f64>)> =
+        vec![(String::from("All Channels"), Some(total_seconds / 3600_f64))];
+
+    entries.push((String::from("Channels"), None));
+    for row in &channel_rows {
+        let channel_name = {
+            ctx.cache.guild(guild_id).and_then(|g| {
+                g.channels
+                    .get(&ChannelId::new(u64::try_from(row.channel_id).ok()?))
+                    .map(|c| c.name.clone())
+            })
+        };
+        entries.push((
+            channel_name.unwrap_or_else(|| row.channel_id.to_string()),
+            Some(row.seconds / 3600_f64),
+        ));
+    }
+
+    if !user_rows.is_empty() {
+        entries.push((
+            String::from(if username.is_some() {
+                "Companions"
+            } else {
+                "Members"
+            }),
+            None,
+        ));
+        let members = util::collect_members_guild_id(ctx, guild_id).await?;
+        for (user_id, seconds) in user_rows {
+            let name = util::get_username_userid(
+                &ctx.http,
+                &members,
+                UserId::new(u64::try_from(user_id)?),
+            )
+            .await;
+            entries.push((name, Some(seconds / 3600_f64)));
+        }
+    }
+
+    let min_offset = offset.min(entries.len());
+    let max_offset = (offset + usize::from(OFFSET_INC)).min(entries.len());
+    let total_lines = entries.len();
+    let entries = &entries[min_offset..max_offset];
+    let longest_name = entries
+        .iter()
+        .filter(|e| e.1.is_some())
+        .map(|e| e.0.len())
+        .max()
+        .unwrap_or(0);
+
+    let lines: Vec<String> = entries
+        .iter()
+        .map(|(name, hours)| match hours {
+            Some(hours) => format!("{name:>longest_name$} \u{2014} {hours:.2}\n"),
+            None => format!("\n{name}\n"),
+        })
+        .collect();
+
+    let mut time_format_string = "%b %d, %Y";
+    if let Some(start_date) = start_date
+        && (end_date - start_date).num_days() < 1
+    {
+        time_format_string = "%l:%M%p";
+    }
+
+    let page_count = total_lines.div_ceil(usize::from(OFFSET_INC));
+    let content = format!(
+        "```{} {} - Page {}/{}\n\n{}```",
+        if let Some(username) = username {
+            format!("{username} since")
+        } else {
+            String::from("Since")
+        },
+        first_time.with_timezone(&Local).format(time_format_string),
+        (offset / usize::from(OFFSET_INC)) + 1,
+        page_count,
+        lines.concat()
+    );
+
+    Ok(Page {
+        content,
+        page_count,
+    })
+}
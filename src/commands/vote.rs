@@ -1,8 +1,9 @@
 use crate::error::{CommandError, CommandResult};
 use crate::model::DB;
+use crate::util;
 use chrono::{Duration, prelude::*};
 use rand::{Rng, thread_rng};
-use serenity::all::{CommandDataOptionValue, CommandInteraction, UserId};
+use serenity::all::{ChannelId, CommandDataOptionValue, CommandInteraction, MessageId, UserId};
 use serenity::builder::EditInteractionResponse;
 use serenity::client::Context;
 use serenity::model::id::GuildId;
@@ -22,19 +23,19 @@ pub async fn vote_from_interaction(
             .await?;
         return Ok(());
     };
-    let user_id = if let Some(o) = interaction.data.options.first() {
-        if let CommandDataOptionValue::User(u) = o.value {
-            u
-        } else {
-            interaction
-                .edit_response(
-                    &ctx.http,
-                    EditInteractionResponse::new().content("Unable to find user"),
-                )
-                .await?;
-            return Ok(());
-        }
-    } else {
+    let Some(user_id) = interaction
+        .data
+        .options
+        .iter()
+        .find(|o| o.name == "user")
+        .and_then(|o| {
+            if let CommandDataOptionValue::User(u) = o.value {
+                Some(u)
+            } else {
+                None
+            }
+        })
+    else {
         interaction
             .edit_response(
                 &ctx.http,
@@ -43,8 +44,24 @@ pub async fn vote_from_interaction(
             .await?;
         return Ok(());
     };
-    if let Some(reply) =
-        process_vote(ctx, is_upvote, interaction.user.id, guild_id, user_id).await?
+    let reason = util::get_string_option(&interaction.data.options, "reason")
+        .map(str::trim)
+        .filter(|r| !r.is_empty());
+    if let Some(Some(reply)) = process_votes(
+        ctx,
+        interaction.user.id,
+        guild_id,
+        interaction.channel_id,
+        None,
+        &[Vote {
+            is_upvote,
+            votee_id: user_id,
+            count: 1,
+            reason,
+        }],
+    )
+    .await?
+    .pop()
     {
         interaction
             .edit_response(&ctx.http, EditInteractionResponse::new().content(reply))
@@ -53,84 +70,272 @@ pub async fn vote_from_interaction(
     Ok(())
 }
 
-pub async fn process_vote(
+// Rules votes in a guild have to follow, admins can change them with /voteconfig
+// Anything a guild hasn't set uses the defaults below
+pub struct VoteConfig {
+    // voters wait a random time in this range, in seconds, after voting before voting again
+    pub cooldown: (i32, i32),
+    // hours after a vote that the votee can't vote on the voter and the voter can't vote on the votee again
+    pub reciprocal_hours: i32,
+    // most votes a voter can make on the same member each day, 0 is unlimited
+    pub max_daily_votes: i32,
+    pub min_account_days: i32,
+    pub min_member_days: i32,
+    // most votes that can be made on a member at once with += N, 1 or less turns bulk votes off
+    pub max_bulk_votes: i32,
+    // days after a karma season starts that it's ended and a new one started, 0 only ends them manually
+    pub season_days: i32,
+    // channels votes can be done in, empty is every channel
+    pub channel_ids: Vec<i64>,
+    pub self_vote_reply: String,
+    pub cooldown_reply: String,
+    pub reciprocal_reply: String,
+    pub limit_reply: String,
+    pub too_new_reply: String,
+    pub channel_reply: String,
+    // reacting to a message with these votes on its author, None if voting by reaction is off
+    pub upvote_emoji: Option<String>,
+    pub downvote_emoji: Option<String>,
+}
+
+pub const DEFAULT_COOLDOWN: (i32, i32) = (300, 600);
+pub const DEFAULT_RECIPROCAL_HOURS: i32 = 12;
+pub const DEFAULT_SELF_VOTE_REPLY: &str = "No.";
+pub const DEFAULT_COOLDOWN_REPLY: &str = "Slow down champ.";
+pub const DEFAULT_RECIPROCAL_REPLY: &str = "Really?...";
+pub const DEFAULT_LIMIT_REPLY: &str = "That's enough votes for them today.";
+pub const DEFAULT_TOO_NEW_REPLY: &str = "You're too new here to vote.";
+pub const DEFAULT_CHANNEL_REPLY: &str = "Votes can't be done in this channel.";
+const BULK_DISABLED_REPLY: &str = "Only one vote can be made at once.";
+
+pub async fn vote_config(db: &PgPool, guild_id: i64) -> Result<VoteConfig, sqlx::Error> {
+    #[allow(clippy::panic)]
+    let row = sqlx::query!("SELECT * FROM vote_config WHERE guild_id = $1", guild_id)
+        .fetch_optional(db)
+        .await?;
+    let row = row.as_ref();
+    let text = |value: Option<&Option<String>>, default: &str| {
+        value
+            .and_then(Option::clone)
+            .unwrap_or_else(|| String::from(default))
+    };
+    Ok(VoteConfig {
+        cooldown: (
+            row.and_then(|r| r.cooldown_min_seconds)
+                .unwrap_or(DEFAULT_COOLDOWN.0),
+            row.and_then(|r| r.cooldown_max_seconds)
+                .unwrap_or(DEFAULT_COOLDOWN.1),
+        ),
+        reciprocal_hours: row
+            .and_then(|r| r.reciprocal_hours)
+            .unwrap_or(DEFAULT_RECIPROCAL_HOURS),
+        max_daily_votes: row.and_then(|r| r.max_daily_votes).unwrap_or_default(),
+        min_account_days: row.and_then(|r| r.min_account_days).unwrap_or_default(),
+        min_member_days: row.and_then(|r| r.min_member_days).unwrap_or_default(),
+        max_bulk_votes: row.and_then(|r| r.max_bulk_votes).unwrap_or_default(),
+        season_days: row.and_then(|r| r.season_days).unwrap_or_default(),
+        channel_ids: row.and_then(|r| r.channel_ids.clone()).unwrap_or_default(),
+        self_vote_reply: text(row.map(|r| &r.self_vote_reply), DEFAULT_SELF_VOTE_REPLY),
+        cooldown_reply: text(row.map(|r| &r.cooldown_reply), DEFAULT_COOLDOWN_REPLY),
+        reciprocal_reply: text(row.map(|r| &r.reciprocal_reply), DEFAULT_RECIPROCAL_REPLY),
+        limit_reply: text(row.map(|r| &r.limit_reply), DEFAULT_LIMIT_REPLY),
+        too_new_reply: text(row.map(|r| &r.too_new_reply), DEFAULT_TOO_NEW_REPLY),
+        channel_reply: text(row.map(|r| &r.channel_reply), DEFAULT_CHANNEL_REPLY),
+        upvote_emoji: row.and_then(|r| r.upvote_emoji.clone()),
+        downvote_emoji: row.and_then(|r| r.downvote_emoji.clone()),
+    })
+}
+
+// A vote on a member, see process_votes
+pub struct Vote<'a> {
+    pub is_upvote: bool,
+    pub votee_id: UserId,
+    // how many votes to make at once, more than one is only allowed up to the guild's bulk vote limit
+    pub count: i32,
+    pub reason: Option<&'a str>,
+}
+
+// Records each vote that follows the guild's vote rules
+// Votes made together, like from one message, only wait out the cooldown once
+// message_id is the message reacted to when voting by reaction, so the vote can be reverted if the reaction is removed
+// Returns the reply to send for each vote, None if it was recorded
+#[allow(clippy::similar_names)]
+pub async fn process_votes(
     ctx: &Context,
-    is_upvote: bool,
-    author_id: UserId,
+    voter_id: UserId,
     guild_id: GuildId,
-    user_id: UserId,
-) -> Result<Option<&'static str>, CommandError> {
-    let author_id = i64::from(author_id);
-    let guild_id = i64::from(guild_id);
-    let user_id = i64::from(user_id);
+    channel_id: ChannelId,
+    message_id: Option<MessageId>,
+    votes: &[Vote<'_>],
+) -> Result<Vec<Option<String>>, CommandError> {
     let db = {
         let data = ctx.data.read().await;
         #[allow(clippy::unwrap_used)]
         data.get::<DB>().unwrap().clone()
     };
+    let config = vote_config(&db, i64::from(guild_id)).await?;
+    let now = Utc::now();
+    let reject_all = |reply: &str| Ok(votes.iter().map(|_| Some(String::from(reply))).collect());
 
-    if author_id == user_id && is_upvote {
-        record_vote(db, false, author_id, guild_id, author_id).await?;
-        return Ok(Some("No."));
+    if !config.channel_ids.is_empty() && !config.channel_ids.contains(&i64::from(channel_id)) {
+        return reject_all(&config.channel_reply);
     }
 
+    if config.min_account_days > 0 {
+        let created =
+            DateTime::from_timestamp(voter_id.created_at().unix_timestamp(), 0).unwrap_or_default();
+        if now.signed_duration_since(created) < Duration::days(config.min_account_days.into()) {
+            return reject_all(&config.too_new_reply);
+        }
+    }
+    if config.min_member_days > 0 {
+        let joined = guild_id
+            .member(ctx, voter_id)
+            .await?
+            .joined_at
+            .and_then(|j| DateTime::from_timestamp(j.unix_timestamp(), 0));
+        if joined.is_none_or(|j| {
+            now.signed_duration_since(j) < Duration::days(config.min_member_days.into())
+        }) {
+            return reject_all(&config.too_new_reply);
+        }
+    }
+
+    let voter_id = i64::from(voter_id);
+    let guild_id = i64::from(guild_id);
+
     #[allow(clippy::panic)]
     let last_vote_time: DateTime<Utc> = sqlx::query!(
         "SELECT MAX(create_date) AS last FROM vote WHERE voter_id = $1",
-        author_id
+        voter_id
     )
     .fetch_one(&db)
     .await?
     .last
     .unwrap_or_default();
 
-    let now = Utc::now();
-    #[allow(clippy::cast_possible_truncation, clippy::unwrap_used)]
-    if now.signed_duration_since(last_vote_time)
-        < Duration::try_seconds((300.0 + (300.0 * thread_rng().r#gen::<f64>())) as i64).unwrap()
-    {
-        return Ok(Some("Slow down champ."));
-    }
+    let (cooldown_min, cooldown_max) = config.cooldown;
+    let cooldown = if cooldown_max > cooldown_min {
+        thread_rng().gen_range(cooldown_min..=cooldown_max)
+    } else {
+        cooldown_min
+    };
+    let on_cooldown =
+        now.signed_duration_since(last_vote_time) < Duration::seconds(cooldown.into());
 
-    #[allow(clippy::panic, clippy::unwrap_used)]
-    if let Some(last_vote_against) = sqlx::query!(
-        "SELECT voter_id, create_date FROM vote WHERE guild_id = $1 AND votee_id = $2 ORDER BY create_date DESC LIMIT 1",
-        guild_id,
-        author_id)
-        .fetch_optional(&db).await?
-            && last_vote_against.voter_id == user_id && now.signed_duration_since(last_vote_against.create_date) < Duration::try_hours(12).unwrap() {
-                return Ok(Some("Really?..."));
-            }
-    #[allow(clippy::panic, clippy::unwrap_used)]
-    if let Some(last_vote_from) = sqlx::query!(
-        "SELECT votee_id, create_date FROM vote WHERE guild_id = $1 AND voter_id = $2 ORDER BY create_date DESC LIMIT 1",
-        guild_id,
-        author_id)
-        .fetch_optional(&db).await?
-            && last_vote_from.votee_id == user_id && now.signed_duration_since(last_vote_from.create_date) < Duration::try_hours(12).unwrap() {
-                return Ok(Some("Really?..."));
+    let mut replies = Vec::with_capacity(votes.len());
+    for vote in votes {
+        let votee_id = i64::from(vote.votee_id);
+        if votee_id == voter_id && vote.is_upvote {
+            record_vote(
+                db.clone(),
+                false,
+                voter_id,
+                guild_id,
+                voter_id,
+                1,
+                None,
+                None,
+            )
+            .await?;
+            replies.push(Some(config.self_vote_reply.clone()));
+            continue;
+        }
+        if on_cooldown {
+            replies.push(Some(config.cooldown_reply.clone()));
+            continue;
+        }
+        if vote.count > 1 && vote.count > config.max_bulk_votes {
+            replies.push(Some(if config.max_bulk_votes > 1 {
+                format!(
+                    "Only up to {} votes can be made at once.",
+                    config.max_bulk_votes
+                )
+            } else {
+                String::from(BULK_DISABLED_REPLY)
+            }));
+            continue;
+        }
+
+        let reciprocal_window = Duration::hours(config.reciprocal_hours.into());
+        #[allow(clippy::panic)]
+        if let Some(last_vote_against) = sqlx::query!(
+            "SELECT voter_id, create_date FROM vote WHERE guild_id = $1 AND votee_id = $2 ORDER BY create_date DESC LIMIT 1",
+            guild_id,
+            voter_id)
+            .fetch_optional(&db).await?
+                && last_vote_against.voter_id == votee_id && now.signed_duration_since(last_vote_against.create_date) < reciprocal_window {
+                    replies.push(Some(config.reciprocal_reply.clone()));
+                    continue;
+                }
+        #[allow(clippy::panic)]
+        if let Some(last_vote_from) = sqlx::query!(
+            "SELECT votee_id, create_date FROM vote WHERE guild_id = $1 AND voter_id = $2 AND create_date < $3 ORDER BY create_date DESC LIMIT 1",
+            guild_id,
+            voter_id,
+            now)
+            .fetch_optional(&db).await?
+                && last_vote_from.votee_id == votee_id && now.signed_duration_since(last_vote_from.create_date) < reciprocal_window {
+                    replies.push(Some(config.reciprocal_reply.clone()));
+                    continue;
+                }
+
+        if config.max_daily_votes > 0 {
+            #[allow(clippy::panic)]
+            let votes_today = sqlx::query!(
+                r#"SELECT count(*) AS "count!" FROM vote WHERE guild_id = $1 AND voter_id = $2 AND votee_id = $3 AND create_date > now() - interval '1 day'"#,
+                guild_id,
+                voter_id,
+                votee_id
+            )
+            .fetch_one(&db)
+            .await?
+            .count;
+            if votes_today + i64::from(vote.count) > i64::from(config.max_daily_votes) {
+                replies.push(Some(config.limit_reply.clone()));
+                continue;
             }
+        }
 
-    record_vote(db, is_upvote, author_id, guild_id, user_id).await?;
+        record_vote(
+            db.clone(),
+            vote.is_upvote,
+            voter_id,
+            guild_id,
+            votee_id,
+            vote.count,
+            vote.reason,
+            message_id.map(i64::from),
+        )
+        .await?;
+        replies.push(None);
+    }
 
-    Ok(None)
+    Ok(replies)
 }
 
-#[allow(clippy::similar_names)]
+// Records count votes from voter_id on votee_id
+#[allow(clippy::similar_names, clippy::too_many_arguments)]
 pub async fn record_vote(
     db: PgPool,
     is_upvote: bool,
     voter_id: i64,
     guild_id: i64,
     votee_id: i64,
+    count: i32,
+    reason: Option<&str>,
+    message_id: Option<i64>,
 ) -> Result<(), sqlx::Error> {
     #[allow(clippy::panic)]
     sqlx::query!(
-        "INSERT INTO vote(guild_id, voter_id, votee_id, is_upvote) VALUES ($1, $2, $3, $4)",
+        "INSERT INTO vote(guild_id, voter_id, votee_id, is_upvote, reason, message_id) SELECT $1, $2, $3, $4, $5, $6 FROM generate_series(1, $7)",
         guild_id,
         voter_id,
         votee_id,
-        is_upvote
+        is_upvote,
+        reason,
+        message_id,
+        count
     )
     .execute(&db)
     .await?;
@@ -141,9 +346,83 @@ pub async fn record_vote(
          ON CONFLICT ON CONSTRAINT user_karma_pkey DO UPDATE SET karma = user_karma.karma + $3",
         guild_id,
         votee_id,
-        if is_upvote { 1 } else { -1 },
+        if is_upvote { count } else { -count },
     )
     .execute(&db)
     .await?;
     Ok(())
 }
+
+// Removes the vote a voter made by reacting to a message and takes back the karma it gave
+// Returns false if there was no vote to remove, like when the vote was rejected
+pub async fn revert_vote(
+    db: &PgPool,
+    is_upvote: bool,
+    voter_id: i64,
+    guild_id: i64,
+    message_id: i64,
+) -> Result<bool, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    #[allow(clippy::panic)]
+    let Some(vote) = sqlx::query!(
+        "DELETE FROM vote WHERE id = (SELECT id FROM vote WHERE message_id = $1 AND guild_id = $2 AND voter_id = $3 AND is_upvote = $4 ORDER BY create_date DESC LIMIT 1) RETURNING votee_id",
+        message_id,
+        guild_id,
+        voter_id,
+        is_upvote
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    else {
+        return Ok(false);
+    };
+
+    #[allow(clippy::panic)]
+    sqlx::query!(
+        "UPDATE user_karma SET karma = karma - $3 WHERE guild_id = $1 AND user_id = $2",
+        guild_id,
+        vote.votee_id,
+        if is_upvote { 1 } else { -1 },
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+// Removes every vote made by reacting to the messages and takes back the karma they gave
+// is_upvote only removes votes in that direction, for when one vote emoji's reactions are cleared
+// Returns how many votes were removed
+pub async fn revert_message_votes(
+    db: &PgPool,
+    message_ids: &[i64],
+    is_upvote: Option<bool>,
+) -> Result<u64, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    #[allow(clippy::panic)]
+    let reverted = sqlx::query!(
+        "DELETE FROM vote WHERE message_id = ANY($1) AND ($2::boolean IS NULL OR is_upvote = $2) RETURNING guild_id, votee_id, is_upvote",
+        message_ids,
+        is_upvote
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    for vote in &reverted {
+        #[allow(clippy::panic)]
+        sqlx::query!(
+            "UPDATE user_karma SET karma = karma - $3 WHERE guild_id = $1 AND user_id = $2",
+            vote.guild_id,
+            vote.votee_id,
+            if vote.is_upvote { 1 } else { -1 },
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(reverted.len() as u64)
+}
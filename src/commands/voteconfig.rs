@@ -0,0 +1,456 @@
+use crate::commands::vote::{self, DEFAULT_COOLDOWN};
+use crate::error::{CommandError, CommandResult};
+use crate::model::DB;
+use crate::season;
+use crate::util;
+use serenity::all::{
+    CommandDataOption, CommandDataOptionValue, CommandInteraction, GuildId, ReactionType,
+};
+use serenity::builder::EditInteractionResponse;
+use serenity::client::Context;
+use std::fmt::Write as _;
+
+// Replies with the vote rules of the guild
+pub async fn show(ctx: &Context, interaction: &CommandInteraction) -> CommandResult {
+    let Some(guild_id) = interaction.guild_id else {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new().content("Command can only be used in a server"),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    let content = describe(ctx, guild_id).await?;
+    interaction
+        .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+        .await?;
+
+    Ok(())
+}
+
+// Changes the vote rules of the guild, admin only
+// Every argument is optional, rules that aren't given are left as they are
+// cooldown_min, cooldown_max: range of seconds voters wait after voting before they can vote again
+// reciprocal_hours: hours after a vote that the two members can't vote on each other
+// daily_limit: most votes a member can make on the same member each day, 0 is unlimited
+// account_days, member_days: how old a voter's account and membership in the server have to be
+// bulk_limit: most votes that can be made on a member at once with += N, 1 or less turns bulk votes off
+// season_days: days after a karma season starts that a new one is started, 0 only starts them with /voteconfig newseason
+pub async fn set(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    options: &[CommandDataOption],
+) -> CommandResult {
+    let Some(guild_id) = admin_guild(ctx, interaction).await? else {
+        return Ok(());
+    };
+    let integer_option = |name: &str| -> Result<Option<i32>, std::num::TryFromIntError> {
+        options
+            .iter()
+            .find(|o| o.name == name)
+            .and_then(|o| {
+                if let CommandDataOptionValue::Integer(i) = o.value {
+                    Some(i)
+                } else {
+                    None
+                }
+            })
+            .map(i32::try_from)
+            .transpose()
+    };
+    let cooldown_min = integer_option("cooldown_min")?;
+    let cooldown_max = integer_option("cooldown_max")?;
+
+    {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        let db = data.get::<DB>().unwrap();
+        let config = vote::vote_config(db, i64::from(guild_id)).await?;
+        if cooldown_min.unwrap_or(config.cooldown.0) > cooldown_max.unwrap_or(config.cooldown.1) {
+            interaction
+                .edit_response(
+                    &ctx.http,
+                    EditInteractionResponse::new()
+                        .content("The minimum cooldown can't be longer than the maximum cooldown"),
+                )
+                .await?;
+            return Ok(());
+        }
+        #[allow(clippy::panic)]
+        sqlx::query!(
+            "INSERT INTO vote_config(guild_id, cooldown_min_seconds, cooldown_max_seconds, reciprocal_hours, max_daily_votes, min_account_days, min_member_days, max_bulk_votes, season_days) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+             ON CONFLICT ON CONSTRAINT vote_config_pkey DO UPDATE SET
+               cooldown_min_seconds = COALESCE($2, vote_config.cooldown_min_seconds),
+               cooldown_max_seconds = COALESCE($3, vote_config.cooldown_max_seconds),
+               reciprocal_hours = COALESCE($4, vote_config.reciprocal_hours),
+               max_daily_votes = COALESCE($5, vote_config.max_daily_votes),
+               min_account_days = COALESCE($6, vote_config.min_account_days),
+               min_member_days = COALESCE($7, vote_config.min_member_days),
+               max_bulk_votes = COALESCE($8, vote_config.max_bulk_votes),
+               season_days = COALESCE($9, vote_config.season_days)",
+            i64::from(guild_id),
+            cooldown_min,
+            cooldown_max,
+            integer_option("reciprocal_hours")?,
+            integer_option("daily_limit")?,
+            integer_option("account_days")?,
+            integer_option("member_days")?,
+            integer_option("bulk_limit")?,
+            integer_option("season_days")?
+        )
+        .execute(db)
+        .await?;
+    }
+
+    let content = describe(ctx, guild_id).await?;
+    interaction
+        .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+        .await?;
+
+    Ok(())
+}
+
+// Changes what the bot replies with when a vote is rejected, admin only
+// Takes one required and one optional argument
+// kind: which rejection to change the reply of
+// text: the new reply, the default reply is used again if not given
+pub async fn reply(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    options: &[CommandDataOption],
+) -> CommandResult {
+    let Some(guild_id) = admin_guild(ctx, interaction).await? else {
+        return Ok(());
+    };
+    let Some(kind) = util::get_string_option(options, "kind") else {
+        return Err("Missing required arguments".into());
+    };
+    let text = util::get_string_option(options, "text")
+        .map(str::trim)
+        .filter(|t| !t.is_empty());
+
+    {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        let db = data.get::<DB>().unwrap();
+        #[allow(clippy::panic)]
+        sqlx::query!(
+            "INSERT INTO vote_config(guild_id) VALUES ($1) ON CONFLICT ON CONSTRAINT vote_config_pkey DO NOTHING",
+            i64::from(guild_id)
+        )
+        .execute(db)
+        .await?;
+        #[allow(clippy::panic)]
+        sqlx::query!(
+            "UPDATE vote_config SET
+               self_vote_reply = CASE WHEN $2 = 'self' THEN $3 ELSE self_vote_reply END,
+               cooldown_reply = CASE WHEN $2 = 'cooldown' THEN $3 ELSE cooldown_reply END,
+               reciprocal_reply = CASE WHEN $2 = 'reciprocal' THEN $3 ELSE reciprocal_reply END,
+               limit_reply = CASE WHEN $2 = 'limit' THEN $3 ELSE limit_reply END,
+               too_new_reply = CASE WHEN $2 = 'age' THEN $3 ELSE too_new_reply END,
+               channel_reply = CASE WHEN $2 = 'channel' THEN $3 ELSE channel_reply END
+             WHERE guild_id = $1",
+            i64::from(guild_id),
+            kind,
+            text
+        )
+        .execute(db)
+        .await?;
+    }
+
+    let content = describe(ctx, guild_id).await?;
+    interaction
+        .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+        .await?;
+
+    Ok(())
+}
+
+// Allows or disallows votes in a channel, admin only
+// Votes are allowed in every channel until one is allowed, then only in allowed channels until the last one is disallowed
+// Takes a single required argument of the channel to toggle
+pub async fn channel(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    options: &[CommandDataOption],
+) -> CommandResult {
+    let Some(guild_id) = admin_guild(ctx, interaction).await? else {
+        return Ok(());
+    };
+    let Some(channel_id) = options.iter().find(|o| o.name == "channel").and_then(|o| {
+        if let CommandDataOptionValue::Channel(c) = o.value {
+            Some(c)
+        } else {
+            None
+        }
+    }) else {
+        return Err("Missing required arguments".into());
+    };
+
+    // removing the last channel stores NULL, since no channels means votes are allowed everywhere
+    let row = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        let db = data.get::<DB>().unwrap();
+        #[allow(clippy::panic)]
+        sqlx::query!(
+            r#"INSERT INTO vote_config(guild_id, channel_ids) VALUES ($1, ARRAY[$2::int8])
+               ON CONFLICT ON CONSTRAINT vote_config_pkey DO UPDATE SET channel_ids =
+                 CASE WHEN $2 = ANY(vote_config.channel_ids) THEN NULLIF(array_remove(vote_config.channel_ids, $2), '{}')
+                 ELSE array_append(COALESCE(vote_config.channel_ids, '{}'), $2) END
+               RETURNING ($2 = ANY(channel_ids)) IS TRUE AS "allowed!", channel_ids IS NULL AS "unrestricted!""#,
+            i64::from(guild_id),
+            i64::from(channel_id)
+        )
+        .fetch_one(db)
+        .await?
+    };
+
+    let content = if row.allowed {
+        format!("Votes are now allowed in <#{channel_id}>")
+    } else if row.unrestricted {
+        String::from("Votes are now allowed in every channel")
+    } else {
+        format!("Votes are no longer allowed in <#{channel_id}>")
+    };
+    interaction
+        .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+        .await?;
+
+    Ok(())
+}
+
+// Sets the emoji that votes on a message's author when reacted to the message with, admin only
+// Takes one required and one optional argument
+// kind: "upvote" or "downvote"
+// emoji: unicode or custom emoji, voting by reaction of that kind is turned off if not given
+pub async fn emoji(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    options: &[CommandDataOption],
+) -> CommandResult {
+    let Some(guild_id) = admin_guild(ctx, interaction).await? else {
+        return Ok(());
+    };
+    let Some(kind) = util::get_string_option(options, "kind") else {
+        return Err("Missing required arguments".into());
+    };
+    let emoji = match util::get_string_option(options, "emoji")
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .map(ReactionType::try_from)
+    {
+        Some(Ok(e)) => Some(e.to_string()),
+        Some(Err(_)) => {
+            interaction
+                .edit_response(
+                    &ctx.http,
+                    EditInteractionResponse::new().content("That isn't an emoji"),
+                )
+                .await?;
+            return Ok(());
+        }
+        None => None,
+    };
+
+    {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        let db = data.get::<DB>().unwrap();
+        #[allow(clippy::panic)]
+        sqlx::query!(
+            "INSERT INTO vote_config(guild_id, upvote_emoji, downvote_emoji) VALUES ($1, CASE WHEN $2 = 'upvote' THEN $3 END, CASE WHEN $2 = 'downvote' THEN $3 END)
+             ON CONFLICT ON CONSTRAINT vote_config_pkey DO UPDATE SET
+               upvote_emoji = CASE WHEN $2 = 'upvote' THEN $3 ELSE vote_config.upvote_emoji END,
+               downvote_emoji = CASE WHEN $2 = 'downvote' THEN $3 ELSE vote_config.downvote_emoji END",
+            i64::from(guild_id),
+            kind,
+            emoji
+        )
+        .execute(db)
+        .await?;
+    }
+
+    let content = match emoji {
+        Some(emoji) => format!("Reacting to a message with {emoji} now {kind}s its author"),
+        None => format!("Reactions no longer {kind} a message's author"),
+    };
+    interaction
+        .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+        .await?;
+
+    Ok(())
+}
+
+// Archives the karma standings of the current season and starts a new one, admin only
+pub async fn new_season(ctx: &Context, interaction: &CommandInteraction) -> CommandResult {
+    let Some(guild_id) = admin_guild(ctx, interaction).await? else {
+        return Ok(());
+    };
+
+    let number = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        let db = data.get::<DB>().unwrap();
+        season::end_season(db, i64::from(guild_id)).await?
+    };
+
+    interaction
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new().content(format!(
+                "Season {number} has ended, its standings can be seen with `/karma season:{number}`. Season {} starts now",
+                number + 1
+            )),
+        )
+        .await?;
+
+    Ok(())
+}
+
+// Puts every vote rule and reply of the guild back to the default, admin only
+pub async fn reset(ctx: &Context, interaction: &CommandInteraction) -> CommandResult {
+    let Some(guild_id) = admin_guild(ctx, interaction).await? else {
+        return Ok(());
+    };
+
+    {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        let db = data.get::<DB>().unwrap();
+        #[allow(clippy::panic)]
+        sqlx::query!(
+            "DELETE FROM vote_config WHERE guild_id = $1",
+            i64::from(guild_id)
+        )
+        .execute(db)
+        .await?;
+    }
+
+    interaction
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new().content("Vote rules are back to the defaults"),
+        )
+        .await?;
+
+    Ok(())
+}
+
+// Returns the guild the command was run in if the user can change its vote rules, otherwise replies with why they can't
+async fn admin_guild(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+) -> Result<Option<GuildId>, serenity::Error> {
+    let content = match interaction.guild_id {
+        None => "Command can only be used in a server",
+        Some(_) if !util::is_guild_admin(interaction) => {
+            "Only server admins can change the vote rules"
+        }
+        guild_id => return Ok(guild_id),
+    };
+    interaction
+        .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+        .await?;
+    Ok(None)
+}
+
+async fn describe(ctx: &Context, guild_id: GuildId) -> Result<String, CommandError> {
+    let config = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        let db = data.get::<DB>().unwrap();
+        vote::vote_config(db, i64::from(guild_id)).await?
+    };
+
+    let mut content = String::from("```Vote rules\n\n");
+    writeln!(
+        content,
+        "Cooldown:          {}\u{2013}{} seconds{}",
+        config.cooldown.0,
+        config.cooldown.1,
+        if config.cooldown == DEFAULT_COOLDOWN {
+            " (default)"
+        } else {
+            ""
+        }
+    )?;
+    writeln!(
+        content,
+        "Reciprocal window: {} hours",
+        config.reciprocal_hours
+    )?;
+    if config.max_daily_votes > 0 {
+        writeln!(
+            content,
+            "Daily limit:       {} votes per member",
+            config.max_daily_votes
+        )?;
+    } else {
+        writeln!(content, "Daily limit:       none")?;
+    }
+    writeln!(
+        content,
+        "Account age:       {} days",
+        config.min_account_days
+    )?;
+    writeln!(
+        content,
+        "Membership age:    {} days",
+        config.min_member_days
+    )?;
+    if config.max_bulk_votes > 1 {
+        writeln!(
+            content,
+            "Bulk votes:        up to {} at once",
+            config.max_bulk_votes
+        )?;
+    } else {
+        writeln!(content, "Bulk votes:        off")?;
+    }
+    if config.season_days > 0 {
+        writeln!(content, "Karma seasons:     {} days", config.season_days)?;
+    } else {
+        writeln!(content, "Karma seasons:     started manually")?;
+    }
+    writeln!(content, "\nReplies")?;
+    for (kind, text) in [
+        ("self", &config.self_vote_reply),
+        ("cooldown", &config.cooldown_reply),
+        ("reciprocal", &config.reciprocal_reply),
+        ("limit", &config.limit_reply),
+        ("age", &config.too_new_reply),
+        ("channel", &config.channel_reply),
+    ] {
+        writeln!(content, "{kind:>10}: {text}")?;
+    }
+    content.push_str("```");
+    if config.channel_ids.is_empty() {
+        content.push_str("Votes are allowed in every channel");
+    } else {
+        let channels: Vec<String> = config
+            .channel_ids
+            .iter()
+            .map(|c| format!("<#{c}>"))
+            .collect();
+        write!(content, "Votes are only allowed in {}", channels.join(", "))?;
+    }
+    let reactions: Vec<String> = [
+        (config.upvote_emoji.as_ref(), "upvotes"),
+        (config.downvote_emoji.as_ref(), "downvotes"),
+    ]
+    .into_iter()
+    .filter_map(|(emoji, kind)| emoji.map(|e| format!("{e} {kind}")))
+    .collect();
+    if !reactions.is_empty() {
+        write!(
+            content,
+            "\nReacting to a message votes on its author: {}",
+            reactions.join(", ")
+        )?;
+    }
+
+    Ok(content)
+}
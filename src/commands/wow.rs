@@ -1,5 +1,6 @@
 use crate::config;
-use crate::error::CommandResult;
+use crate::error::{CommandError, CommandResult};
+use crate::model::WowRealms;
 use chrono::{DateTime, Local, LocalResult, TimeZone, Utc};
 use image::{ExtendedColorType, ImageEncoder, ImageFormat, codecs::png::PngEncoder, imageops};
 use reqwest::StatusCode;
@@ -11,9 +12,12 @@ use serenity::model::Timestamp;
 use serenity::model::colour::Colour;
 use std::borrow::Cow;
 use std::fmt;
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, Instant, SystemTime};
 use tracing::{error, warn};
 
+// How long the list of realms used for autocomplete is kept before fetching it again
+const REALM_CACHE_DURATION: Duration = Duration::from_hours(24);
+
 const CLASS_COLOURS: [Colour; 12] = [
     Colour::from_rgb(199, 156, 110),
     Colour::from_rgb(245, 140, 186),
@@ -243,6 +247,48 @@ struct Search {
     results: Vec<SearchResult>,
 }
 
+// Gets (name, slug) of every US realm from the connected realm index, cached for REALM_CACHE_DURATION
+async fn realm_names(ctx: &Context) -> Result<Vec<(String, String)>, CommandError> {
+    let cache = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        data.get::<WowRealms>().unwrap().clone()
+    };
+    if let Some((fetched, realms)) = &*cache.read().await
+        && fetched.elapsed() < REALM_CACHE_DURATION
+    {
+        return Ok(realms.clone());
+    }
+
+    let access_token = get_access_token(ctx).await?;
+    let search: Search = reqwest::Client::new().get(format!("https://us.api.blizzard.com/data/wow/search/connected-realm?namespace=dynamic-us&locale=en_US&orderby=id&_pageSize=1000&access_token={access_token}"))
+        .send().await?.error_for_status()?.json().await?;
+    let mut realms: Vec<(String, String)> = search
+        .results
+        .into_iter()
+        .flat_map(|r| r.data.realms)
+        .map(|r| (r.name.en_us.unwrap_or_else(|| r.slug.clone()), r.slug))
+        .collect();
+    realms.sort();
+    realms.dedup();
+
+    *cache.write().await = Some((Instant::now(), realms.clone()));
+    Ok(realms)
+}
+
+// Suggests realms whose name or slug contains what's been typed so far, as (name, slug) pairs
+pub async fn realm_autocomplete(
+    ctx: &Context,
+    value: &str,
+) -> Result<Vec<(String, String)>, CommandError> {
+    let value = value.to_lowercase();
+    Ok(realm_names(ctx)
+        .await?
+        .into_iter()
+        .filter(|(name, slug)| name.to_lowercase().contains(&value) || slug.contains(&value))
+        .collect())
+}
+
 // Get access token from global state or Blizzard API if token missing/expired
 async fn get_access_token(ctx: &Context) -> Result<String, reqwest::Error> {
     let mut wow_config = {
@@ -23,6 +23,21 @@ pub struct Psql {
 #[derive(Deserialize)]
 pub struct Shippo {
     pub api_key: String,
+    // tracking updates are pushed by Shippo if set, instead of only being polled
+    pub webhook: Option<ShippoWebhook>,
+}
+
+#[derive(Deserialize)]
+pub struct ShippoWebhook {
+    // address the webhook server listens on, like 0.0.0.0:8080
+    pub listen: String,
+    // the webhook URL given to Shippo must have this as its token query parameter, like /shippo?token=...
+    pub token: String,
+}
+
+#[derive(Deserialize)]
+pub struct Sound {
+    pub directory: String,
 }
 
 #[derive(Deserialize)]
@@ -30,6 +45,22 @@ pub struct TarkovMarket {
     pub api_key: String,
 }
 
+// Which service shipments are tracked with
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrackingProvider {
+    #[default]
+    Shippo,
+    // canned tracking that doesn't need an API key, see tracking::Fake
+    Fake,
+}
+
+#[derive(Default, Deserialize)]
+pub struct Tracking {
+    #[serde(default)]
+    pub provider: TrackingProvider,
+}
+
 #[derive(Clone, Deserialize)]
 pub struct TomorrowIO {
     pub api_key: String,
@@ -77,8 +108,8 @@ impl TypeMapKey for Google {
     type Value = Google;
 }
 
-impl TypeMapKey for Shippo {
-    type Value = Shippo;
+impl TypeMapKey for Sound {
+    type Value = Sound;
 }
 
 impl TypeMapKey for TarkovMarket {
@@ -111,9 +142,13 @@ pub struct Main {
     pub discord: Discord,
     pub google: Google,
     pub psql: Psql,
-    pub shippo: Shippo,
+    // only needed when tracking with Shippo
+    pub shippo: Option<Shippo>,
+    pub sound: Sound,
     pub tarkov_market: TarkovMarket,
     pub tomorrow_io: TomorrowIO,
+    #[serde(default)]
+    pub tracking: Tracking,
     pub air_now: AirNow,
     pub twitch: Twitch,
     pub wolfram_alpha: WolframAlpha,
@@ -0,0 +1,323 @@
+use crate::error::CommandError;
+use chrono::{DateTime, Datelike, Days, NaiveTime, Utc};
+use futures::stream::StreamExt;
+use serenity::all::{ChannelId, CreateEmbed, CreateMessage, GuildId, Http};
+use sqlx::{Pool, Postgres};
+use std::fmt::Write as _;
+use std::sync::Arc;
+use tracing::{error, info};
+
+// How many members, games, and commands are listed in each section
+const TOP_COUNT: i64 = 5;
+// Longer game names are cut off so sections stay under Discord's embed field length limit
+const MAX_NAME_LENGTH: usize = 40;
+
+// Posts last week's digest to every configured channel once the week is over, weeks start on Monday in UTC
+pub async fn post_digests_loop(discord_http: Arc<Http>, db: Pool<Postgres>) {
+    info!("starting weekly digest poster");
+    let mut interval = tokio::time::interval(std::time::Duration::from_hours(1));
+
+    loop {
+        interval.tick().await;
+
+        let week_start = week_start(Utc::now());
+        // digests are only marked posted once they're sent, so one that fails is tried again next hour
+        #[allow(clippy::panic)]
+        let configs = match sqlx::query!(
+            "SELECT guild_id, channel_id FROM digest_config WHERE (last_post_date < $1) IS NOT FALSE",
+            week_start
+        )
+        .fetch_all(&db)
+        .await
+        {
+            Ok(c) => c,
+            Err(e) => {
+                error!(error = %e, "error getting digest configs from db");
+                continue;
+            }
+        };
+
+        for config in configs {
+            let (Ok(guild_id), Ok(channel_id)) = (
+                u64::try_from(config.guild_id),
+                u64::try_from(config.channel_id),
+            ) else {
+                error!(
+                    guild_id = config.guild_id,
+                    channel_id = config.channel_id,
+                    "unable to convert digest ids"
+                );
+                continue;
+            };
+            let embed =
+                match build_digest(&discord_http, &db, GuildId::new(guild_id), week_start).await {
+                    Ok(e) => e,
+                    Err(e) => {
+                        error!(error = %e, guild_id, "error building weekly digest");
+                        continue;
+                    }
+                };
+            if let Err(e) = ChannelId::new(channel_id)
+                .send_message(&discord_http, CreateMessage::new().embed(embed))
+                .await
+            {
+                error!(error = %e, guild_id, channel_id, "error posting weekly digest");
+                continue;
+            }
+            #[allow(clippy::panic)]
+            if let Err(e) = sqlx::query!(
+                "UPDATE digest_config SET last_post_date = now() WHERE guild_id = $1",
+                config.guild_id
+            )
+            .execute(&db)
+            .await
+            {
+                error!(error = %e, guild_id, "error marking weekly digest posted");
+            }
+        }
+    }
+}
+
+// Returns the start of the week now is in, Monday at midnight UTC
+pub fn week_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    (now.date_naive() - Days::new(now.weekday().num_days_from_monday().into()))
+        .and_time(NaiveTime::MIN)
+        .and_utc()
+}
+
+// Builds the digest of the week ending at end, with changes from the week before it
+pub async fn build_digest(
+    http: &Http,
+    db: &Pool<Postgres>,
+    guild_id: GuildId,
+    end: DateTime<Utc>,
+) -> Result<CreateEmbed, CommandError> {
+    let start = end - Days::new(7);
+    let previous_start = start - Days::new(7);
+    let guild = guild_id.to_partial_guild(http).await?;
+    // pages through every member, a single members request only returns up to 1000
+    let mut member_ids: Vec<i64> = Vec::new();
+    let mut members = guild_id.members_iter(http).boxed();
+    while let Some(member) = members.next().await {
+        let member = member?;
+        if !member.user.bot {
+            member_ids.push(i64::from(member.user.id));
+        }
+    }
+    let channel_ids: Vec<i64> = guild_id
+        .channels(http)
+        .await?
+        .into_keys()
+        .map(i64::from)
+        .collect();
+    let guild_id = i64::from(guild_id);
+
+    #[allow(clippy::panic)]
+    let messages = sqlx::query!(
+        r#"SELECT count(*) FILTER (WHERE create_date >= $3) AS "current!", count(*) FILTER (WHERE create_date < $3) AS "previous!" FROM message WHERE guild_id = $1 AND create_date >= $2 AND create_date < $4"#,
+        guild_id,
+        previous_start,
+        start,
+        end
+    )
+    .fetch_one(db)
+    .await?;
+    #[allow(clippy::panic)]
+    let chatters = sqlx::query!(
+        r#"SELECT author_id, count(*) AS "count!" FROM message WHERE guild_id = $1 AND author_id = ANY($2) AND create_date >= $3 AND create_date < $4 GROUP BY 1 ORDER BY 2 DESC LIMIT $5"#,
+        guild_id,
+        &member_ids,
+        start,
+        end,
+        TOP_COUNT
+    )
+    .fetch_all(db)
+    .await?;
+    let mut messages_field = with_delta(messages.current, messages.previous);
+    for (rank, chatter) in (1..).zip(chatters) {
+        write!(
+            messages_field,
+            "\n{rank}. <@{}> \u{2014} {}",
+            chatter.author_id, chatter.count
+        )?;
+    }
+
+    // sessions are clipped to each week, sessions that haven't ended count up to now
+    #[allow(clippy::panic)]
+    let playtime = sqlx::query!(
+        r#"
+SELECT
+  COALESCE(SUM(EXTRACT(EPOCH FROM LEAST(COALESCE(end_date, now()), $4) - GREATEST(start_date, $3))) FILTER (WHERE COALESCE(end_date, now()) > $3) / 3600, 0)::float8 AS "current!",
+  COALESCE(SUM(EXTRACT(EPOCH FROM LEAST(COALESCE(end_date, now()), $3) - GREATEST(start_date, $2))) FILTER (WHERE start_date < $3) / 3600, 0)::float8 AS "previous!"
+FROM game_session
+WHERE user_id = ANY($1)
+AND COALESCE(end_date, now()) > $2
+AND start_date < $4"#,
+        &member_ids,
+        previous_start,
+        start,
+        end
+    )
+    .fetch_one(db)
+    .await?;
+    #[allow(clippy::panic)]
+    let games = sqlx::query!(
+        r#"
+SELECT COALESCE(a.game, s.game_name) AS "game!",
+  (SUM(EXTRACT(EPOCH FROM LEAST(COALESCE(s.end_date, now()), $3) - GREATEST(s.start_date, $2))) / 3600)::float8 AS "hours!"
+FROM game_session s
+LEFT JOIN game_alias a ON a.guild_id = $5 AND a.alias = s.game_name
+WHERE s.user_id = ANY($1)
+AND COALESCE(s.end_date, now()) > $2
+AND s.start_date < $3
+GROUP BY 1
+ORDER BY 2 DESC
+LIMIT $4"#,
+        &member_ids,
+        start,
+        end,
+        TOP_COUNT,
+        i64::from(guild_id)
+    )
+    .fetch_all(db)
+    .await?;
+    let mut games_field = format!(
+        "{:.1} hours ({:+.1} from last week)",
+        playtime.current,
+        playtime.current - playtime.previous
+    );
+    for (rank, game) in (1..).zip(games) {
+        write!(
+            games_field,
+            "\n{rank}. {} \u{2014} {:.1} hours",
+            shorten(&game.game),
+            game.hours
+        )?;
+    }
+
+    #[allow(clippy::panic)]
+    let votes = sqlx::query!(
+        r#"SELECT count(*) FILTER (WHERE create_date >= $3) AS "current!", count(*) FILTER (WHERE create_date < $3) AS "previous!" FROM vote WHERE guild_id = $1 AND create_date >= $2 AND create_date < $4"#,
+        guild_id,
+        previous_start,
+        start,
+        end
+    )
+    .fetch_one(db)
+    .await?;
+    #[allow(clippy::panic)]
+    let karma = sqlx::query!(
+        r#"SELECT votee_id, SUM(CASE WHEN is_upvote THEN 1 ELSE -1 END) AS "net!" FROM vote WHERE guild_id = $1 AND create_date >= $2 AND create_date < $3 GROUP BY 1 HAVING SUM(CASE WHEN is_upvote THEN 1 ELSE -1 END) <> 0 ORDER BY 2 DESC"#,
+        guild_id,
+        start,
+        end
+    )
+    .fetch_all(db)
+    .await?;
+    let mut karma_field = format!("{} votes", with_delta(votes.current, votes.previous));
+    let gains: Vec<String> = karma
+        .iter()
+        .filter(|k| k.net > 0)
+        .take(3)
+        .map(|k| format!("<@{}> {:+}", k.votee_id, k.net))
+        .collect();
+    let losses: Vec<String> = karma
+        .iter()
+        .rev()
+        .filter(|k| k.net < 0)
+        .take(3)
+        .map(|k| format!("<@{}> {:+}", k.votee_id, k.net))
+        .collect();
+    if !gains.is_empty() {
+        write!(karma_field, "\nGains: {}", gains.join(", "))?;
+    }
+    if !losses.is_empty() {
+        write!(karma_field, "\nLosses: {}", losses.join(", "))?;
+    }
+
+    #[allow(clippy::panic)]
+    let command_counts = sqlx::query!(
+        r#"SELECT count(*) FILTER (WHERE create_date >= $3) AS "current!", count(*) FILTER (WHERE create_date < $3) AS "previous!" FROM command WHERE guild_id = $1 AND create_date >= $2 AND create_date < $4"#,
+        guild_id,
+        previous_start,
+        start,
+        end
+    )
+    .fetch_one(db)
+    .await?;
+    #[allow(clippy::panic)]
+    let commands = sqlx::query!(
+        r#"SELECT name, count(*) AS "count!" FROM command WHERE guild_id = $1 AND create_date >= $2 AND create_date < $3 GROUP BY 1 ORDER BY 2 DESC, 1 LIMIT $4"#,
+        guild_id,
+        start,
+        end,
+        TOP_COUNT
+    )
+    .fetch_all(db)
+    .await?;
+    let mut commands_field = format!(
+        "{} used",
+        with_delta(command_counts.current, command_counts.previous)
+    );
+    for (rank, command) in (1..).zip(commands) {
+        write!(
+            commands_field,
+            "\n{rank}. `/{}` \u{2014} {}",
+            command.name, command.count
+        )?;
+    }
+
+    // shipments aren't tied to a guild, only to the channel they were tracked in
+    #[allow(clippy::panic)]
+    let shipments = sqlx::query!(
+        r#"
+SELECT
+  count(*) FILTER (WHERE create_date >= $3 AND create_date < $4) AS "tracked!",
+  count(*) FILTER (WHERE create_date >= $2 AND create_date < $3) AS "previous_tracked!",
+  count(*) FILTER (WHERE status = 'delivered' AND update_date >= $3 AND update_date < $4) AS "delivered!",
+  count(*) FILTER (WHERE status = 'delivered' AND update_date >= $2 AND update_date < $3) AS "previous_delivered!"
+FROM shipment
+WHERE channel_id = ANY($1)"#,
+        &channel_ids,
+        previous_start,
+        start,
+        end
+    )
+    .fetch_one(db)
+    .await?;
+
+    Ok(CreateEmbed::new()
+        .title(format!("Weekly digest for {}", guild.name))
+        .description(format!(
+            "{} \u{2013} {}",
+            start.format("%b %e"),
+            (end - Days::new(1)).format("%b %e, %Y")
+        ))
+        .timestamp(serenity::model::timestamp::Timestamp::now())
+        .field("Messages", messages_field, false)
+        .field("Games", games_field, false)
+        .field("Karma", karma_field, false)
+        .field("Commands", commands_field, false)
+        .field(
+            "Shipments",
+            format!(
+                "{} tracked, {} delivered",
+                with_delta(shipments.tracked, shipments.previous_tracked),
+                with_delta(shipments.delivered, shipments.previous_delivered)
+            ),
+            false,
+        ))
+}
+
+fn with_delta(current: i64, previous: i64) -> String {
+    format!("{current} ({:+} from last week)", current - previous)
+}
+
+fn shorten(name: &str) -> String {
+    if name.chars().count() <= MAX_NAME_LENGTH {
+        return String::from(name);
+    }
+    let mut shortened: String = name.chars().take(MAX_NAME_LENGTH - 1).collect();
+    shortened.push('\u{2026}');
+    shortened
+}
@@ -1,17 +1,27 @@
 use crate::commands;
+use crate::commands::gamealias::GameAliasPages;
+use crate::commands::gametime::GametimePages;
+use crate::commands::leaderboard::Leaderboard;
+use crate::commands::playtime::PlaytimePages;
+use crate::commands::statushistory::StatusHistoryPages;
+use crate::commands::voicetime::VoicetimePages;
 use crate::event::report_interaction_error;
+use crate::paginate::{self, Paginate};
 
-use chrono::prelude::*;
-use serenity::all::CommandDataOptionValue;
+use serenity::all::{CommandDataOptionValue, CommandInteraction, ComponentInteraction};
 use serenity::builder::{
-    CreateInteractionResponse, CreateInteractionResponseMessage, EditInteractionResponse,
-    EditMessage,
+    AutocompleteChoice, CreateAutocompleteResponse, CreateInteractionResponse,
+    EditInteractionResponse,
 };
 use serenity::client::Context;
-use serenity::model::{application::Interaction, channel::MessageFlags};
+use serenity::model::application::Interaction;
 use sqlx::{Pool, Postgres};
 use tracing::error;
 
+// Discord allows at most 25 autocomplete choices, each with a name and value of at most 100 characters
+const MAX_CHOICES: usize = 25;
+const MAX_CHOICE_LENGTH: usize = 100;
+
 pub async fn create(ctx: Context, db: Pool<Postgres>, interaction: Interaction) {
     if let Interaction::Command(command) = interaction {
         if let Err(e) = command.defer(&ctx.http).await {
@@ -25,21 +35,63 @@ pub async fn create(ctx: Context, db: Pool<Postgres>, interaction: Interaction)
         }
         crate::event::record_command(&db, &command).await;
         if let Err(e) = match command.data.name.as_str() {
+            "activity" => commands::activity::activity(&ctx, &command).await,
             "affixes" => commands::affixes::affixes(&ctx, &command).await,
-            //"asuh" => commands::asuh::asuh(&ctx, &command).await,
+            "asuh" => commands::sound::random(&ctx, &command).await,
             "birdtime" => commands::time::time(&ctx, &command, "Europe/Oslo").await,
             "botinfo" => commands::botinfo::botinfo(&ctx, &command).await,
+            "digest" => {
+                if let Some(subcommand) = command.data.options.first() {
+                    if let CommandDataOptionValue::SubCommand(suboptions) = &subcommand.value {
+                        match subcommand.name.as_str() {
+                            "channel" => {
+                                commands::digest::channel(&ctx, &command, suboptions).await
+                            }
+                            "disable" => commands::digest::disable(&ctx, &command).await,
+                            "preview" => commands::digest::preview(&ctx, &command).await,
+                            _ => Err("Unrecognized digest subcommand".into()),
+                        }
+                    } else {
+                        Err("Malformed digest subcommand".into())
+                    }
+                } else {
+                    Err("Missing digest subcommand".into())
+                }
+            }
             "downvote" => commands::vote::vote_from_interaction(&ctx, &command, false).await,
             "forecast" => commands::weather::forecast(&ctx, &command).await,
             "fortune" => commands::fortune::fortune(&ctx, &command).await,
+            "gamealias" => {
+                if let Some(subcommand) = command.data.options.first() {
+                    if let CommandDataOptionValue::SubCommand(suboptions) = &subcommand.value {
+                        match subcommand.name.as_str() {
+                            "add" => commands::gamealias::add(&ctx, &command, suboptions).await,
+                            "list" => commands::gamealias::list(&ctx, &command).await,
+                            "remove" => {
+                                commands::gamealias::remove(&ctx, &command, suboptions).await
+                            }
+                            "suggest" => commands::gamealias::suggest(&ctx, &command).await,
+                            _ => Err("Unrecognized gamealias subcommand".into()),
+                        }
+                    } else {
+                        Err("Malformed gamealias subcommand".into())
+                    }
+                } else {
+                    Err("Missing gamealias subcommand".into())
+                }
+            }
+            "gametime" => commands::gametime::gametime(&ctx, &command).await,
+            "heatmap" => commands::heatmap::heatmap(&ctx, &command).await,
             "invite" => commands::invite::invite(&ctx, &command).await,
             "jpg" => commands::jpg::jpg(&ctx, &command).await,
             "karma" => commands::karma::karma(&ctx, &command).await,
             "lastseen" => commands::lastseen::lastseen(&ctx, &command).await,
             "lastplayed" => commands::lastplayed::lastplayed(&ctx, &command).await,
+            "listening" => commands::listening::listening(&ctx, &command).await,
             "math" => commands::wolframalpha::short(&ctx, &command).await,
             "mirotime" => commands::time::time(&ctx, &command, "Europe/Helsinki").await,
             "nieltime" => commands::time::time(&ctx, &command, "Europe/Stockholm").await,
+            "nowplaying" => commands::nowplaying::nowplaying(&ctx, &command).await,
             "ping" => commands::ping::ping(&ctx, &command).await,
             "playtime" => commands::playtime::playtime(&ctx, &command).await,
             "raiderio" => commands::raiderio::raiderio(&ctx, &command).await,
@@ -48,14 +100,86 @@ pub async fn create(ctx: Context, db: Pool<Postgres>, interaction: Interaction)
             "roll" => commands::roll::roll(&ctx, &command).await,
             "sebbitime" => commands::time::time(&ctx, &command, "Europe/Copenhagen").await,
             "serverinfo" => commands::serverinfo::serverinfo(&ctx, &command).await,
+            "shipment" => {
+                if let Some(subcommand) = command.data.options.first() {
+                    if let CommandDataOptionValue::SubCommand(suboptions) = &subcommand.value {
+                        match subcommand.name.as_str() {
+                            "comment" => {
+                                commands::shipping::comment(&ctx, &command, suboptions).await
+                            }
+                            "notify" => {
+                                commands::shipping::notify(&ctx, &command, suboptions).await
+                            }
+                            _ => Err("Unrecognized shipment subcommand".into()),
+                        }
+                    } else {
+                        Err("Malformed shipment subcommand".into())
+                    }
+                } else {
+                    Err("Missing shipment subcommand".into())
+                }
+            }
+            "shipments" => commands::shipping::shipments(&ctx, &command).await,
+            "sound" => {
+                if let Some(subcommand) = command.data.options.first() {
+                    if let CommandDataOptionValue::SubCommand(suboptions) = &subcommand.value {
+                        match subcommand.name.as_str() {
+                            "add" => commands::sound::add(&ctx, &command, suboptions).await,
+                            "list" => commands::sound::list(&ctx, &command).await,
+                            "play" => commands::sound::play(&ctx, &command, suboptions).await,
+                            "random" => commands::sound::random(&ctx, &command).await,
+                            "remove" => commands::sound::remove(&ctx, &command, suboptions).await,
+                            "volume" => commands::sound::volume(&ctx, &command, suboptions).await,
+                            _ => Err("Unrecognized sound subcommand".into()),
+                        }
+                    } else {
+                        Err("Malformed sound subcommand".into())
+                    }
+                } else {
+                    Err("Missing sound subcommand".into())
+                }
+            }
             "source" => commands::source::source(&ctx, &command).await,
+            "statushistory" => commands::statushistory::statushistory(&ctx, &command).await,
+            "subscribe" => commands::subscribe::subscribe(&ctx, &command).await,
+            "subscriptions" => commands::subscribe::subscriptions(&ctx, &command).await,
             "tarkov" => commands::tarkov::tarkov(&ctx, &command).await,
+            "timezone" => commands::timezone::timezone(&ctx, &command).await,
             "top" => commands::top::top(&ctx, &command).await,
             "topcommand" => commands::topcommand::topcommand(&ctx, &command).await,
             "toplength" => commands::toplength::toplength(&ctx, &command).await,
             "track" => commands::shipping::track(&ctx, &command).await,
             "upvote" => commands::vote::vote_from_interaction(&ctx, &command, true).await,
+            "untrack" => commands::shipping::untrack(&ctx, &command).await,
+            "unsubscribe" => commands::subscribe::unsubscribe(&ctx, &command).await,
             "userinfo" => commands::userinfo::userinfo(&ctx, &command).await,
+            "voicetime" => commands::voicetime::voicetime(&ctx, &command).await,
+            "voteconfig" => {
+                if let Some(subcommand) = command.data.options.first() {
+                    if let CommandDataOptionValue::SubCommand(suboptions) = &subcommand.value {
+                        match subcommand.name.as_str() {
+                            "channel" => {
+                                commands::voteconfig::channel(&ctx, &command, suboptions).await
+                            }
+                            "emoji" => {
+                                commands::voteconfig::emoji(&ctx, &command, suboptions).await
+                            }
+                            "newseason" => commands::voteconfig::new_season(&ctx, &command).await,
+                            "reply" => {
+                                commands::voteconfig::reply(&ctx, &command, suboptions).await
+                            }
+                            "reset" => commands::voteconfig::reset(&ctx, &command).await,
+                            "set" => commands::voteconfig::set(&ctx, &command, suboptions).await,
+                            "show" => commands::voteconfig::show(&ctx, &command).await,
+                            _ => Err("Unrecognized voteconfig subcommand".into()),
+                        }
+                    } else {
+                        Err("Malformed voteconfig subcommand".into())
+                    }
+                } else {
+                    Err("Missing voteconfig subcommand".into())
+                }
+            }
             "weather" => commands::weather::weather(&ctx, &command).await,
             "whois" => commands::whois::whois(&ctx, &command).await,
             "zalgo" => commands::zalgo::zalgo(&ctx, &command).await,
@@ -127,119 +251,105 @@ pub async fn create(ctx: Context, db: Pool<Postgres>, interaction: Interaction)
                 .await;
             }
         }
+    } else if let Interaction::Autocomplete(autocomplete) = interaction {
+        autocomplete_option(&ctx, &autocomplete).await;
     } else if let Some(interaction) = interaction.message_component() {
         let fields: Vec<&str> = interaction.data.custom_id.split(':').collect();
-        let command = fields[0];
-        if command != "playtime" {
-            return;
-        }
-        let prev_next = fields[1];
-        let button_id = match fields[2].parse::<i32>() {
-            Ok(id) => id,
-            Err(e) => {
-                error!(error = %e, "error parsing button_id from playtime interaction");
-                return;
-            }
-        };
-        let row = {
-            #[allow(clippy::panic)]
-            match sqlx::query!(r#"SELECT author_id, user_ids, username, start_date, end_date, start_offset FROM playtime_button WHERE id = $1"#, button_id).fetch_one(&db).await {
-                Ok(row) => row,
-                Err(e) => {
-                    error!(error = %e, "error getting playtime interaction buttons");
-                    return;
+        match fields[0] {
+            "page" => page_component(&ctx, &db, &interaction, &fields).await,
+            "shipment" => {
+                if let Err(e) = commands::shipping::refresh(&ctx, &interaction, &fields).await {
+                    error!(%e, "Error refreshing shipment");
+                    report_interaction_error(&ctx, format!("error refreshing shipment: `{e}`"))
+                        .await;
                 }
             }
-        };
-
-        let user_ids: Vec<i64> = row.user_ids;
-        let username: Option<String> = row.username;
-        let start_date: Option<DateTime<Utc>> = row.start_date;
-        let end_date: DateTime<Utc> = row.end_date;
-        let offset: i32 = match prev_next {
-            "first" => 0,
-            "prev" => (row.start_offset - i32::from(commands::playtime::OFFSET_INC)).max(0),
-            "next" => row.start_offset + i32::from(commands::playtime::OFFSET_INC),
-            _ => return,
-        };
+            _ => {}
+        }
+    }
+}
 
-        #[allow(clippy::unwrap_used)] // offset isn't negative
-        let new_content = match commands::playtime::gen_playtime_message(
-            &ctx,
-            &user_ids,
-            username.as_ref(),
-            start_date,
-            end_date,
-            usize::try_from(offset).unwrap(),
-        )
-        .await
-        {
-            Ok(c) => c,
-            Err(e) => {
-                error!(error = %e, "error generating new content for playtime interaction");
-                return;
-            }
-        };
-        if let Some(flags) = interaction.message.flags
-            && flags.contains(MessageFlags::EPHEMERAL)
-        {
-            return;
+// Changes the page of a paged message, dispatching to the command that rendered it
+async fn page_component(
+    ctx: &Context,
+    db: &Pool<Postgres>,
+    interaction: &ComponentInteraction,
+    fields: &[&str],
+) {
+    let Some(state) = paginate::load(ctx, db, interaction, fields).await else {
+        return;
+    };
+    match state.kind.as_str() {
+        GameAliasPages::KIND => paginate::turn::<GameAliasPages>(ctx, db, interaction, state).await,
+        GametimePages::KIND => paginate::turn::<GametimePages>(ctx, db, interaction, state).await,
+        Leaderboard::KIND => paginate::turn::<Leaderboard>(ctx, db, interaction, state).await,
+        PlaytimePages::KIND => paginate::turn::<PlaytimePages>(ctx, db, interaction, state).await,
+        StatusHistoryPages::KIND => {
+            paginate::turn::<StatusHistoryPages>(ctx, db, interaction, state).await;
         }
-        let mut message = interaction.message.clone();
-        if let Err(e) = message
-            .edit(
-                &ctx,
-                EditMessage::new().content(&new_content).components(
-                    commands::playtime::create_components(offset, &new_content, button_id, true),
-                ),
-            )
-            .await
-        {
-            error!(error = %e, "error updating playtime messge components");
-            return;
+        VoicetimePages::KIND => {
+            paginate::turn::<VoicetimePages>(ctx, db, interaction, state).await;
         }
+        _ => error!(kind = state.kind, id = state.id, "Unknown paginator kind"),
+    }
+}
 
-        if let Err(e) = interaction
-            .create_response(
-                &ctx,
-                CreateInteractionResponse::UpdateMessage(CreateInteractionResponseMessage::new()),
-            )
-            .await
-        {
-            error!(error = %e, "error creating playtime interaction response");
-            return;
+// Responds to an autocomplete interaction with choices from the provider for the focused option
+async fn autocomplete_option(ctx: &Context, interaction: &CommandInteraction) {
+    let Some(focused) = interaction.data.autocomplete() else {
+        return;
+    };
+    let value = focused.value.trim();
+    let choices = match (interaction.data.name.as_str(), focused.name) {
+        ("gamealias", "alias") => {
+            commands::gamealias::alias_autocomplete(ctx, interaction, value).await
         }
-
-        {
-            #[allow(clippy::panic)]
-            if let Err(e) = sqlx::query!(
-                r#"UPDATE playtime_button SET start_offset = $2 WHERE id = $1"#,
-                button_id,
-                offset
-            )
-            .execute(&db)
-            .await
-            {
-                error!(error = %e, "error updating playtime_button table after interaction");
-            }
+        ("gamealias" | "gametime" | "subscribe", "game") => {
+            commands::gametime::autocomplete(ctx, interaction, value).await
         }
-
-        // leave buttons disabled for 2 seconds, then send the message again with buttons enabled
-        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
-
-        if let Err(e) = message
-            .edit(
-                &ctx,
-                EditMessage::new().components(commands::playtime::create_components(
-                    offset,
-                    &new_content,
-                    button_id,
-                    false,
-                )),
-            )
-            .await
-        {
-            error!(error = %e, "error updating playtime messge components");
+        ("raiderio" | "wow", "realm") => commands::wow::realm_autocomplete(ctx, value).await,
+        ("sound", "name") => commands::sound::autocomplete(ctx, interaction, value).await,
+        ("topcommand", "command") => commands::topcommand::autocomplete(ctx, value).await,
+        ("shipment" | "track" | "untrack", "number") => {
+            commands::shipping::autocomplete(ctx, interaction, value).await
         }
+        ("unsubscribe", "game") => commands::subscribe::autocomplete(ctx, interaction, value).await,
+        _ => return,
+    };
+    let choices = match choices {
+        Ok(c) => c,
+        Err(e) => {
+            error!(
+                command = interaction.data.name,
+                option = focused.name,
+                error = %e,
+                "Error running autocomplete"
+            );
+            // still respond so the client isn't left waiting on choices
+            vec![]
+        }
+    };
+
+    if let Err(e) = interaction
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Autocomplete(
+                CreateAutocompleteResponse::new().set_choices(
+                    choices
+                        .into_iter()
+                        // Discord rejects choices with a name or value over 100 characters
+                        .filter(|(name, value)| {
+                            name.chars().count() <= MAX_CHOICE_LENGTH
+                                && value.chars().count() <= MAX_CHOICE_LENGTH
+                        })
+                        .take(MAX_CHOICES)
+                        .map(|(name, value)| AutocompleteChoice::new(name, value))
+                        .collect(),
+                ),
+            ),
+        )
+        .await
+    {
+        error!(command = interaction.data.name, error = %e, "Error responding to autocomplete");
     }
 }
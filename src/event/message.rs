@@ -3,17 +3,21 @@ use crate::event::report_interaction_error;
 use crate::{commands, twitch};
 use num_format::{Locale, ToFormattedString};
 use serenity::all::UserId;
-use serenity::builder::CreateMessage;
+use serenity::builder::{CreateAllowedMentions, CreateMessage};
 use serenity::client::Context;
 use serenity::model::{
     channel::Message,
     event::MessageUpdateEvent,
-    id::{ChannelId, MessageId},
+    id::{ChannelId, GuildId, MessageId},
 };
 use sqlx::types::Decimal;
 use sqlx::{Pool, Postgres};
+use std::collections::HashSet;
 use tracing::error;
 
+// Most members that can be voted on in one message
+const MAX_VOTES_PER_MESSAGE: usize = 5;
+
 pub async fn create(handler: &Handler, ctx: &Context, msg: &Message) {
     {
         #[allow(clippy::panic)]
@@ -33,29 +37,9 @@ VALUES ($1, $2, $3, $4, $5)"#,
             error!(%e, "error inserting message into db");
         }
     }
-    if let Some(caps) = handler.vote_regex.captures(&msg.content)
-        && let Ok(user_id) = caps[1].parse::<u64>().map(UserId::new)
-    {
-        let is_upvote = &caps[2] == "++";
+    if handler.vote_regex.is_match(&msg.content) {
         if let Some(guild_id) = msg.guild_id {
-            match commands::vote::process_vote(ctx, is_upvote, msg.author.id, guild_id, user_id)
-                .await
-            {
-                Ok(Some(reply)) => {
-                    if let Err(e) = msg.reply(&ctx, reply).await {
-                        error!(%e, "unable to reply to message");
-                    }
-                }
-                Err(e) => {
-                    error!(%e, "unable to process vote message");
-                    report_interaction_error(
-                        ctx,
-                        format!("error running vote from message: `{e}`"),
-                    )
-                    .await;
-                }
-                _ => {}
-            }
+            process_votes(handler, ctx, msg, guild_id).await;
         } else if let Err(e) = msg.reply(&ctx, "Votes can only be done in a server").await {
             error!(%e, "unable to reply to message");
         }
@@ -97,6 +81,107 @@ VALUES ($1, $2, $3, $4, $5)"#,
     }
 }
 
+// Processes every vote in a message and replies once with which were counted and why the rest weren't
+// A message with a single vote only gets a reply if the vote wasn't counted
+async fn process_votes(handler: &Handler, ctx: &Context, msg: &Message, guild_id: GuildId) {
+    // each vote's mention, change in karma, and why it wasn't counted
+    let mut results: Vec<(String, i32, Option<String>)> = vec![];
+    let mut votes = vec![];
+    // index in results of each vote
+    let mut vote_results = vec![];
+    let mut votee_ids = HashSet::new();
+    for caps in handler.vote_regex.captures_iter(&msg.content) {
+        let Ok(id) = caps[2].parse::<u64>() else {
+            continue;
+        };
+        let is_role = &caps[1] == "&";
+        let is_upvote = &caps[3] == "++" || caps.get(4).is_some_and(|s| s.as_str() == "+");
+        let count = caps.get(5).map_or(1, |n| n.as_str().parse().unwrap_or(0));
+        let refusal = if is_role {
+            Some(String::from("Roles can't be voted on."))
+        } else if count == 0 {
+            Some(String::from("Votes have to be for at least 1."))
+        } else if !votee_ids.insert(id) {
+            Some(String::from("They were already voted on in this message."))
+        } else if votes.len() >= MAX_VOTES_PER_MESSAGE {
+            Some(format!(
+                "Only {MAX_VOTES_PER_MESSAGE} members can be voted on in one message."
+            ))
+        } else {
+            None
+        };
+        if refusal.is_none() {
+            vote_results.push(results.len());
+            votes.push(commands::vote::Vote {
+                is_upvote,
+                votee_id: UserId::new(id),
+                count,
+                reason: caps.get(6).map(|r| r.as_str().trim()),
+            });
+        }
+        results.push((
+            format!("<@{}{id}>", if is_role { "&" } else { "" }),
+            if is_upvote { count } else { -count },
+            refusal,
+        ));
+    }
+
+    if !votes.is_empty() {
+        match commands::vote::process_votes(
+            ctx,
+            msg.author.id,
+            guild_id,
+            msg.channel_id,
+            None,
+            &votes,
+        )
+        .await
+        {
+            Ok(replies) => {
+                for (i, reply) in vote_results.into_iter().zip(replies) {
+                    results[i].2 = reply;
+                }
+            }
+            Err(e) => {
+                error!(%e, "unable to process vote message");
+                report_interaction_error(ctx, format!("error running vote from message: `{e}`"))
+                    .await;
+                return;
+            }
+        }
+    }
+
+    let content = if let [(_, _, refusal)] = results.as_slice() {
+        let Some(refusal) = refusal else {
+            return;
+        };
+        refusal.clone()
+    } else {
+        results
+            .iter()
+            .map(|(mention, change, refusal)| match refusal {
+                None => format!("{mention} {change:+}"),
+                Some(refusal) => format!("{mention} not counted: {refusal}"),
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    };
+    // mentions in the reply are only there to show who was voted on, they shouldn't ping anyone again
+    if let Err(e) = msg
+        .channel_id
+        .send_message(
+            &ctx.http,
+            CreateMessage::new()
+                .content(content)
+                .reference_message(msg)
+                .allowed_mentions(CreateAllowedMentions::new()),
+        )
+        .await
+    {
+        error!(%e, "unable to reply to message");
+    }
+}
+
 pub async fn delete(db: &Pool<Postgres>, channel_id: ChannelId, message_id: MessageId) {
     #[allow(clippy::panic)]
     if let Err(e) = sqlx::query!(
@@ -1,25 +1,31 @@
 mod interaction;
 mod message;
 mod presence;
+mod reaction;
+mod subscription;
+mod voice;
+
+pub use presence::{backfill_sessions, end_stale_sessions, session_cutoffs};
 
 use crate::model;
 
 use serde_json::json;
 use serenity::all::{
-    Command, CommandDataOptionValue, CommandInteraction, CommandOptionType, EditMessage,
-    Interaction,
+    ChannelType, Command, CommandDataOptionValue, CommandInteraction, CommandOptionType,
+    EditMessage, Interaction,
 };
 use serenity::async_trait;
 use serenity::builder::{CreateCommand, CreateCommandOption};
 use serenity::client::{Context, EventHandler};
 use serenity::json::Value;
 use serenity::model::{
-    channel::Message,
+    channel::{Message, Reaction},
     event::MessageUpdateEvent,
     gateway::{Presence, Ready},
     guild::{Guild, Member, UnavailableGuild},
     id::{ChannelId, GuildId, MessageId, UserId},
     user::User,
+    voice::VoiceState,
 };
 use sqlx::{Pool, Postgres};
 use std::collections::HashMap;
@@ -44,7 +50,10 @@ impl Handler {
             twitch_clip_regex: regex::RegexBuilder::new(r"https?://(www\.)?twitch.tv/(\w+)/clip")
                 .case_insensitive(true)
                 .build()?,
-            vote_regex: regex::RegexBuilder::new(r"<@!?(\d+?)>\s*(\+\+|--)").build()?,
+            vote_regex: regex::RegexBuilder::new(
+                r"<@([!&]?)(\d+)>\s*(\+\+|--|([+-])=\s*(\d{1,4}))(?:\s+for\s+([^<\n]{1,200}))?",
+            )
+            .build()?,
             suppress_embed_channel_id: ChannelId::new(suppress_embed_channel_id),
         })
     }
@@ -81,12 +90,33 @@ impl EventHandler for Handler {
         }
 
         match Command::set_global_commands(&ctx.http, vec![
+            CreateCommand::new("activity")
+                .description("Shows how much and when a user is online")
+                .set_options(vec![
+                    CreateCommandOption::new(CommandOptionType::User, "user", "User to show activity for")
+                        .required(true),
+                    CreateCommandOption::new(CommandOptionType::String, "duration", "Duration to show activity for (2 weeks, 3 months, etc), defaults to 30 days")
+                ]),
             CreateCommand::new("affixes").description("Sends this week's US Mythic+ affixes"),
-            //CreateCommand::new("asuh").description("Joins your voice channel and plays bothersome audio"),
+            CreateCommand::new("asuh").description("Joins your voice channel and plays a random soundboard sound"),
             CreateCommand::new("botinfo").description("Displays details about the bot"),
+            CreateCommand::new("digest")
+                .description("Weekly summary of this server's activity")
+                .set_options(vec![
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "channel", "Post the weekly digest in a channel every Monday (admin only)")
+                        .add_sub_option(CreateCommandOption::new(CommandOptionType::Channel, "channel", "Channel to post the digest in")
+                                        .required(true)
+                                        .channel_types(vec![ChannelType::Text, ChannelType::News])),
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "disable", "Stop posting the weekly digest (admin only)"),
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "preview", "Show the digest of last week"),
+                ]),
             CreateCommand::new("downvote").description("Downvote a user (lowering their karma by one)")
-                .add_option(CreateCommandOption::new(CommandOptionType::User, "user", "User to downvote")
-                            .required(true)),
+                .set_options(vec![
+                    CreateCommandOption::new(CommandOptionType::User, "user", "User to downvote")
+                        .required(true),
+                    CreateCommandOption::new(CommandOptionType::String, "reason", "Why they're being downvoted")
+                        .max_length(200),
+                ]),
             CreateCommand::new("forecast")
                 .description("Sends hourly weather conditions over the next 12 hours for an area")
                 .set_options(vec![
@@ -96,6 +126,37 @@ impl EventHandler for Handler {
                         .max_int_value(12)
                 ]),
             CreateCommand::new("fortune").description("Sends a random adage"),
+            CreateCommand::new("gamealias")
+                .description("Merge names of the same game so their playtime is counted together")
+                .set_options(vec![
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "add", "Count playtime of one game name as another (admin only)")
+                        .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "alias", "Game name to merge")
+                                        .required(true)
+                                        .set_autocomplete(true))
+                        .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "game", "Game name to merge it into")
+                                        .required(true)
+                                        .set_autocomplete(true)),
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "list", "List merged game names"),
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "remove", "Stop merging a game name (admin only)")
+                        .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "alias", "Merged game name")
+                                        .required(true)
+                                        .set_autocomplete(true)),
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "suggest", "List game names that are likely the same game"),
+                ]),
+            CreateCommand::new("gametime")
+                .description("Ranks members of this server by time spent playing a game")
+                .set_options(vec![
+                    CreateCommandOption::new(CommandOptionType::String, "game", "Game to show playtime for")
+                        .required(true)
+                        .set_autocomplete(true),
+                    CreateCommandOption::new(CommandOptionType::String, "duration", "Duration to show playtime for (1 week, 2 months, etc)")
+                ]),
+            CreateCommand::new("heatmap")
+                .description("Shows when a user or everyone in this server is active")
+                .set_options(vec![
+                    CreateCommandOption::new(CommandOptionType::User, "user", "User to show activity for"),
+                    CreateCommandOption::new(CommandOptionType::String, "duration", "Duration to show activity for (2 weeks, 3 months, etc; defaults to 4 weeks)")
+                ]),
             CreateCommand::new("jpg")
                 .description("Efficiently compresses the most recently posted image")
                 .add_option(
@@ -103,11 +164,16 @@ impl EventHandler for Handler {
             CreateCommand::new("serverinfo").description("Displays details about this server"),
             CreateCommand::new("invite").description("Generates link to add bot to a server you administrate"),
             CreateCommand::new("karma").description("Lists members by karma points")
-                .add_option(
-                    CreateCommandOption::new(CommandOptionType::Integer, "count", "The number of members to list (defaults to 5)")
+                .set_options(vec![
+                    CreateCommandOption::new(CommandOptionType::User, "user", "Show a member's karma history instead of the leaderboard"),
+                    CreateCommandOption::new(CommandOptionType::String, "window", "Only count votes within this time (last 30 days, past week, etc)"),
+                    CreateCommandOption::new(CommandOptionType::Integer, "season", "Show the final standings of an ended season (defaults to the current season)")
+                        .min_int_value(1),
+                    CreateCommandOption::new(CommandOptionType::Boolean, "include_bots", "Include bots (defaults to false)"),
+                    CreateCommandOption::new(CommandOptionType::Integer, "count", "The number of members to list per page (defaults to 10)")
                         .min_int_value(1)
-                        .max_int_value(100)
-                ),
+                        .max_int_value(25),
+                ]),
             CreateCommand::new("lastseen")
                 .description("Sends how long it's been since a user was last online")
                 .add_option(CreateCommandOption::new(CommandOptionType::User, "user", "User to check")
@@ -116,14 +182,25 @@ impl EventHandler for Handler {
                 .description("How long it's been since a user was last playing a game, and the game they were playing")
                 .add_option(CreateCommandOption::new(CommandOptionType::User, "user", "User to check")
                             .required(true)),
+            CreateCommand::new("listening")
+                .description("Shows the most listened to Spotify artists and tracks of a user or everyone in this server")
+                .set_options(vec![
+                    CreateCommandOption::new(CommandOptionType::User, "user", "User to show listening for"),
+                    CreateCommandOption::new(CommandOptionType::String, "duration", "Duration to show listening for (1 week, 2 months, etc)")
+                ]),
             CreateCommand::new("math")
                 .description("Does math (with Wolfram Alpha)")
                 .add_option(CreateCommandOption::new(CommandOptionType::String, "question", "A question; answerable by text")
                             .required(true)),
+            CreateCommand::new("nowplaying")
+                .description("Shows everyone in this server playing a game right now"),
             CreateCommand::new("ping").description("pong"),
             CreateCommand::new("playtime")
                 .description("Shows all recorded video game playtime of a user or everyone in this server")
-                .add_option(CreateCommandOption::new(CommandOptionType::User, "user", "User to show playtime for")),
+                .set_options(vec![
+                    CreateCommandOption::new(CommandOptionType::User, "user", "User to show playtime for"),
+                    CreateCommandOption::new(CommandOptionType::Boolean, "chart", "Show charts of top games and playtime per week instead of a table")
+                ]),
             CreateCommand::new("raiderio")
                 .description("Displays raider.io stats for given character")
                 .set_options(vec![
@@ -131,20 +208,88 @@ impl EventHandler for Handler {
                         .required(true),
                     CreateCommandOption::new(CommandOptionType::String, "realm", "Realm that character is on")
                         .required(true)
+                        .set_autocomplete(true)
                 ]),
             CreateCommand::new("recentplaytime")
                 .description("Shows video game playtime over a specified duration of a user or everyone in this server")
                 .set_options(vec![
                     CreateCommandOption::new(CommandOptionType::String, "duration", "Duration to show playtime for (1 week, 2 months, etc)")
                         .required(true),
-                    CreateCommandOption::new(CommandOptionType::User, "user", "User to show playtime for")
+                    CreateCommandOption::new(CommandOptionType::User, "user", "User to show playtime for"),
+                    CreateCommandOption::new(CommandOptionType::Boolean, "chart", "Show charts of top games and playtime per week instead of a table")
                 ]),
             CreateCommand::new("roll")
                 .description("Roll a die")
                 .add_option(CreateCommandOption::new(CommandOptionType::Integer, "sides", "Sides on die (default 100)")
                             .min_int_value(1)
                             .max_int_value(u32::MAX.into())),
+            CreateCommand::new("shipment")
+                .description("Manage a shipment you're tracking")
+                .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "comment", "Change the comment of a shipment")
+                            .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "number", "Tracking number")
+                                            .required(true)
+                                            .set_autocomplete(true))
+                            .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "comment", "New comment (removes the comment if not given)")
+                                            .max_length(50)))
+                .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "notify", "Choose which changes to your shipments you're notified about, deliveries are always notified")
+                            .add_sub_option(CreateCommandOption::new(CommandOptionType::Boolean, "transit", "When a shipment starts moving (default: on)"))
+                            .add_sub_option(CreateCommandOption::new(CommandOptionType::Boolean, "out_for_delivery", "When a shipment is out for delivery (default: on)"))
+                            .add_sub_option(CreateCommandOption::new(CommandOptionType::Boolean, "eta", "When a shipment's expected delivery date changes (default: off)"))
+                            .add_sub_option(CreateCommandOption::new(CommandOptionType::Boolean, "returned", "When a shipment is returned to the sender (default: on)"))
+                            .add_sub_option(CreateCommandOption::new(CommandOptionType::Boolean, "failure", "When a shipment can't be delivered (default: on)"))),
+            CreateCommand::new("shipments").description("List the shipments you're tracking that haven't been delivered"),
+            CreateCommand::new("sound")
+                .description("Server soundboard")
+                .set_options(vec![
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "add", "Add a sound (admin only)")
+                        .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "name", "Sound name (lowercase letters, numbers, - and _)")
+                                        .required(true)
+                                        .max_length(32))
+                        .add_sub_option(CreateCommandOption::new(CommandOptionType::Attachment, "file", "Audio clip, at most 30 seconds")
+                                        .required(true)),
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "list", "List this server's sounds"),
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "play", "Joins your voice channel and plays a sound")
+                        .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "name", "Sound name")
+                                        .required(true)
+                                        .set_autocomplete(true)),
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "random", "Joins your voice channel and plays a random sound"),
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "remove", "Remove a sound (admin only)")
+                        .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "name", "Sound name")
+                                        .required(true)
+                                        .set_autocomplete(true)),
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "volume", "Set soundboard volume (admin only)")
+                        .add_sub_option(CreateCommandOption::new(CommandOptionType::Integer, "percent", "Volume percent")
+                                        .required(true)
+                                        .min_int_value(1)
+                                        .max_int_value(200)),
+                ]),
             CreateCommand::new("source").description("Sends link to bot source code"),
+            CreateCommand::new("statushistory")
+                .description("Shows the custom statuses a user has set")
+                .add_option(CreateCommandOption::new(CommandOptionType::User, "user", "User to show statuses for (defaults to you)")),
+            CreateCommand::new("subscribe")
+                .description("Get notified when members of this server start playing a game")
+                .set_options(vec![
+                    CreateCommandOption::new(CommandOptionType::String, "game", "Game to be notified about")
+                        .required(true)
+                        .set_autocomplete(true),
+                    CreateCommandOption::new(CommandOptionType::Integer, "players", "Only notify once this many other members are playing (defaults to 1)")
+                        .min_int_value(1)
+                        .max_int_value(25),
+                    CreateCommandOption::new(CommandOptionType::Channel, "channel", "Ping you in this channel instead of sending a DM")
+                        .channel_types(vec![ChannelType::Text, ChannelType::Voice, ChannelType::News]),
+                    CreateCommandOption::new(CommandOptionType::Integer, "quiet_start", "Hour of the day in your timezone to stop notifying at (0-23)")
+                        .min_int_value(0)
+                        .max_int_value(23),
+                    CreateCommandOption::new(CommandOptionType::Integer, "quiet_end", "Hour of the day in your timezone to start notifying again at (0-23)")
+                        .min_int_value(0)
+                        .max_int_value(23),
+                    CreateCommandOption::new(CommandOptionType::Integer, "cooldown", "Minutes to wait before notifying about this game again (defaults to 60)")
+                        .min_int_value(0)
+                        .max_int_value(10080),
+                ]),
+            CreateCommand::new("subscriptions")
+                .description("Lists the games you're subscribed to in this server"),
             /*CreateCommand::new("tarkov")
             .description("Sends flea market and vendor info for item")
             .create_option(|o| {
@@ -154,38 +299,144 @@ impl EventHandler for Handler {
             .required(true)
         }),
         })*/
+            CreateCommand::new("timezone")
+                .description("Sets the timezone used when showing you times, or shows your current timezone")
+                .add_option(CreateCommandOption::new(CommandOptionType::String, "zone", "Timezone name (America/New_York, Europe/Oslo, etc)")),
             CreateCommand::new("top")
                 .description("Lists members by number of sent messages")
-                .add_option(CreateCommandOption::new(CommandOptionType::Integer, "count", "The number of members to list (defaults to 5)")
-                            .min_int_value(1)
-                            .max_int_value(100)),
+                .set_options(vec![
+                    CreateCommandOption::new(CommandOptionType::String, "scope", "Rank messages in this channel or the whole server (defaults to channel)")
+                        .add_string_choice("Channel", "channel")
+                        .add_string_choice("Server", "server"),
+                    CreateCommandOption::new(CommandOptionType::Channel, "channel", "Rank messages in a specific channel")
+                        .channel_types(vec![ChannelType::Text, ChannelType::Voice, ChannelType::News]),
+                    CreateCommandOption::new(CommandOptionType::String, "window", "Only count messages within this time (last 30 days, past week, etc)"),
+                    CreateCommandOption::new(CommandOptionType::Boolean, "include_bots", "Include bots (defaults to false)"),
+                    CreateCommandOption::new(CommandOptionType::Boolean, "include_commands", "Include messages that are commands (defaults to false)"),
+                    CreateCommandOption::new(CommandOptionType::Integer, "count", "The number of members to list per page (defaults to 10)")
+                        .min_int_value(1)
+                        .max_int_value(25),
+                ]),
             CreateCommand::new("topcommand")
                 .description("Lists members by most command invocations")
                 .add_option(CreateCommandOption::new(CommandOptionType::String, "command", "Command to list invocations for")
-                            .required(true)),
+                            .required(true)
+                            .set_autocomplete(true)),
             CreateCommand::new("toplength")
                 .description("Lists members by average length of sent messages")
-                .add_option(CreateCommandOption::new(CommandOptionType::Integer, "count", "The number of members to list (defaults to 5)")
-                            .min_int_value(1)
-                            .max_int_value(100)),
+                .set_options(vec![
+                    CreateCommandOption::new(CommandOptionType::String, "scope", "Rank messages in this channel or the whole server (defaults to channel)")
+                        .add_string_choice("Channel", "channel")
+                        .add_string_choice("Server", "server"),
+                    CreateCommandOption::new(CommandOptionType::Channel, "channel", "Rank messages in a specific channel")
+                        .channel_types(vec![ChannelType::Text, ChannelType::Voice, ChannelType::News]),
+                    CreateCommandOption::new(CommandOptionType::String, "window", "Only count messages within this time (last 30 days, past week, etc)"),
+                    CreateCommandOption::new(CommandOptionType::Boolean, "include_bots", "Include bots (defaults to false)"),
+                    CreateCommandOption::new(CommandOptionType::Boolean, "include_commands", "Include messages that are commands (defaults to false)"),
+                    CreateCommandOption::new(CommandOptionType::Integer, "count", "The number of members to list per page (defaults to 10)")
+                        .min_int_value(1)
+                        .max_int_value(25),
+                ]),
             CreateCommand::new("track")
                 .description("Track shipment")
                 .set_options(vec![
-                    CreateCommandOption::new(CommandOptionType::String, "carrier", "Shipping company")
+                    CreateCommandOption::new(CommandOptionType::String, "number", "Tracking number")
                         .required(true)
+                        .set_autocomplete(true),
+                    CreateCommandOption::new(CommandOptionType::String, "carrier", "Shipping company, detected from the tracking number if not given")
+                        .add_string_choice("Australia Post", "australia_post")
+                        .add_string_choice("Canada Post", "canada_post")
+                        .add_string_choice("DHL eCommerce", "dhl_ecommerce")
+                        .add_string_choice("DHL Express", "dhl_express")
                         .add_string_choice("FedEx", "fedex")
+                        .add_string_choice("LaserShip", "lasership")
+                        .add_string_choice("OnTrac", "ontrac")
+                        .add_string_choice("Purolator", "purolator")
+                        .add_string_choice("Royal Mail", "royal_mail")
                         .add_string_choice("UPS", "ups")
                         .add_string_choice("USPS", "usps"),
-                    CreateCommandOption::new(CommandOptionType::String, "number", "Tracking number")
-                        .required(true),
                     CreateCommandOption::new(CommandOptionType::String, "comment", "Optional comment descriping shipment, will be sent to channel upon package delivery")
                 ]),
+            CreateCommand::new("untrack")
+                .description("Stop tracking a shipment")
+                .add_option(CreateCommandOption::new(CommandOptionType::String, "number", "Tracking number")
+                            .required(true)
+                            .set_autocomplete(true)),
+            CreateCommand::new("unsubscribe")
+                .description("Stop being notified when members of this server start playing a game")
+                .add_option(CreateCommandOption::new(CommandOptionType::String, "game", "Game to stop being notified about")
+                            .required(true)
+                            .set_autocomplete(true)),
             CreateCommand::new("userinfo").description("Displays details about a user")
                 .add_option(CreateCommandOption::new(CommandOptionType::User, "user", "User to display")
                             .required(true)),
             CreateCommand::new("upvote").description("Upvote a user (increasing their karma by one)")
-                .add_option(CreateCommandOption::new(CommandOptionType::User, "user", "User to upvote")
-                            .required(true)),
+                .set_options(vec![
+                    CreateCommandOption::new(CommandOptionType::User, "user", "User to upvote")
+                        .required(true),
+                    CreateCommandOption::new(CommandOptionType::String, "reason", "Why they're being upvoted")
+                        .max_length(200),
+                ]),
+            CreateCommand::new("voicetime")
+                .description("Shows time spent in voice channels by a user or everyone in this server")
+                .set_options(vec![
+                    CreateCommandOption::new(CommandOptionType::User, "user", "User to show voice time for"),
+                    CreateCommandOption::new(CommandOptionType::String, "duration", "Duration to show voice time for (1 week, 2 months, etc)")
+                ]),
+            CreateCommand::new("voteconfig")
+                .description("Rules for voting on members in this server")
+                .set_options(vec![
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "channel", "Allow or disallow votes in a channel (admin only)")
+                        .add_sub_option(CreateCommandOption::new(CommandOptionType::Channel, "channel", "Channel to allow or disallow votes in")
+                                        .required(true)
+                                        .channel_types(vec![ChannelType::Text, ChannelType::News])),
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "emoji", "Vote on a message's author by reacting to it with an emoji (admin only)")
+                        .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "kind", "Whether the emoji upvotes or downvotes")
+                                        .required(true)
+                                        .add_string_choice("Upvote", "upvote")
+                                        .add_string_choice("Downvote", "downvote"))
+                        .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "emoji", "Emoji to vote with (turns voting by reaction off if not given)")
+                                        .max_length(100)),
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "newseason", "Archive the current karma standings and start a new season from zero (admin only)"),
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "reply", "Change the reply to a rejected vote (admin only)")
+                        .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "kind", "Which rejected votes to change the reply to")
+                                        .required(true)
+                                        .add_string_choice("Voting on yourself", "self")
+                                        .add_string_choice("Voting again too soon", "cooldown")
+                                        .add_string_choice("Voting back too soon", "reciprocal")
+                                        .add_string_choice("Daily limit reached", "limit")
+                                        .add_string_choice("Account or membership too new", "age")
+                                        .add_string_choice("Channel not allowed", "channel"))
+                        .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "text", "New reply (default reply if not given)")
+                                        .max_length(200)),
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "reset", "Put every vote rule and reply back to the default (admin only)"),
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "set", "Change vote rules, rules not given are left as they are (admin only)")
+                        .add_sub_option(CreateCommandOption::new(CommandOptionType::Integer, "cooldown_min", "Shortest wait after voting before voting again, in seconds")
+                                        .min_int_value(0)
+                                        .max_int_value(86400))
+                        .add_sub_option(CreateCommandOption::new(CommandOptionType::Integer, "cooldown_max", "Longest wait after voting before voting again, in seconds")
+                                        .min_int_value(0)
+                                        .max_int_value(86400))
+                        .add_sub_option(CreateCommandOption::new(CommandOptionType::Integer, "reciprocal_hours", "Hours after a vote that the two members can't vote on each other")
+                                        .min_int_value(0)
+                                        .max_int_value(720))
+                        .add_sub_option(CreateCommandOption::new(CommandOptionType::Integer, "daily_limit", "Most votes a member can make on the same member each day (0 for no limit)")
+                                        .min_int_value(0)
+                                        .max_int_value(1000))
+                        .add_sub_option(CreateCommandOption::new(CommandOptionType::Integer, "account_days", "How many days old a voter's Discord account has to be")
+                                        .min_int_value(0)
+                                        .max_int_value(3650))
+                        .add_sub_option(CreateCommandOption::new(CommandOptionType::Integer, "member_days", "How many days a voter has to have been in this server")
+                                        .min_int_value(0)
+                                        .max_int_value(3650))
+                        .add_sub_option(CreateCommandOption::new(CommandOptionType::Integer, "bulk_limit", "Most votes that can be made on a member at once with @member += N (1 to turn off)")
+                                        .min_int_value(1)
+                                        .max_int_value(100))
+                        .add_sub_option(CreateCommandOption::new(CommandOptionType::Integer, "season_days", "Start a new karma season this many days after each one starts (0 to only start them manually)")
+                                        .min_int_value(0)
+                                        .max_int_value(3650)),
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "show", "Show this server's vote rules"),
+                ]),
             CreateCommand::new("weather")
                 .description("Sends weather conditions for an area")
                 .add_option(CreateCommandOption::new(CommandOptionType::String, "location", "Area to get weather for; can be city name, postal code, or decimal lat/long (default: Austin, TX)")),
@@ -204,15 +455,17 @@ impl EventHandler for Handler {
                         .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "character", "Character name")
                                         .required(true))
                         .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "realm", "Character's realm")
-                                        .required(true)),
-                    CreateCommandOption::new(CommandOptionType::SubCommand, "realm", "Status of WoW realm").add_sub_option(CreateCommandOption::new(CommandOptionType::String, "realm", "Realm name")),
+                                        .required(true)
+                                        .set_autocomplete(true)),
+                    CreateCommandOption::new(CommandOptionType::SubCommand, "realm", "Status of WoW realm").add_sub_option(CreateCommandOption::new(CommandOptionType::String, "realm", "Realm name").set_autocomplete(true)),
                     CreateCommandOption::new(CommandOptionType::SubCommand, "search", "Search all realms for WoW character by name")
                         .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "character", "Character name").required(true)),
                     CreateCommandOption::new(CommandOptionType::SubCommand, "transmog", "Image of character from WoW armory")
                         .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "character", "Character name")
                                         .required(true))
                         .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "realm", "Character's realm")
-                                        .required(true))
+                                        .required(true)
+                                        .set_autocomplete(true))
                 ]),
             CreateCommand::new("zalgo")
                 .description("HE COMES")
@@ -229,10 +482,14 @@ impl EventHandler for Handler {
         presence::update(&ctx, &self.db, update.guild_id, update, false).await;
     }
 
+    async fn voice_state_update(&self, _ctx: Context, old: Option<VoiceState>, new: VoiceState) {
+        voice::update(&self.db, old.as_ref(), &new).await;
+    }
+
     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
         let db = self.db.clone();
         // TODO? tokio::spawn
-        interaction::create(ctx, db, interaction).await;
+        Box::pin(interaction::create(ctx, db, interaction)).await;
     }
 
     async fn guild_create(&self, ctx: Context, guild: Guild, is_new: Option<bool>) {
@@ -248,6 +505,7 @@ impl EventHandler for Handler {
             Some(true) => false,
             Some(false) | None => true,
         };
+        voice::sync_guild(&self.db, &guild).await;
         for (_, presence) in guild.presences {
             presence::update(&ctx, &self.db, Some(guild.id), presence, is_startup).await;
         }
@@ -314,6 +572,7 @@ impl EventHandler for Handler {
         _guild_id: Option<GuildId>,
     ) {
         message::delete(&self.db, channel_id, message_id).await;
+        reaction::remove_messages(&self.db, &[message_id]).await;
     }
 
     async fn message_delete_bulk(
@@ -323,9 +582,31 @@ impl EventHandler for Handler {
         message_ids: Vec<MessageId>,
         _guild_id: Option<GuildId>,
     ) {
+        reaction::remove_messages(&self.db, &message_ids).await;
         message::delete_bulk(&self.db, channel_id, message_ids).await;
     }
 
+    async fn reaction_add(&self, ctx: Context, add_reaction: Reaction) {
+        reaction::add(&ctx, &self.db, &add_reaction).await;
+    }
+
+    async fn reaction_remove(&self, _ctx: Context, removed_reaction: Reaction) {
+        reaction::remove(&self.db, &removed_reaction).await;
+    }
+
+    async fn reaction_remove_all(
+        &self,
+        _ctx: Context,
+        _channel_id: ChannelId,
+        removed_from_message_id: MessageId,
+    ) {
+        reaction::remove_messages(&self.db, &[removed_from_message_id]).await;
+    }
+
+    async fn reaction_remove_emoji(&self, _ctx: Context, removed_reactions: Reaction) {
+        reaction::remove_emoji(&self.db, &removed_reactions).await;
+    }
+
     async fn message_update(
         &self,
         ctx: Context,
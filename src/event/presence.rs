@@ -1,12 +1,14 @@
+use super::subscription;
 use crate::model;
+use chrono::{DateTime, Utc};
 use serenity::client::Context;
 use serenity::model::{
-    gateway::{ActivityType, Presence},
-    id::GuildId,
+    gateway::{Activity, ActivityType, Presence},
+    id::{GuildId, UserId},
 };
 use sqlx::{Pool, Postgres};
 use std::collections::HashSet;
-use tracing::{error, warn};
+use tracing::{error, info, warn};
 
 pub async fn update(
     ctx: &Context,
@@ -64,6 +66,12 @@ pub async fn update(
         }
     });
 
+    let activities: Vec<model::UserActivity> = presence
+        .activities
+        .iter()
+        .filter_map(user_activity)
+        .collect();
+
     if guild_id.is_none() {
         warn!(user_id = user_id.get(), status = ?presence.status, ?game_name, "Presence without guild");
     }
@@ -94,20 +102,34 @@ pub async fn update(
         }
     }
 
-    // Do nothing if presence's status and game name haven't changed since the last update we saw
+    // Do nothing if presence's status, game name, and activities haven't changed since the last update we saw
     let last_presence_map = {
         let data = ctx.data.read().await;
         #[allow(clippy::unwrap_used)]
-        if let Some(last_presence) = data
-            .get::<model::LastUserPresence>()
-            .unwrap()
-            .clone()
-            .read()
-            .await
-            .get(&user_id)
-            && last_presence.status == presence.status
-            && last_presence.game_name == game_name
-        {
+        let last_presence_map = data.get::<model::LastUserPresence>().unwrap().clone();
+        let (presence_changed, activities_changed, game_changed) =
+            match last_presence_map.read().await.get(&user_id) {
+                Some(last_presence) => (
+                    last_presence.status != presence.status || last_presence.game_name != game_name,
+                    last_presence.activities != activities,
+                    last_presence.game_name != game_name,
+                ),
+                None => (true, true, true),
+            };
+        if !presence_changed && !activities_changed {
+            return;
+        }
+
+        if activities_changed && let Err(e) = update_activities(db, user_id, &activities).await {
+            error!(%e, "Error saving user_activity");
+            return;
+        }
+        if !presence_changed {
+            last_presence_map
+                .write()
+                .await
+                .entry(user_id)
+                .and_modify(|p| p.activities = activities);
             return;
         }
 
@@ -125,9 +147,25 @@ pub async fn update(
             error!(%e, "Error saving user_presence");
             return;
         }
+        if let Err(e) =
+            update_sessions(db, user_id, presence.status.name(), game_name.as_deref()).await
+        {
+            error!(%e, "Error saving game_session or online_session");
+        }
+        // presence seen while starting up isn't someone starting to play, they may have been playing for hours
+        if game_changed
+            && !is_startup
+            && let Some(game_name) = &game_name
+        {
+            tokio::spawn(subscription::notify(
+                ctx.clone(),
+                db.clone(),
+                user_id,
+                game_name.clone(),
+            ));
+        }
 
-        #[allow(clippy::unwrap_used)]
-        data.get::<model::LastUserPresence>().unwrap().clone()
+        last_presence_map
     };
     let mut last_presence_map = last_presence_map.write().await;
     last_presence_map.insert(
@@ -135,6 +173,263 @@ pub async fn update(
         model::UserPresence {
             status: presence.status,
             game_name,
+            activities,
         },
     );
 }
+
+// Converts an activity into how it's recorded, or None for activity types that aren't recorded
+fn user_activity(activity: &Activity) -> Option<model::UserActivity> {
+    let kind = match activity.kind {
+        ActivityType::Playing => "playing",
+        ActivityType::Streaming => "streaming",
+        ActivityType::Listening => "listening",
+        ActivityType::Watching => "watching",
+        ActivityType::Custom => "custom",
+        ActivityType::Competing => "competing",
+        _ => return None,
+    };
+    let emoji = activity.emoji.as_ref().map(|e| match e.id {
+        Some(id) if e.animated == Some(true) => format!("<a:{}:{id}>", e.name),
+        Some(id) => format!("<:{}:{id}>", e.name),
+        None => e.name.clone(),
+    });
+    Some(model::UserActivity {
+        kind,
+        name: truncate(&activity.name, 512),
+        details: activity.details.as_deref().map(|d| truncate(d, 512)),
+        state: activity.state.as_deref().map(|s| truncate(s, 512)),
+        url: activity.url.as_ref().map(|u| truncate(u.as_str(), 512)),
+        emoji: emoji.map(|e| truncate(&e, 128)),
+    })
+}
+
+fn truncate(s: &str, max_chars: usize) -> String {
+    s.trim().chars().take(max_chars).collect()
+}
+
+// Ends the user's open activities that aren't in activities, and starts any in activities that aren't open
+async fn update_activities(
+    db: &Pool<Postgres>,
+    user_id: UserId,
+    activities: &[model::UserActivity],
+) -> Result<(), sqlx::Error> {
+    let mut tx = db.begin().await?;
+    #[allow(clippy::panic)]
+    let open = sqlx::query!(
+        r#"SELECT id, kind::text AS "kind!", name, details, state, url, emoji FROM user_activity WHERE user_id = $1 AND end_date IS NULL"#,
+        i64::from(user_id)
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let mut ended = vec![];
+    let mut continuing = vec![];
+    for row in open {
+        let position = activities.iter().position(|a| {
+            a.kind == row.kind
+                && a.name == row.name
+                && a.details == row.details
+                && a.state == row.state
+                && a.url == row.url
+                && a.emoji == row.emoji
+        });
+        match position {
+            Some(i) if !continuing.contains(&i) => continuing.push(i),
+            _ => ended.push(row.id),
+        }
+    }
+
+    if !ended.is_empty() {
+        #[allow(clippy::panic)]
+        sqlx::query!(
+            "UPDATE user_activity SET end_date = now() WHERE id = ANY($1)",
+            &ended
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+    for (i, activity) in activities.iter().enumerate() {
+        if continuing.contains(&i) {
+            continue;
+        }
+        #[allow(clippy::panic)]
+        sqlx::query!(
+            "INSERT INTO user_activity (user_id, kind, name, details, state, url, emoji) VALUES ($1, $2::activity_type, $3, $4, $5, $6, $7)",
+            i64::from(user_id),
+            activity.kind as _,
+            activity.name,
+            activity.details,
+            activity.state,
+            activity.url,
+            activity.emoji
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await
+}
+
+// Ends the user's open game and online sessions if they changed, and starts new ones for what they're doing now
+// Offline and invisible aren't recorded as online sessions
+async fn update_sessions(
+    db: &Pool<Postgres>,
+    user_id: UserId,
+    status: &str,
+    game_name: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    let user_id = i64::from(user_id);
+    let mut tx = db.begin().await?;
+    #[allow(clippy::panic)]
+    sqlx::query!(
+        "UPDATE game_session SET end_date = now() WHERE user_id = $1 AND end_date IS NULL AND game_name IS DISTINCT FROM $2",
+        user_id,
+        game_name
+    )
+    .execute(&mut *tx)
+    .await?;
+    #[allow(clippy::panic)]
+    sqlx::query!(
+        r#"
+INSERT INTO game_session (user_id, game_name)
+SELECT $1, $2::varchar
+WHERE $2::varchar IS NOT NULL
+AND NOT EXISTS (SELECT 1 FROM game_session WHERE user_id = $1 AND end_date IS NULL)"#,
+        user_id,
+        game_name
+    )
+    .execute(&mut *tx)
+    .await?;
+    #[allow(clippy::panic)]
+    sqlx::query!(
+        "UPDATE online_session SET end_date = now() WHERE user_id = $1 AND end_date IS NULL AND status <> $2::online_status",
+        user_id,
+        status as _
+    )
+    .execute(&mut *tx)
+    .await?;
+    #[allow(clippy::panic)]
+    sqlx::query!(
+        r#"
+INSERT INTO online_session (user_id, status)
+SELECT $1, $2::online_status
+WHERE $2::online_status NOT IN ('offline', 'invisible')
+AND NOT EXISTS (SELECT 1 FROM online_session WHERE user_id = $1 AND end_date IS NULL)"#,
+        user_id,
+        status as _
+    )
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await
+}
+
+// Ends activities and sessions left open by the last run of the bot at the last time it was known to be running
+// Should be run before connecting to Discord, open activities and sessions are started again by the first presence updates
+pub async fn end_stale_sessions(db: &Pool<Postgres>) {
+    #[allow(clippy::panic)]
+    if let Err(e) = sqlx::query!(
+        "UPDATE user_activity SET end_date = GREATEST(start_date, COALESCE((SELECT max(update_date) FROM bot_start), now())) WHERE end_date IS NULL"
+    )
+    .execute(db)
+    .await
+    {
+        error!(%e, "Error ending stale user_activity");
+    }
+    #[allow(clippy::panic)]
+    if let Err(e) = sqlx::query!(
+        "UPDATE game_session SET end_date = GREATEST(start_date, COALESCE((SELECT max(update_date) FROM bot_start), now())) WHERE end_date IS NULL"
+    )
+    .execute(db)
+    .await
+    {
+        error!(%e, "Error ending stale game_session");
+    }
+    #[allow(clippy::panic)]
+    if let Err(e) = sqlx::query!(
+        "UPDATE online_session SET end_date = GREATEST(start_date, COALESCE((SELECT max(update_date) FROM bot_start), now())) WHERE end_date IS NULL"
+    )
+    .execute(db)
+    .await
+    {
+        error!(%e, "Error ending stale online_session");
+    }
+}
+
+// Returns when game_session and online_session started being recorded, which is now if they're empty
+// Should be run before connecting to Discord, so presence before these dates is what backfill_sessions needs to build sessions from
+pub async fn session_cutoffs(
+    db: &Pool<Postgres>,
+) -> Result<(DateTime<Utc>, DateTime<Utc>), sqlx::Error> {
+    #[allow(clippy::panic)]
+    let row = sqlx::query!(
+        r#"SELECT COALESCE((SELECT min(start_date) FROM game_session), now()) AS "game!", COALESCE((SELECT min(start_date) FROM online_session), now()) AS "online!""#
+    )
+    .fetch_one(db)
+    .await?;
+    Ok((row.game, row.online))
+}
+
+// Builds game and online sessions from user_presence recorded before sessions were, sessions still open at the cutoff end there
+// Does nothing once there's no presence left before the cutoffs to build from
+pub async fn backfill_sessions(
+    db: Pool<Postgres>,
+    game_cutoff: DateTime<Utc>,
+    online_cutoff: DateTime<Utc>,
+) {
+    #[allow(clippy::panic)]
+    match sqlx::query!(
+        r"
+WITH changes AS (
+  SELECT user_id, game_name, create_date, LAG(game_name) OVER w AS prev_game
+  FROM user_presence
+  WHERE create_date < $1
+  WINDOW w AS (PARTITION BY user_id ORDER BY create_date, id)
+), spans AS (
+  SELECT user_id, game_name, create_date AS start_date,
+    COALESCE(LEAD(create_date) OVER (PARTITION BY user_id ORDER BY create_date), $1) AS end_date
+  FROM changes
+  WHERE game_name IS DISTINCT FROM prev_game
+)
+INSERT INTO game_session (user_id, game_name, start_date, end_date)
+SELECT user_id, game_name, start_date, end_date
+FROM spans
+WHERE game_name IS NOT NULL
+ORDER BY start_date",
+        game_cutoff
+    )
+    .execute(&db)
+    .await
+    {
+        Ok(r) => info!(rows = r.rows_affected(), "Backfilled game_session"),
+        Err(e) => error!(%e, "Error backfilling game_session"),
+    }
+
+    #[allow(clippy::panic)]
+    match sqlx::query!(
+        r"
+WITH changes AS (
+  SELECT user_id, status, create_date, LAG(status) OVER w AS prev_status
+  FROM user_presence
+  WHERE create_date < $1
+  WINDOW w AS (PARTITION BY user_id ORDER BY create_date, id)
+), spans AS (
+  SELECT user_id, status, create_date AS start_date,
+    COALESCE(LEAD(create_date) OVER (PARTITION BY user_id ORDER BY create_date), $1) AS end_date
+  FROM changes
+  WHERE status IS DISTINCT FROM prev_status
+)
+INSERT INTO online_session (user_id, status, start_date, end_date)
+SELECT user_id, status, start_date, end_date
+FROM spans
+WHERE status NOT IN ('offline', 'invisible')
+ORDER BY start_date",
+        online_cutoff
+    )
+    .execute(&db)
+    .await
+    {
+        Ok(r) => info!(rows = r.rows_affected(), "Backfilled online_session"),
+        Err(e) => error!(%e, "Error backfilling online_session"),
+    }
+}
@@ -0,0 +1,145 @@
+use crate::commands::vote::{self, Vote, VoteConfig};
+use crate::event::report_interaction_error;
+use serenity::client::Context;
+use serenity::model::channel::{Reaction, ReactionType};
+use serenity::model::id::MessageId;
+use sqlx::{Pool, Postgres};
+use tracing::error;
+
+// Votes on the author of a message when a member reacts to it with one of the guild's vote emojis
+// Rejected votes have their reaction removed so it doesn't look like it counted
+pub async fn add(ctx: &Context, db: &Pool<Postgres>, reaction: &Reaction) {
+    let (Some(guild_id), Some(voter_id)) = (reaction.guild_id, reaction.user_id) else {
+        return;
+    };
+    if reaction.member.as_ref().is_some_and(|m| m.user.bot) {
+        return;
+    }
+    let config = match vote::vote_config(db, i64::from(guild_id)).await {
+        Ok(c) => c,
+        Err(e) => {
+            error!(%e, "Error getting vote_config");
+            return;
+        }
+    };
+    let Some(is_upvote) = vote_emoji(&config, &reaction.emoji) else {
+        return;
+    };
+    let author_id = match reaction.message_author_id {
+        Some(author_id) => author_id,
+        None => match reaction.message(&ctx.http).await {
+            Ok(m) => m.author.id,
+            Err(e) => {
+                error!(%e, "Error getting reacted message");
+                return;
+            }
+        },
+    };
+
+    match vote::process_votes(
+        ctx,
+        voter_id,
+        guild_id,
+        reaction.channel_id,
+        Some(reaction.message_id),
+        &[Vote {
+            is_upvote,
+            votee_id: author_id,
+            count: 1,
+            reason: None,
+        }],
+    )
+    .await
+    .map(|mut replies| replies.pop().flatten())
+    {
+        Ok(Some(_)) => {
+            if let Err(e) = reaction.delete(&ctx.http).await {
+                error!(%e, "unable to remove rejected vote reaction");
+            }
+        }
+        Ok(None) => {}
+        Err(e) => {
+            error!(%e, "unable to process vote reaction");
+            report_interaction_error(ctx, format!("error running vote from reaction: `{e}`")).await;
+        }
+    }
+}
+
+// Reverts the vote made by a reaction when the reaction is removed
+pub async fn remove(db: &Pool<Postgres>, reaction: &Reaction) {
+    let (Some(guild_id), Some(voter_id)) = (reaction.guild_id, reaction.user_id) else {
+        return;
+    };
+    let config = match vote::vote_config(db, i64::from(guild_id)).await {
+        Ok(c) => c,
+        Err(e) => {
+            error!(%e, "Error getting vote_config");
+            return;
+        }
+    };
+    let Some(is_upvote) = vote_emoji(&config, &reaction.emoji) else {
+        return;
+    };
+
+    if let Err(e) = vote::revert_vote(
+        db,
+        is_upvote,
+        i64::from(voter_id),
+        i64::from(guild_id),
+        i64::from(reaction.message_id),
+    )
+    .await
+    {
+        error!(%e, "Error reverting vote");
+    }
+}
+
+// Reverts the votes made by a vote emoji when all of its reactions are removed from a message
+pub async fn remove_emoji(db: &Pool<Postgres>, reaction: &Reaction) {
+    let Some(guild_id) = reaction.guild_id else {
+        return;
+    };
+    let config = match vote::vote_config(db, i64::from(guild_id)).await {
+        Ok(c) => c,
+        Err(e) => {
+            error!(%e, "Error getting vote_config");
+            return;
+        }
+    };
+    let Some(is_upvote) = vote_emoji(&config, &reaction.emoji) else {
+        return;
+    };
+
+    if let Err(e) =
+        vote::revert_message_votes(db, &[i64::from(reaction.message_id)], Some(is_upvote)).await
+    {
+        error!(%e, "Error reverting emoji votes");
+    }
+}
+
+// Reverts every vote made by reacting to the messages, when their reactions are cleared or they're deleted
+pub async fn remove_messages(db: &Pool<Postgres>, message_ids: &[MessageId]) {
+    let message_ids: Vec<i64> = message_ids.iter().copied().map(i64::from).collect();
+    if let Err(e) = vote::revert_message_votes(db, &message_ids, None).await {
+        error!(%e, "Error reverting message votes");
+    }
+}
+
+// Returns whether the emoji upvotes or downvotes, None if it isn't a vote emoji in the guild
+// Custom emojis are matched by ID so renaming them doesn't stop them from voting
+fn vote_emoji(config: &VoteConfig, emoji: &ReactionType) -> Option<bool> {
+    let matches = |configured: Option<&String>| {
+        configured.is_some_and(|c| match (ReactionType::try_from(c.as_str()), emoji) {
+            (Ok(ReactionType::Custom { id: a, .. }), ReactionType::Custom { id: b, .. }) => a == *b,
+            (_, ReactionType::Unicode(e)) => e == c,
+            _ => false,
+        })
+    };
+    if matches(config.upvote_emoji.as_ref()) {
+        Some(true)
+    } else if matches(config.downvote_emoji.as_ref()) {
+        Some(false)
+    } else {
+        None
+    }
+}
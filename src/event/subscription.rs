@@ -0,0 +1,177 @@
+use crate::commands::timezone::user_timezone;
+use crate::model;
+use chrono::prelude::*;
+use serenity::all::{ChannelId, CreateAllowedMentions, CreateMessage, GuildId, UserId};
+use serenity::client::Context;
+use sqlx::{Pool, Postgres};
+use tracing::error;
+
+// Notifies members subscribed to the game user_id just started playing, in every guild they share
+// Should be run after the user's game_session is started, so they're counted as playing
+pub async fn notify(ctx: Context, db: Pool<Postgres>, user_id: UserId, game_name: String) {
+    let guild_ids: Vec<i64> = {
+        let guild_lists = {
+            let data = ctx.data.read().await;
+            #[allow(clippy::unwrap_used)]
+            data.get::<model::UserGuildList>().unwrap().clone()
+        };
+        guild_lists
+            .read()
+            .await
+            .get(&user_id)
+            .map(|g| g.iter().map(|id| i64::from(*id)).collect())
+            .unwrap_or_default()
+    };
+    if guild_ids.is_empty() {
+        return;
+    }
+
+    // subscriptions off cooldown for the game, aliases are merged into the name subscriptions use
+    #[allow(clippy::panic)]
+    let subscriptions = match sqlx::query!(
+        r#"
+SELECT id, guild_id, user_id, game, min_players, channel_id, quiet_start, quiet_end
+FROM game_subscription
+WHERE guild_id = ANY($1)
+AND game = COALESCE((SELECT a.game FROM game_alias a WHERE a.guild_id = game_subscription.guild_id AND a.alias = $2), $2)
+AND user_id <> $3
+AND (last_notify_date IS NULL OR last_notify_date + cooldown_minutes * interval '1 minute' <= now())"#,
+        &guild_ids,
+        game_name,
+        i64::from(user_id)
+    )
+    .fetch_all(&db)
+    .await
+    {
+        Ok(s) => s,
+        Err(e) => {
+            error!(%e, "Error getting game_subscription");
+            return;
+        }
+    };
+
+    for subscription in subscriptions {
+        let (Ok(guild_id), Ok(subscriber_id)) = (
+            u64::try_from(subscription.guild_id),
+            u64::try_from(subscription.user_id),
+        ) else {
+            error!(
+                id = subscription.id,
+                "unable to convert game_subscription ids"
+            );
+            continue;
+        };
+        let (guild_id, subscriber_id) = (GuildId::new(guild_id), UserId::new(subscriber_id));
+        let Some((guild_name, member_ids)) = ctx.cache.guild(guild_id).map(|g| {
+            (
+                g.name.clone(),
+                g.members
+                    .keys()
+                    .map(|id| i64::from(*id))
+                    .collect::<Vec<i64>>(),
+            )
+        }) else {
+            continue;
+        };
+
+        // members of the guild, other than the subscriber, playing the game right now
+        #[allow(clippy::panic)]
+        let players = match sqlx::query!(
+            "SELECT s.user_id FROM game_session s LEFT JOIN game_alias a ON a.guild_id = $4 AND a.alias = s.game_name WHERE s.end_date IS NULL AND COALESCE(a.game, s.game_name) = $1 AND s.user_id = ANY($2) AND s.user_id <> $3 ORDER BY s.start_date",
+            subscription.game,
+            &member_ids,
+            subscription.user_id,
+            subscription.guild_id
+        )
+        .fetch_all(&db)
+        .await
+        {
+            Ok(p) => p,
+            Err(e) => {
+                error!(%e, "Error getting game_session players");
+                continue;
+            }
+        };
+        if players.len() < usize::try_from(subscription.min_players).unwrap_or(usize::MAX) {
+            continue;
+        }
+
+        if let (Some(start), Some(end)) = (subscription.quiet_start, subscription.quiet_end) {
+            let tz = match user_timezone(&ctx, subscriber_id).await {
+                Ok(tz) => tz,
+                Err(e) => {
+                    error!(%e, "Error getting subscriber timezone");
+                    continue;
+                }
+            };
+            let hour = i16::try_from(Utc::now().with_timezone(&tz).hour()).unwrap_or_default();
+            // quiet hours can wrap past midnight, like 22 to 7
+            let quiet = if start <= end {
+                start <= hour && hour < end
+            } else {
+                hour >= start || hour < end
+            };
+            if quiet {
+                continue;
+            }
+        }
+
+        // claim the notification so overlapping presence updates don't notify twice
+        #[allow(clippy::panic)]
+        match sqlx::query!(
+            "UPDATE game_subscription SET last_notify_date = now() WHERE id = $1 AND (last_notify_date IS NULL OR last_notify_date + cooldown_minutes * interval '1 minute' <= now())",
+            subscription.id
+        )
+        .execute(&db)
+        .await
+        {
+            Ok(r) if r.rows_affected() == 0 => continue,
+            Ok(_) => {}
+            Err(e) => {
+                error!(%e, "Error updating game_subscription");
+                continue;
+            }
+        }
+
+        let player_mentions: Vec<String> = players
+            .iter()
+            .map(|p| format!("<@{}>", p.user_id))
+            .collect();
+        let content = if players.len() > 1 {
+            format!(
+                "{} are playing {} in {guild_name}",
+                player_mentions.join(", "),
+                subscription.game
+            )
+        } else {
+            format!(
+                "{} started playing {} in {guild_name}",
+                player_mentions.concat(),
+                subscription.game
+            )
+        };
+        let res = match subscription.channel_id.map(u64::try_from) {
+            Some(Err(e)) => {
+                error!(error = %e, channel_id = subscription.channel_id, "unable to convert channel id");
+                continue;
+            }
+            // only the subscriber is pinged, not the players or anything in the game's name
+            Some(Ok(channel_id)) => ChannelId::new(channel_id)
+                .send_message(
+                    &ctx.http,
+                    CreateMessage::new()
+                        .content(format!("<@{subscriber_id}>: {content}"))
+                        .allowed_mentions(CreateAllowedMentions::new().users([subscriber_id])),
+                )
+                .await
+                .map(|_| ()),
+            None => subscriber_id
+                .direct_message(&ctx.http, CreateMessage::new().content(content))
+                .await
+                .map(|_| ()),
+        };
+        if let Err(e) = res {
+            error!(%e, subscriber = subscriber_id.get(), "Error sending game subscription notification");
+        }
+    }
+}
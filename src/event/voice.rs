@@ -0,0 +1,149 @@
+use serenity::model::{guild::Guild, id::ChannelId, voice::VoiceState};
+use sqlx::{Pool, Postgres};
+use tracing::error;
+
+// The parts of a voice state that are tracked in voice_session, a change in any of them starts a new session
+#[derive(PartialEq, Eq)]
+struct SessionState {
+    channel_id: Option<ChannelId>,
+    mute: bool,
+    deaf: bool,
+    stream: bool,
+}
+
+impl From<&VoiceState> for SessionState {
+    fn from(state: &VoiceState) -> Self {
+        Self {
+            channel_id: state.channel_id,
+            mute: state.self_mute || state.mute,
+            deaf: state.self_deaf || state.deaf,
+            stream: state.self_stream.unwrap_or(false),
+        }
+    }
+}
+
+// Closes the user's open voice session and opens a new one if they're still in a voice channel
+// Called on join, leave, move, and any mute/deaf/stream change
+pub async fn update(db: &Pool<Postgres>, old: Option<&VoiceState>, new: &VoiceState) {
+    let Some(guild_id) = new.guild_id else {
+        return;
+    };
+    if new.member.as_ref().is_some_and(|m| m.user.bot) {
+        // ignore updates from bots
+        return;
+    }
+    let state = SessionState::from(new);
+    if let Some(old) = old
+        && SessionState::from(old) == state
+    {
+        return;
+    }
+
+    let mut tx = match db.begin().await {
+        Ok(t) => t,
+        Err(e) => {
+            error!(%e, "Error starting voice_session transaction");
+            return;
+        }
+    };
+
+    #[allow(clippy::panic)]
+    if let Err(e) = sqlx::query!(
+        "UPDATE voice_session SET leave_date = now() WHERE guild_id = $1 AND user_id = $2 AND leave_date IS NULL",
+        i64::from(guild_id),
+        i64::from(new.user_id)
+    )
+    .execute(&mut *tx)
+    .await
+    {
+        error!(%e, "Error closing voice_session");
+        return;
+    }
+
+    if let Some(channel_id) = state.channel_id {
+        #[allow(clippy::panic)]
+        if let Err(e) = sqlx::query!(
+            "INSERT INTO voice_session (guild_id, channel_id, user_id, mute, deaf, stream) VALUES ($1, $2, $3, $4, $5, $6)",
+            i64::from(guild_id),
+            i64::from(channel_id),
+            i64::from(new.user_id),
+            state.mute,
+            state.deaf,
+            state.stream
+        )
+        .execute(&mut *tx)
+        .await
+        {
+            error!(%e, "Error saving voice_session");
+            return;
+        }
+    }
+
+    if let Err(e) = tx.commit().await {
+        error!(%e, "Error committing voice_session");
+    }
+}
+
+// Reconciles open voice sessions with the voice states sent when a guild becomes available
+// Sessions that no longer match are closed now, since we can't know when they actually ended while we weren't connected
+pub async fn sync_guild(db: &Pool<Postgres>, guild: &Guild) {
+    let mut user_ids = vec![];
+    let mut channel_ids = vec![];
+    let mut mutes = vec![];
+    let mut deafs = vec![];
+    let mut streams = vec![];
+    for (user_id, voice_state) in &guild.voice_states {
+        if guild.members.get(user_id).is_some_and(|m| m.user.bot) {
+            continue;
+        }
+        let state = SessionState::from(voice_state);
+        if let Some(channel_id) = state.channel_id {
+            user_ids.push(i64::from(*user_id));
+            channel_ids.push(i64::from(channel_id));
+            mutes.push(state.mute);
+            deafs.push(state.deaf);
+            streams.push(state.stream);
+        }
+    }
+
+    #[allow(clippy::panic)]
+    if let Err(e) = sqlx::query!(
+        r"
+UPDATE voice_session SET leave_date = now()
+WHERE guild_id = $1
+AND leave_date IS NULL
+AND (user_id, channel_id, mute, deaf, stream) NOT IN (SELECT * FROM UNNEST($2::bigint[], $3::bigint[], $4::bool[], $5::bool[], $6::bool[]))",
+        i64::from(guild.id),
+        &user_ids,
+        &channel_ids,
+        &mutes,
+        &deafs,
+        &streams
+    )
+    .execute(db)
+    .await
+    {
+        error!(%e, "Error closing stale voice_sessions");
+        return;
+    }
+
+    #[allow(clippy::panic)]
+    if let Err(e) = sqlx::query!(
+        r"
+INSERT INTO voice_session (guild_id, user_id, channel_id, mute, deaf, stream)
+SELECT $1, s.user_id, s.channel_id, s.mute, s.deaf, s.stream
+FROM UNNEST($2::bigint[], $3::bigint[], $4::bool[], $5::bool[], $6::bool[]) AS s(user_id, channel_id, mute, deaf, stream)
+WHERE NOT EXISTS (SELECT 1 FROM voice_session v WHERE v.guild_id = $1 AND v.user_id = s.user_id AND v.leave_date IS NULL)",
+        i64::from(guild.id),
+        &user_ids,
+        &channel_ids,
+        &mutes,
+        &deafs,
+        &streams
+    )
+    .execute(db)
+    .await
+    {
+        error!(%e, "Error saving voice_sessions on startup");
+    }
+}
@@ -1,12 +1,16 @@
 mod airnow;
+mod chart;
 mod commands;
 mod config;
+mod digest;
 mod error;
 mod event;
 mod google;
 mod model;
-mod shippo;
+mod paginate;
+mod season;
 mod tomorrowio;
+mod tracking;
 mod twitch;
 mod util;
 
@@ -15,6 +19,7 @@ use serenity::all::ApplicationId;
 use serenity::client::Client;
 use serenity::model::gateway::GatewayIntents;
 use serenity::prelude::*;
+use songbird::SerenityInit;
 use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
 use sqlx::{ConnectOptions, Pool, Postgres};
 use std::collections::HashMap;
@@ -87,7 +92,25 @@ async fn main() {
 
     let db_conn = pool.clone();
 
-    let shippo_api_key = cfg.shippo.api_key.clone();
+    let mut shippo_webhook = None;
+    let tracker: Arc<dyn tracking::TrackingProvider> = match cfg.tracking.provider {
+        config::TrackingProvider::Shippo => match cfg.shippo {
+            Some(shippo) => {
+                // an empty token would let any request ending in ?token= through
+                if shippo.webhook.as_ref().is_some_and(|w| w.token.is_empty()) {
+                    error!("Empty token in [shippo.webhook] in config.toml");
+                    exit(1);
+                }
+                shippo_webhook = shippo.webhook;
+                Arc::new(tracking::Shippo::new(shippo.api_key))
+            }
+            None => {
+                error!("Missing [shippo] in config.toml, needed to track shipments with Shippo");
+                exit(1);
+            }
+        },
+        config::TrackingProvider::Fake => Arc::new(tracking::Fake::new(chrono::Utc::now())),
+    };
 
     let event_handler =
         match event::Handler::new(pool.clone(), cfg.discord.suppress_embed_channel_id) {
@@ -102,6 +125,7 @@ async fn main() {
         | GatewayIntents::GUILD_MEMBERS
         | GatewayIntents::GUILD_PRESENCES
         | GatewayIntents::GUILD_MESSAGES
+        | GatewayIntents::GUILD_MESSAGE_REACTIONS
         | GatewayIntents::GUILD_VOICE_STATES
         | GatewayIntents::DIRECT_MESSAGES
         | GatewayIntents::MESSAGE_CONTENT;
@@ -109,19 +133,22 @@ async fn main() {
         .application_id(ApplicationId::new(cfg.discord.application_id))
         .type_map_insert::<model::DB>(pool.clone())
         .type_map_insert::<config::Google>(cfg.google)
-        .type_map_insert::<config::Shippo>(cfg.shippo)
+        .type_map_insert::<config::Sound>(cfg.sound)
         .type_map_insert::<config::TarkovMarket>(cfg.tarkov_market)
         .type_map_insert::<config::TomorrowIO>(cfg.tomorrow_io)
         .type_map_insert::<config::AirNow>(cfg.air_now)
         .type_map_insert::<config::Twitch>(cfg.twitch)
         .type_map_insert::<config::WolframAlpha>(cfg.wolfram_alpha)
         .type_map_insert::<config::Wow>(cfg.wow)
+        .type_map_insert::<model::Tracker>(tracker.clone())
         .type_map_insert::<model::OwnerId>(cfg.owner_id)
         .type_map_insert::<model::LastUserPresence>(Arc::new(RwLock::new(HashMap::new())))
         .type_map_insert::<model::UserGuildList>(Arc::new(RwLock::new(HashMap::new())))
         .type_map_insert::<model::StartInstant>(Instant::now())
         .type_map_insert::<model::GuildVoiceLocks>(Arc::new(Mutex::new(HashMap::new())))
+        .type_map_insert::<model::WowRealms>(Arc::new(RwLock::new(None)))
         .event_handler(event_handler)
+        .register_songbird()
         .await
     {
         Ok(c) => c,
@@ -134,7 +161,8 @@ async fn main() {
     info!("Starting...");
 
     let mut set = JoinSet::new();
-    let shippo_http = client.http.clone();
+    let tracking_http = client.http.clone();
+    let digest_http = client.http.clone();
 
     let shard_manager = client.shard_manager.clone();
     tokio::spawn(async move {
@@ -145,6 +173,19 @@ async fn main() {
         shard_manager.shutdown_all().await;
     });
 
+    event::end_stale_sessions(&db_conn).await;
+    // sessions are backfilled in the background, anything recorded after the cutoffs comes from presence updates
+    match event::session_cutoffs(&db_conn).await {
+        Ok((game_cutoff, online_cutoff)) => {
+            tokio::spawn(event::backfill_sessions(
+                db_conn.clone(),
+                game_cutoff,
+                online_cutoff,
+            ));
+        }
+        Err(e) => error!(%e, "Error getting session backfill cutoffs"),
+    }
+
     set.spawn(async move {
         if let Err(e) = client.start().await {
             error!(%e, "Error running Discord client");
@@ -152,10 +193,20 @@ async fn main() {
         }
     });
 
-    set.spawn(shippo::poll_shipments_loop(
-        shippo_http,
+    if let Some(webhook) = &shippo_webhook {
+        tokio::spawn(tracking::serve_webhooks(
+            webhook.listen.clone(),
+            webhook.token.clone(),
+            tracking_http.clone(),
+            pool.clone(),
+        ));
+    }
+
+    set.spawn(tracking::poll_shipments_loop(
+        tracking_http,
         pool,
-        shippo_api_key,
+        tracker,
+        shippo_webhook.is_some(),
     ));
 
     let start_id =
@@ -170,6 +221,12 @@ async fn main() {
             }
         };
 
+    set.spawn(digest::post_digests_loop(digest_http, db_conn.clone()));
+
+    set.spawn(season::rollover_seasons_loop(db_conn.clone()));
+
+    set.spawn(paginate::gc_loop(db_conn.clone()));
+
     let updater_conn = db_conn.clone();
     if let Some(start_id) = start_id {
         set.spawn(uptime_update_loop(updater_conn.clone(), start_id));
@@ -1,3 +1,4 @@
+use crate::tracking::TrackingProvider;
 use serde::Deserialize;
 use serenity::model::{
     id::{GuildId, UserId},
@@ -20,6 +21,12 @@ impl TypeMapKey for DB {
     type Value = Pool<Postgres>;
 }
 
+pub struct Tracker;
+
+impl TypeMapKey for Tracker {
+    type Value = Arc<dyn TrackingProvider>;
+}
+
 pub struct OwnerId;
 
 impl TypeMapKey for OwnerId {
@@ -29,6 +36,19 @@ impl TypeMapKey for OwnerId {
 pub struct UserPresence {
     pub status: OnlineStatus,
     pub game_name: Option<String>,
+    pub activities: Vec<UserActivity>,
+}
+
+// An activity as it's recorded in the user_activity table
+#[derive(Clone, PartialEq, Eq)]
+pub struct UserActivity {
+    // name of the activity_type enum value
+    pub kind: &'static str,
+    pub name: String,
+    pub details: Option<String>,
+    pub state: Option<String>,
+    pub url: Option<String>,
+    pub emoji: Option<String>,
 }
 
 pub struct LastUserPresence;
@@ -61,6 +81,13 @@ impl fmt::Display for Point {
     }
 }
 
+// (name, slug) of every US realm, along with when the list was fetched
+pub struct WowRealms;
+
+impl TypeMapKey for WowRealms {
+    type Value = Arc<RwLock<Option<(Instant, Vec<(String, String)>)>>>;
+}
+
 pub struct StartInstant;
 
 impl TypeMapKey for StartInstant {
@@ -0,0 +1,395 @@
+use crate::error::{CommandError, CommandResult};
+use crate::model::DB;
+use chrono::{Duration, prelude::*};
+use serde::{Serialize, de::DeserializeOwned};
+use serenity::all::{
+    ButtonStyle, CommandInteraction, ComponentInteraction, ComponentInteractionDataKind,
+};
+use serenity::async_trait;
+use serenity::builder::{
+    CreateActionRow, CreateAllowedMentions, CreateButton, CreateInteractionResponse,
+    CreateInteractionResponseMessage, CreateSelectMenu, CreateSelectMenuKind,
+    CreateSelectMenuOption, EditInteractionResponse, EditMessage,
+};
+use serenity::client::Context;
+use serenity::model::channel::{Message, MessageFlags};
+use sqlx::{Pool, Postgres};
+use tracing::error;
+
+// How long pages of a message can be changed after it's sent
+const EXPIRY_DAYS: i64 = 7;
+// Discord allows at most 25 options in a select menu
+const MAX_SELECT_OPTIONS: usize = 25;
+
+pub struct Page {
+    pub content: String,
+    pub page_count: usize,
+}
+
+// Implemented by the arguments of a command that produces a list too long for one message
+// The implementor is persisted as JSON so that other pages can be rendered when buttons are pressed
+#[async_trait]
+pub trait Paginate: Serialize + DeserializeOwned + Send + Sync {
+    // Identifies the implementor in the paginator table, must be unique
+    const KIND: &'static str;
+
+    // Renders the given zero-indexed page along with the total number of pages
+    async fn render(&self, ctx: &Context, page: usize) -> Result<Page, CommandError>;
+}
+
+// Row from the paginator table along with the page that was requested by a component interaction
+pub struct State {
+    pub id: i32,
+    pub kind: String,
+    payload: serde_json::Value,
+    page: usize,
+}
+
+// Responds to interaction with the first page of pages, adding page controls if there's more than one page
+// Pages can list members and text they wrote, so nothing on them pings anyone
+// If author_only is set only the user that ran the command can change pages
+pub async fn send<P: Paginate>(
+    ctx: &Context,
+    interaction: &CommandInteraction,
+    pages: &P,
+    author_only: bool,
+) -> CommandResult {
+    let page = pages.render(ctx, 0).await?;
+
+    if page.page_count <= 1 {
+        interaction
+            .edit_response(
+                &ctx.http,
+                EditInteractionResponse::new()
+                    .content(&page.content)
+                    .allowed_mentions(CreateAllowedMentions::new()),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let id = {
+        let data = ctx.data.read().await;
+        #[allow(clippy::unwrap_used)]
+        let db = data.get::<DB>().unwrap();
+        #[allow(clippy::panic)]
+        sqlx::query!(
+            "INSERT INTO paginator(expire_date, author_id, author_only, kind, payload, page, page_count) VALUES ($1, $2, $3, $4, $5, 0, $6) RETURNING id",
+            Utc::now() + Duration::days(EXPIRY_DAYS),
+            i64::from(interaction.user.id),
+            author_only,
+            P::KIND,
+            serde_json::to_value(pages)?,
+            i32::try_from(page.page_count)?
+        )
+        .fetch_one(db)
+        .await?
+        .id
+    };
+
+    interaction
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new()
+                .content(&page.content)
+                .allowed_mentions(CreateAllowedMentions::new())
+                .components(create_components(id, 0, page.page_count, true)),
+        )
+        .await?;
+
+    // leave buttons disabled for 2 seconds, then send the message again with buttons enabled
+    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+
+    interaction
+        .edit_response(
+            &ctx.http,
+            EditInteractionResponse::new().components(create_components(
+                id,
+                0,
+                page.page_count,
+                false,
+            )),
+        )
+        .await?;
+
+    Ok(())
+}
+
+// Loads the paginator row for a page component (custom_id "page:<action>:<id>") and works out the requested page
+// Responds to and returns None for interactions on expired messages or from users that aren't allowed to change pages
+pub async fn load(
+    ctx: &Context,
+    db: &Pool<Postgres>,
+    interaction: &ComponentInteraction,
+    fields: &[&str],
+) -> Option<State> {
+    let id = match fields.get(2).map(|f| f.parse::<i32>()) {
+        Some(Ok(id)) => id,
+        Some(Err(e)) => {
+            error!(error = %e, "error parsing id from page interaction");
+            return None;
+        }
+        None => {
+            error!(
+                custom_id = interaction.data.custom_id,
+                "malformed page interaction"
+            );
+            return None;
+        }
+    };
+    #[allow(clippy::panic)]
+    let row = match sqlx::query!(
+        "SELECT expire_date, author_id, author_only, kind, payload, page, page_count FROM paginator WHERE id = $1",
+        id
+    )
+    .fetch_optional(db)
+    .await
+    {
+        Ok(row) => row,
+        Err(e) => {
+            error!(error = %e, "error getting paginator");
+            return None;
+        }
+    };
+    let Some(row) = row.filter(|r| r.expire_date > Utc::now()) else {
+        respond_ephemeral(
+            ctx,
+            interaction,
+            "These pages have expired, run the command again",
+        )
+        .await;
+        let mut message = (*interaction.message).clone();
+        if let Err(e) = message
+            .edit(ctx, EditMessage::new().components(vec![]))
+            .await
+        {
+            error!(error = %e, "error removing expired page components");
+        }
+        return None;
+    };
+    if row.author_only && row.author_id != i64::from(interaction.user.id) {
+        respond_ephemeral(
+            ctx,
+            interaction,
+            &format!("Only <@{}> can change these pages", row.author_id),
+        )
+        .await;
+        return None;
+    }
+
+    let current = usize::try_from(row.page).unwrap_or(0);
+    let last = usize::try_from(row.page_count)
+        .unwrap_or(1)
+        .saturating_sub(1);
+    let page = match fields[1] {
+        "first" => 0,
+        "prev" => current.saturating_sub(1),
+        "next" => current + 1,
+        "last" => last,
+        "jump" => match &interaction.data.kind {
+            ComponentInteractionDataKind::StringSelect { values } => values
+                .first()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(current),
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    Some(State {
+        id,
+        kind: row.kind,
+        payload: row.payload,
+        page: page.min(last),
+    })
+}
+
+// Renders the page requested in state and updates the message the component was on
+pub async fn turn<P: Paginate>(
+    ctx: &Context,
+    db: &Pool<Postgres>,
+    interaction: &ComponentInteraction,
+    state: State,
+) {
+    let pages: P = match serde_json::from_value(state.payload) {
+        Ok(p) => p,
+        Err(e) => {
+            error!(error = %e, kind = state.kind, "error deserializing paginator payload");
+            return;
+        }
+    };
+    let page = match pages.render(ctx, state.page).await {
+        Ok(p) => p,
+        Err(e) => {
+            error!(error = %e, kind = state.kind, "error rendering page");
+            return;
+        }
+    };
+
+    let Some(mut message) = show_page(
+        ctx,
+        interaction,
+        &page.content,
+        create_components(state.id, state.page, page.page_count, true),
+    )
+    .await
+    else {
+        return;
+    };
+
+    #[allow(
+        clippy::panic,
+        clippy::cast_possible_truncation,
+        clippy::cast_possible_wrap
+    )]
+    if let Err(e) = sqlx::query!(
+        "UPDATE paginator SET page = $2, page_count = $3 WHERE id = $1",
+        state.id,
+        state.page as i32,
+        page.page_count as i32
+    )
+    .execute(db)
+    .await
+    {
+        error!(error = %e, "error updating paginator after interaction");
+    }
+
+    enable_page(
+        ctx,
+        &mut message,
+        create_components(state.id, state.page, page.page_count, false),
+    )
+    .await;
+}
+
+// Deletes expired paginator rows every hour
+pub async fn gc_loop(db: Pool<Postgres>) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_hours(1));
+    loop {
+        interval.tick().await;
+
+        #[allow(clippy::panic)]
+        if let Err(e) = sqlx::query!("DELETE FROM paginator WHERE expire_date < now()")
+            .execute(&db)
+            .await
+        {
+            error!(%e, "Error deleting expired paginators");
+        }
+    }
+}
+
+fn create_components(
+    id: i32,
+    page: usize,
+    page_count: usize,
+    disabled: bool,
+) -> Vec<CreateActionRow> {
+    let last = page_count.saturating_sub(1);
+    // show a window of pages around the current one when there are too many to list them all
+    let first_option = page
+        .saturating_sub(MAX_SELECT_OPTIONS / 2)
+        .min(page_count.saturating_sub(MAX_SELECT_OPTIONS));
+    let options = (first_option..page_count)
+        .take(MAX_SELECT_OPTIONS)
+        .map(|p| {
+            CreateSelectMenuOption::new(format!("Page {}", p + 1), p.to_string())
+                .default_selection(p == page)
+        })
+        .collect();
+
+    vec![
+        CreateActionRow::Buttons(vec![
+            CreateButton::new(format!("page:first:{id}"))
+                .style(ButtonStyle::Primary)
+                .label("First")
+                .disabled(disabled || page == 0),
+            CreateButton::new(format!("page:prev:{id}"))
+                .style(ButtonStyle::Primary)
+                .label("Prev")
+                .disabled(disabled || page == 0),
+            CreateButton::new(format!("page:next:{id}"))
+                .style(ButtonStyle::Primary)
+                .label("Next")
+                .disabled(disabled || page >= last),
+            CreateButton::new(format!("page:last:{id}"))
+                .style(ButtonStyle::Primary)
+                .label("Last")
+                .disabled(disabled || page >= last),
+        ]),
+        CreateActionRow::SelectMenu(
+            CreateSelectMenu::new(
+                format!("page:jump:{id}"),
+                CreateSelectMenuKind::String { options },
+            )
+            .placeholder(format!("Page {}/{page_count}", page + 1))
+            .disabled(disabled),
+        ),
+    ]
+}
+
+async fn respond_ephemeral(ctx: &Context, interaction: &ComponentInteraction, content: &str) {
+    if let Err(e) = interaction
+        .create_response(
+            ctx,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .content(content)
+                    .ephemeral(true),
+            ),
+        )
+        .await
+    {
+        error!(error = %e, "error responding to page interaction");
+    }
+}
+
+// Updates the message a component was used on with new content and (disabled) components, then acknowledges the interaction
+// Returns the updated message so its components can be re-enabled with enable_page
+async fn show_page(
+    ctx: &Context,
+    interaction: &ComponentInteraction,
+    content: &str,
+    components: Vec<CreateActionRow>,
+) -> Option<Message> {
+    if let Some(flags) = interaction.message.flags
+        && flags.contains(MessageFlags::EPHEMERAL)
+    {
+        return None;
+    }
+    let mut message = (*interaction.message).clone();
+    if let Err(e) = message
+        .edit(
+            ctx,
+            EditMessage::new().content(content).components(components),
+        )
+        .await
+    {
+        error!(error = %e, "error updating paged message components");
+        return None;
+    }
+
+    if let Err(e) = interaction
+        .create_response(
+            ctx,
+            CreateInteractionResponse::UpdateMessage(CreateInteractionResponseMessage::new()),
+        )
+        .await
+    {
+        error!(error = %e, "error creating paged message interaction response");
+        return None;
+    }
+
+    Some(message)
+}
+
+async fn enable_page(ctx: &Context, message: &mut Message, components: Vec<CreateActionRow>) {
+    // leave buttons disabled for 2 seconds, then send the message again with buttons enabled
+    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+
+    if let Err(e) = message
+        .edit(ctx, EditMessage::new().components(components))
+        .await
+    {
+        error!(error = %e, "error updating paged message components");
+    }
+}
@@ -0,0 +1,115 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+use tracing::{error, info};
+
+// A guild's karma season that hasn't ended yet
+// Karma in the current season is summed from votes since the last season ended
+pub struct CurrentSeason {
+    pub number: i32,
+    // None if no season has ended yet, so the current season covers every vote
+    pub start_date: Option<DateTime<Utc>>,
+}
+
+// Ends the current season of guilds that have a season length, once it's been that long since the season started
+// A guild's first season starts at its first vote
+pub async fn rollover_seasons_loop(db: Pool<Postgres>) {
+    info!("starting karma season rollover");
+    let mut interval = tokio::time::interval(std::time::Duration::from_hours(1));
+
+    loop {
+        interval.tick().await;
+
+        #[allow(clippy::panic)]
+        let guild_ids = match sqlx::query!(
+            r#"
+SELECT c.guild_id
+FROM vote_config c
+WHERE c.season_days > 0
+AND COALESCE(
+  (SELECT max(end_date) FROM karma_season s WHERE s.guild_id = c.guild_id),
+  (SELECT min(create_date) FROM vote v WHERE v.guild_id = c.guild_id)
+) + c.season_days * interval '1 day' <= now()"#
+        )
+        .fetch_all(&db)
+        .await
+        {
+            Ok(g) => g,
+            Err(e) => {
+                error!(error = %e, "error getting guilds with karma seasons to end");
+                continue;
+            }
+        };
+
+        for guild in guild_ids {
+            match end_season(&db, guild.guild_id).await {
+                Ok(number) => info!(guild_id = guild.guild_id, number, "ended karma season"),
+                Err(e) => {
+                    error!(error = %e, guild_id = guild.guild_id, "error ending karma season");
+                }
+            }
+        }
+    }
+}
+
+// Archives the final standings of the guild's current season and starts the next one
+// Returns the number of the season that ended
+pub async fn end_season(db: &Pool<Postgres>, guild_id: i64) -> Result<i32, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    // the unique guild_id and number keeps a season from being ended twice at once
+    #[allow(clippy::panic)]
+    let season = sqlx::query!(
+        r#"
+INSERT INTO karma_season(guild_id, number, start_date, end_date)
+SELECT $1,
+  COALESCE(max(number), 0) + 1,
+  COALESCE(max(end_date), (SELECT min(create_date) FROM vote WHERE guild_id = $1), now()),
+  now()
+FROM karma_season
+WHERE guild_id = $1
+RETURNING id, number, start_date, end_date"#,
+        guild_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    #[allow(clippy::panic)]
+    sqlx::query!(
+        r#"
+INSERT INTO karma_season_standing(season_id, user_id, karma)
+SELECT $1, votee_id, sum(CASE WHEN is_upvote THEN 1 ELSE -1 END)::int4
+FROM vote
+WHERE guild_id = $2
+AND create_date >= $3
+AND create_date < $4
+GROUP BY votee_id"#,
+        season.id,
+        guild_id,
+        season.start_date,
+        season.end_date
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(season.number)
+}
+
+pub async fn current_season(
+    db: &Pool<Postgres>,
+    guild_id: i64,
+) -> Result<CurrentSeason, sqlx::Error> {
+    #[allow(clippy::panic)]
+    let season = sqlx::query!(
+        r#"SELECT COALESCE(max(number), 0) + 1 AS "number!", max(end_date) AS start_date FROM karma_season WHERE guild_id = $1"#,
+        guild_id
+    )
+    .fetch_one(db)
+    .await?;
+
+    Ok(CurrentSeason {
+        number: season.number,
+        start_date: season.start_date,
+    })
+}
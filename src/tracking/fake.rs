@@ -0,0 +1,80 @@
+use crate::error::CommandError;
+use crate::tracking::{
+    OUT_FOR_DELIVERY, Status, Tracking, TrackingEvent, TrackingNumber, TrackingProvider,
+};
+use chrono::{DateTime, Duration, Utc};
+use serenity::async_trait;
+use std::collections::HashMap;
+use tokio::sync::Mutex;
+
+// Tracks shipments without an external service, for running the bot locally and in tests
+// Each lookup of a tracking number moves it one step along, from label created to delivered, an hour apart from start
+// Numbers ending in 0 fail delivery at the last step instead, so every notification can be tried
+pub struct Fake {
+    start: DateTime<Utc>,
+    // how many times each number has been looked up
+    lookups: Mutex<HashMap<String, usize>>,
+}
+
+impl Fake {
+    pub fn new(start: DateTime<Utc>) -> Self {
+        Self {
+            start,
+            lookups: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl TrackingProvider for Fake {
+    async fn track(&self, tracking_number: &TrackingNumber) -> Result<Tracking, CommandError> {
+        let number = tracking_number.number();
+        let start = self.start;
+        let step = {
+            let mut lookups = self.lookups.lock().await;
+            let lookup = lookups.entry(number.clone()).or_default();
+            *lookup += 1;
+            *lookup
+        };
+
+        let last = if number.ends_with('0') {
+            (
+                Status::Failure,
+                None,
+                "Delivery attempted, no access to delivery location",
+            )
+        } else {
+            (Status::Delivered, None, "Delivered, front door")
+        };
+        let steps = [
+            (Status::PreTransit, None, "Shipping label created"),
+            (Status::Transit, None, "Departed shipping partner facility"),
+            (
+                Status::Transit,
+                Some(OUT_FOR_DELIVERY),
+                "Out for delivery today",
+            ),
+            last,
+        ];
+        // the eta goes away once the shipment reaches its last step
+        let eta = (step < steps.len()).then(|| start + Duration::days(3));
+        let events: Vec<TrackingEvent> = steps
+            .into_iter()
+            .take(step)
+            .enumerate()
+            .map(|(i, (status, substatus, details))| TrackingEvent {
+                status,
+                substatus: substatus.map(String::from),
+                details: String::from(details),
+                location: Some(String::from("Springfield, IL, US")),
+                date: start + Duration::hours(i64::try_from(i).unwrap_or_default()),
+            })
+            .collect();
+
+        Ok(Tracking {
+            eta,
+            status: events.last().cloned(),
+            events,
+        })
+    }
+}
@@ -0,0 +1,747 @@
+mod fake;
+mod shippo;
+
+pub use fake::Fake;
+pub use shippo::{Shippo, serve_webhooks};
+
+use crate::error::CommandError;
+use chrono::{DateTime, Duration, Utc};
+use serenity::async_trait;
+use serenity::http::Http;
+use serenity::model::id::ChannelId;
+use sqlx::{Pool, Postgres};
+use std::fmt;
+use std::sync::Arc;
+use tracing::{debug, error, info};
+
+// How often shipments are polled, polling is only a fallback when the provider pushes updates
+const POLL_MINUTES: u64 = 15;
+const FALLBACK_POLL_MINUTES: u64 = 60;
+// Shipments that have had a pushed update more recently than this aren't polled
+const PUSHED_FRESH_HOURS: i64 = 6;
+
+// Substatus of shipments on the truck for delivery, providers map their own codes for it to this
+pub const OUT_FOR_DELIVERY: &str = "out_for_delivery";
+
+// A service that looks up the status of tracking numbers
+// Commands and the poller only see the provider neutral Tracking, so the provider can be changed in config.toml
+#[async_trait]
+pub trait TrackingProvider: Send + Sync {
+    async fn track(&self, tracking_number: &TrackingNumber) -> Result<Tracking, CommandError>;
+}
+
+// Where a shipment is, as reported by a tracking provider
+pub struct Tracking {
+    pub eta: Option<DateTime<Utc>>,
+    // None if the provider doesn't know about the shipment yet
+    pub status: Option<TrackingEvent>,
+    // every event so far, usually including the current status
+    pub events: Vec<TrackingEvent>,
+}
+
+#[derive(Clone)]
+pub struct TrackingEvent {
+    pub status: Status,
+    // like "out_for_delivery" or "package_delayed"
+    pub substatus: Option<String>,
+    pub details: String,
+    pub location: Option<String>,
+    pub date: DateTime<Utc>,
+}
+
+pub enum TrackingNumber {
+    AustraliaPost(String),
+    CanadaPost(String),
+    DhlEcommerce(String),
+    DhlExpress(String),
+    FedEx(String),
+    LaserShip(String),
+    OnTrac(String),
+    Purolator(String),
+    RoyalMail(String),
+    Ups(String),
+    Usps(String),
+}
+
+impl TrackingNumber {
+    // Returns None if the carrier isn't supported
+    pub fn from_carrier(carrier: &str, number: String) -> Option<Self> {
+        use TrackingNumber::*;
+        match carrier {
+            "australia_post" => Some(AustraliaPost(number)),
+            "canada_post" => Some(CanadaPost(number)),
+            "dhl_ecommerce" => Some(DhlEcommerce(number)),
+            "dhl_express" => Some(DhlExpress(number)),
+            "fedex" => Some(FedEx(number)),
+            "lasership" => Some(LaserShip(number)),
+            "ontrac" => Some(OnTrac(number)),
+            "purolator" => Some(Purolator(number)),
+            "royal_mail" => Some(RoyalMail(number)),
+            "ups" => Some(Ups(number)),
+            "usps" => Some(Usps(number)),
+            _ => None,
+        }
+    }
+
+    // Tracking numbers are often copied with spaces between groups of digits, and are stored without them in uppercase
+    pub fn normalize(number: &str) -> String {
+        number
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>()
+            .to_uppercase()
+    }
+
+    // Guesses the carrier from the format of the tracking number, checking its check digit where the format has one
+    // Returns None if the format isn't recognized, Purolator numbers can't be told apart from FedEx ones so are never detected
+    pub fn detect(number: &str) -> Option<Self> {
+        use TrackingNumber::*;
+        let n = number.to_uppercase();
+        if !n.is_ascii() {
+            return None;
+        }
+        let all_digits = n.bytes().all(|b| b.is_ascii_digit());
+        let prefix_digits = |prefix: &str, len: usize| {
+            n.len() == prefix.len() + len
+                && n.starts_with(prefix)
+                && n[prefix.len()..].bytes().all(|b| b.is_ascii_digit())
+        };
+
+        if n.len() == 18 && n.starts_with("1Z") && n.bytes().all(|b| b.is_ascii_alphanumeric()) {
+            return check_ups(&n[2..]).then_some(Ups(n));
+        }
+        // UPU S10 international numbers, like EC123456785US, end with the country of the postal service
+        if n.len() == 13
+            && n[..2].bytes().all(|b| b.is_ascii_uppercase())
+            && n[2..11].bytes().all(|b| b.is_ascii_digit())
+            && n[11..].bytes().all(|b| b.is_ascii_uppercase())
+        {
+            if !check_s10(&n[2..11]) {
+                return None;
+            }
+            return match &n[11..] {
+                "AU" => Some(AustraliaPost(n)),
+                "CA" => Some(CanadaPost(n)),
+                "GB" => Some(RoyalMail(n)),
+                "US" => Some(Usps(n)),
+                _ => None,
+            };
+        }
+        if prefix_digits("C", 14) || prefix_digits("D", 14) {
+            return Some(OnTrac(n));
+        }
+        if prefix_digits("1LS", 12) || prefix_digits("LX", 8) {
+            return Some(LaserShip(n));
+        }
+        if n.starts_with("GM")
+            && (18..=24).contains(&n.len())
+            && n[2..].bytes().all(|b| b.is_ascii_digit())
+        {
+            return Some(DhlEcommerce(n));
+        }
+        if !all_digits {
+            return None;
+        }
+
+        match n.len() {
+            10 if check_mod7(&n) => Some(DhlExpress(n)),
+            12 if check_fedex(&n) => Some(FedEx(n)),
+            15 if check_mod10(&n) => Some(FedEx(n)),
+            // FedEx SmartPost numbers are checked as if they had the USPS 92 prefix
+            20 if check_mod10(&format!("92{n}")) => Some(FedEx(n)),
+            20 if check_mod10(&n) => Some(Usps(n)),
+            // FedEx Ground 96 numbers end with a 15 digit ground number
+            22 if n.starts_with("96") && check_mod10(&n[7..]) => Some(FedEx(n)),
+            22 if n.starts_with('9') && check_mod10(&n) => Some(Usps(n)),
+            // USPS labels can be scanned with 420 and a 5 or 9 digit ZIP code before the IMpb number
+            30 | 34 if n.starts_with("420") => {
+                let impb = &n[n.len() - 22..];
+                check_mod10(impb).then(|| Usps(impb.to_string()))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn carrier(&self) -> &'static str {
+        use TrackingNumber::*;
+        match self {
+            AustraliaPost(_) => "australia_post",
+            CanadaPost(_) => "canada_post",
+            DhlEcommerce(_) => "dhl_ecommerce",
+            DhlExpress(_) => "dhl_express",
+            FedEx(_) => "fedex",
+            LaserShip(_) => "lasership",
+            OnTrac(_) => "ontrac",
+            Purolator(_) => "purolator",
+            RoyalMail(_) => "royal_mail",
+            Ups(_) => "ups",
+            Usps(_) => "usps",
+        }
+    }
+
+    pub fn number(&self) -> String {
+        use TrackingNumber::*;
+        match self {
+            AustraliaPost(n) | CanadaPost(n) | DhlEcommerce(n) | DhlExpress(n) | FedEx(n)
+            | LaserShip(n) | OnTrac(n) | Purolator(n) | RoyalMail(n) | Ups(n) | Usps(n) => {
+                n.clone()
+            }
+        }
+    }
+}
+
+impl fmt::Display for TrackingNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.carrier(), self.number())
+    }
+}
+
+// The digit a check digit is calculated from, letters count as (ASCII value - 63) mod 10
+fn check_value(b: u8) -> u32 {
+    if b.is_ascii_digit() {
+        u32::from(b - b'0')
+    } else {
+        u32::from(b.saturating_sub(63)) % 10
+    }
+}
+
+// The last character is the check digit of the rest
+fn check_digit(n: &str) -> Option<(&[u8], u32)> {
+    let (last, rest) = n.as_bytes().split_last()?;
+    last.is_ascii_digit()
+        .then(|| (rest, u32::from(last - b'0')))
+}
+
+// UPS numbers after the 1Z, every other character is doubled
+fn check_ups(n: &str) -> bool {
+    check_digit(n).is_some_and(|(rest, check)| {
+        let sum: u32 = rest
+            .iter()
+            .enumerate()
+            .map(|(i, b)| check_value(*b) * if i % 2 == 1 { 2 } else { 1 })
+            .sum();
+        (10 - sum % 10) % 10 == check
+    })
+}
+
+// GS1 mod 10 used by USPS and FedEx Ground, digits are weighted 3 and 1 starting from the right
+fn check_mod10(n: &str) -> bool {
+    check_digit(n).is_some_and(|(rest, check)| {
+        let sum: u32 = rest
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(i, b)| check_value(*b) * if i % 2 == 0 { 3 } else { 1 })
+            .sum();
+        (10 - sum % 10) % 10 == check
+    })
+}
+
+// FedEx Express numbers, digits are weighted 1, 3 and 7 starting from the right
+fn check_fedex(n: &str) -> bool {
+    const WEIGHTS: [u32; 3] = [1, 3, 7];
+    check_digit(n).is_some_and(|(rest, check)| {
+        let sum: u32 = rest
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(i, b)| check_value(*b) * WEIGHTS[i % 3])
+            .sum();
+        sum % 11 % 10 == check
+    })
+}
+
+// DHL Express numbers, the check digit is the rest of the number mod 7
+fn check_mod7(n: &str) -> bool {
+    check_digit(n).is_some_and(|(rest, check)| {
+        rest.iter()
+            .fold(0, |acc, b| (acc * 10 + check_value(*b)) % 7)
+            == check
+    })
+}
+
+// The 8 digit serial number and check digit of a UPU S10 number
+fn check_s10(n: &str) -> bool {
+    const WEIGHTS: [u32; 8] = [8, 6, 4, 2, 3, 5, 9, 7];
+    check_digit(n).is_some_and(|(rest, check)| {
+        let sum: u32 = rest
+            .iter()
+            .zip(WEIGHTS)
+            .map(|(b, w)| check_value(*b) * w)
+            .sum();
+        match 11 - sum % 11 {
+            10 => check == 0,
+            11 => check == 5,
+            c => c == check,
+        }
+    })
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    Unknown,
+    PreTransit,
+    Transit,
+    Delivered,
+    Returned,
+    Failure,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        #[allow(clippy::enum_glob_use)]
+        use Status::*;
+        match self {
+            Unknown => write!(f, "unknown"),
+            PreTransit => write!(f, "pre_transit"),
+            Transit => write!(f, "transit"),
+            Delivered => write!(f, "delivered"),
+            Returned => write!(f, "returned"),
+            Failure => write!(f, "failure"),
+        }
+    }
+}
+
+// A shipment being tracked, as stored in the db
+#[derive(Debug)]
+pub struct Shipment {
+    pub id: i32,
+    pub carrier: String,
+    pub tracking_number: String,
+    pub author_id: i64,
+    pub channel_id: i64,
+    pub status: String,
+    pub substatus: Option<String>,
+    pub eta: Option<DateTime<Utc>>,
+    pub comment: Option<String>,
+}
+
+// Which status changes a user is notified about, delivery is always notified
+// Users without a shipment_notify_config row use the table's defaults, which these match
+#[allow(clippy::struct_excessive_bools)]
+struct NotifyConfig {
+    transit: bool,
+    out_for_delivery: bool,
+    eta: bool,
+    returned: bool,
+    failure: bool,
+}
+
+impl Default for NotifyConfig {
+    fn default() -> Self {
+        Self {
+            transit: true,
+            out_for_delivery: true,
+            eta: false,
+            returned: true,
+            failure: true,
+        }
+    }
+}
+
+// Polls the provider for shipments that haven't been delivered
+// If pushed is set the provider also pushes updates (see receive_tracking), so polling is slower and skips shipments with a recent push
+pub async fn poll_shipments_loop(
+    discord_http: Arc<Http>,
+    db: Pool<Postgres>,
+    provider: Arc<dyn TrackingProvider>,
+    pushed: bool,
+) {
+    info!(pushed, "starting shipment poller");
+    let mut interval = tokio::time::interval(std::time::Duration::from_mins(if pushed {
+        FALLBACK_POLL_MINUTES
+    } else {
+        POLL_MINUTES
+    }));
+
+    loop {
+        interval.tick().await;
+
+        let pushed_cutoff = Utc::now() - Duration::hours(PUSHED_FRESH_HOURS);
+        #[allow(clippy::panic)]
+        let shipments = match sqlx::query_as!(
+            Shipment,
+            r#"SELECT id, carrier::text AS "carrier!", tracking_number, author_id, channel_id, status::text AS "status!", substatus, eta, comment FROM shipment WHERE status = ANY('{transit, pre_transit, unknown}') AND (webhook_date IS NULL OR webhook_date < $1)"#,
+            pushed_cutoff
+        )
+        .fetch_all(&db)
+        .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                error!(error = %e, "error getting shipments from db");
+                continue;
+            }
+        };
+        debug!(shipments = shipments.len(), "polling for shipments");
+        for shipment in shipments {
+            let Some(tracking_number) =
+                TrackingNumber::from_carrier(&shipment.carrier, shipment.tracking_number.clone())
+            else {
+                error!(
+                    carrier = shipment.carrier,
+                    "unrecognized carrier in shipment polling"
+                );
+                continue;
+            };
+            let tracking = match provider.track(&tracking_number).await {
+                Ok(t) => t,
+                Err(e) => {
+                    error!(error = %e, %tracking_number, "error polling shipment");
+                    continue;
+                }
+            };
+            if let Err(e) = update_shipment(&discord_http, &db, &shipment, tracking).await {
+                error!(error = %e, %tracking_number, "error updating polled shipment");
+            }
+        }
+    }
+}
+
+// Updates a shipment from tracking pushed by the provider, rather than polled
+// Returns false if nobody is tracking the shipment
+pub async fn receive_tracking(
+    http: &Http,
+    db: &Pool<Postgres>,
+    tracking_number: &TrackingNumber,
+    tracking: Tracking,
+) -> Result<bool, CommandError> {
+    #[allow(clippy::panic)]
+    let shipment = sqlx::query_as!(
+        Shipment,
+        r#"UPDATE shipment SET webhook_date = now() WHERE carrier = $1::shipment_carrier AND tracking_number = $2 RETURNING id, carrier::text AS "carrier!", tracking_number, author_id, channel_id, status::text AS "status!", substatus, eta, comment"#,
+        tracking_number.carrier() as _,
+        tracking_number.number()
+    )
+    .fetch_optional(db)
+    .await?;
+    let Some(shipment) = shipment else {
+        return Ok(false);
+    };
+
+    update_shipment(http, db, &shipment, tracking).await?;
+    Ok(true)
+}
+
+// Saves a shipment's latest tracking, records its tracking events, and notifies its author of status changes
+pub async fn update_shipment(
+    http: &Http,
+    db: &Pool<Postgres>,
+    shipment: &Shipment,
+    tracking: Tracking,
+) -> Result<(), CommandError> {
+    // the history usually includes the current status, events already recorded are skipped
+    for event in tracking.events.iter().chain(tracking.status.iter()) {
+        #[allow(clippy::panic)]
+        sqlx::query!(
+            "INSERT INTO shipment_event(shipment_id, status, substatus, details, location, event_date) VALUES ($1, $2::shipment_tracking_status, $3, $4, $5, $6)
+             ON CONFLICT ON CONSTRAINT shipment_event_shipment_id_event_date_status_key DO NOTHING",
+            shipment.id,
+            event.status.to_string() as _,
+            event.substatus,
+            event.details,
+            event.location,
+            event.date
+        )
+        .execute(db)
+        .await?;
+    }
+
+    let Some(status) = tracking.status else {
+        return Ok(());
+    };
+    let new_status = status.status.to_string();
+    #[allow(clippy::panic)]
+    sqlx::query!(
+        "UPDATE shipment SET status = $2::shipment_tracking_status, substatus = $3, eta = $4 WHERE id = $1",
+        shipment.id,
+        new_status.clone() as _,
+        status.substatus,
+        tracking.eta
+    )
+    .execute(db)
+    .await?;
+
+    #[allow(clippy::panic)]
+    let config = sqlx::query_as!(
+        NotifyConfig,
+        "SELECT transit, out_for_delivery, eta, returned, failure FROM shipment_notify_config WHERE user_id = $1",
+        shipment.author_id
+    )
+    .fetch_optional(db)
+    .await?
+    .unwrap_or_default();
+
+    let name = match &shipment.comment {
+        Some(c) => format!(
+            "{} shipment {} ({c})",
+            shipment.carrier, shipment.tracking_number
+        ),
+        None => format!("{} shipment {}", shipment.carrier, shipment.tracking_number),
+    };
+    let content = notification(shipment, &status, tracking.eta, &config);
+
+    if let Some(content) = content {
+        let channel_id = ChannelId::new(u64::try_from(shipment.channel_id)?);
+        channel_id
+            .say(
+                http,
+                format!("<@{}>: Your {name} {content}", shipment.author_id),
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
+// What to tell a shipment's author about its latest status, None if the change isn't one they're notified about
+// shipment is the shipment as it was before status and eta were saved
+fn notification(
+    shipment: &Shipment,
+    status: &TrackingEvent,
+    eta: Option<DateTime<Utc>>,
+    config: &NotifyConfig,
+) -> Option<String> {
+    let status_changed = status.status.to_string() != shipment.status;
+    match status.status {
+        Status::Delivered if status_changed => Some(format!(
+            "was marked as delivered at {} with the following message: {}",
+            status.date, status.details
+        )),
+        Status::Returned if status_changed && config.returned => Some(format!(
+            "is being returned to the sender: {}",
+            status.details
+        )),
+        Status::Failure if status_changed && config.failure => {
+            Some(format!("couldn't be delivered: {}", status.details))
+        }
+        Status::Transit
+            if status.substatus.as_deref() == Some(OUT_FOR_DELIVERY)
+                && shipment.substatus.as_deref() != Some(OUT_FOR_DELIVERY)
+                && config.out_for_delivery =>
+        {
+            Some(format!("is out for delivery: {}", status.details))
+        }
+        Status::Transit if status_changed && config.transit => {
+            Some(format!("is in transit: {}", status.details))
+        }
+        Status::PreTransit | Status::Transit
+            if config.eta
+                && shipment.eta.is_some()
+                && eta.map(|e| e.date_naive()) != shipment.eta.map(|e| e.date_naive()) =>
+        {
+            Some(match eta {
+                Some(eta) => format!("is now expected {}", eta.format("%A, %b %d")),
+                None => String::from("no longer has an expected delivery date"),
+            })
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    // Returns the carrier and number detect finds, for comparing in asserts
+    fn detect(number: &str) -> Option<(&'static str, String)> {
+        TrackingNumber::detect(&TrackingNumber::normalize(number))
+            .map(|t| (t.carrier(), t.number()))
+    }
+
+    // Looks number up with a Fake provider until it stops changing, saving each status like update_shipment does
+    // Returns the notification sent for each lookup
+    #[allow(clippy::unwrap_used)]
+    fn fake_notifications(number: &str, config: &NotifyConfig) -> Vec<Option<String>> {
+        let start = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        let fake = Fake::new(start);
+        let tracking_number = TrackingNumber::Usps(String::from(number));
+        let mut shipment = Shipment {
+            id: 1,
+            carrier: String::from("usps"),
+            tracking_number: String::from(number),
+            author_id: 1,
+            channel_id: 1,
+            status: String::from("unknown"),
+            substatus: None,
+            eta: None,
+            comment: None,
+        };
+        let mut notifications = vec![];
+        for _ in 0..4 {
+            let tracking = futures::executor::block_on(fake.track(&tracking_number)).unwrap();
+            let status = tracking.status.unwrap();
+            notifications.push(notification(&shipment, &status, tracking.eta, config));
+            shipment.status = status.status.to_string();
+            shipment.substatus = status.substatus;
+            shipment.eta = tracking.eta;
+        }
+        notifications
+    }
+
+    #[test]
+    fn fake_notifies_each_transition() {
+        assert_eq!(
+            fake_notifications("9400111201080805483016", &NotifyConfig::default()),
+            vec![
+                None,
+                Some(String::from(
+                    "is in transit: Departed shipping partner facility"
+                )),
+                Some(String::from("is out for delivery: Out for delivery today")),
+                Some(String::from(
+                    "was marked as delivered at 2026-01-01 03:00:00 UTC with the following message: Delivered, front door"
+                )),
+            ]
+        );
+    }
+
+    #[test]
+    fn fake_fails_numbers_ending_in_zero() {
+        let notifications = fake_notifications("9400111201080805483010", &NotifyConfig::default());
+        assert_eq!(
+            notifications.last(),
+            Some(&Some(String::from(
+                "couldn't be delivered: Delivery attempted, no access to delivery location"
+            )))
+        );
+    }
+
+    #[test]
+    fn disabled_notifications_are_skipped() {
+        let config = NotifyConfig {
+            transit: false,
+            out_for_delivery: false,
+            eta: false,
+            returned: false,
+            failure: false,
+        };
+        // deliveries are always notified
+        assert_eq!(
+            fake_notifications("9400111201080805483016", &config)
+                .iter()
+                .filter(|n| n.is_some())
+                .count(),
+            1
+        );
+        assert!(
+            fake_notifications("9400111201080805483010", &config)
+                .iter()
+                .all(Option::is_none)
+        );
+    }
+
+    #[test]
+    fn detects_ups() {
+        assert_eq!(
+            detect("1Z5R89390357567127"),
+            Some(("ups", String::from("1Z5R89390357567127")))
+        );
+        assert_eq!(
+            detect("1z879e930346834440"),
+            Some(("ups", String::from("1Z879E930346834440")))
+        );
+        assert_eq!(detect("1Z5R89390357567128"), None);
+    }
+
+    #[test]
+    fn detects_usps() {
+        assert_eq!(
+            detect("9400 1112 0108 0805 4830 16"),
+            Some(("usps", String::from("9400111201080805483016")))
+        );
+        assert_eq!(
+            detect("03071790000523483741"),
+            Some(("usps", String::from("03071790000523483741")))
+        );
+        // 420 and a ZIP code before the IMpb number
+        assert_eq!(
+            detect("420123459400111201080805483016"),
+            Some(("usps", String::from("9400111201080805483016")))
+        );
+        assert_eq!(
+            detect("4201234567899400111201080805483016"),
+            Some(("usps", String::from("9400111201080805483016")))
+        );
+        assert_eq!(detect("9400111201080805483017"), None);
+    }
+
+    #[test]
+    fn detects_fedex() {
+        assert_eq!(
+            detect("986578788855"),
+            Some(("fedex", String::from("986578788855")))
+        );
+        assert_eq!(
+            detect("449044304137821"),
+            Some(("fedex", String::from("449044304137821")))
+        );
+        // SmartPost
+        assert_eq!(
+            detect("61299998820821171811"),
+            Some(("fedex", String::from("61299998820821171811")))
+        );
+        // Ground 96
+        assert_eq!(
+            detect("9611020987654312345672"),
+            Some(("fedex", String::from("9611020987654312345672")))
+        );
+        assert_eq!(detect("986578788856"), None);
+    }
+
+    #[test]
+    fn detects_dhl_express() {
+        assert_eq!(
+            detect("3318810025"),
+            Some(("dhl_express", String::from("3318810025")))
+        );
+        assert_eq!(detect("3318810026"), None);
+    }
+
+    #[test]
+    fn detects_s10_by_country() {
+        assert_eq!(
+            detect("EC123456785US"),
+            Some(("usps", String::from("EC123456785US")))
+        );
+        assert_eq!(
+            detect("RB123456785CA"),
+            Some(("canada_post", String::from("RB123456785CA")))
+        );
+        assert_eq!(
+            detect("RB123456785GB"),
+            Some(("royal_mail", String::from("RB123456785GB")))
+        );
+        assert_eq!(
+            detect("RB123456785AU"),
+            Some(("australia_post", String::from("RB123456785AU")))
+        );
+        assert_eq!(detect("EC123456786US"), None);
+        assert_eq!(detect("EC123456785DE"), None);
+    }
+
+    #[test]
+    fn detects_by_prefix() {
+        assert_eq!(
+            detect("C11031500001879"),
+            Some(("ontrac", String::from("C11031500001879")))
+        );
+        assert_eq!(
+            detect("1LS123456789012"),
+            Some(("lasership", String::from("1LS123456789012")))
+        );
+        assert_eq!(
+            detect("GM2951173225174494"),
+            Some(("dhl_ecommerce", String::from("GM2951173225174494")))
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_formats() {
+        assert_eq!(detect(""), None);
+        assert_eq!(detect("hello"), None);
+        assert_eq!(detect("12345"), None);
+        assert_eq!(detect("\u{e9}C123456785US"), None);
+    }
+}
@@ -0,0 +1,236 @@
+use crate::error::CommandError;
+use crate::tracking::{self, Status, Tracking, TrackingEvent, TrackingNumber, TrackingProvider};
+use axum::Router;
+use axum::extract::{Json, Query, State};
+use axum::http::StatusCode;
+use axum::routing::post;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serenity::async_trait;
+use serenity::http::Http;
+use sqlx::{Pool, Postgres};
+use std::fmt;
+use std::sync::Arc;
+use tracing::{debug, error, info};
+
+// Tracks shipments with the Shippo API, Shippo's carrier tokens are the same as the shipment_carrier enum
+pub struct Shippo {
+    api_key: String,
+}
+
+impl Shippo {
+    pub fn new(api_key: String) -> Self {
+        Self { api_key }
+    }
+}
+
+#[async_trait]
+impl TrackingProvider for Shippo {
+    async fn track(&self, tracking_number: &TrackingNumber) -> Result<Tracking, CommandError> {
+        let client = reqwest::Client::new();
+
+        let response = client
+            .post("https://api.goshippo.com/tracks/")
+            .header("Authorization", format!("ShippoToken {}", self.api_key))
+            .form(&[
+                ("carrier", tracking_number.carrier()),
+                ("tracking_number", &tracking_number.number()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<TrackingResponse>()
+            .await?;
+
+        Ok(response.into())
+    }
+}
+
+struct WebhookState {
+    http: Arc<Http>,
+    db: Pool<Postgres>,
+    token: String,
+}
+
+#[derive(Deserialize)]
+struct WebhookQuery {
+    token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct WebhookBody {
+    event: String,
+    data: WebhookTrack,
+}
+
+// The same track object as a tracking request returns, along with which shipment it's for
+#[derive(Deserialize)]
+struct WebhookTrack {
+    carrier: String,
+    tracking_number: String,
+    #[serde(flatten)]
+    track: TrackingResponse,
+}
+
+// Receives Shippo's track_updated webhooks and updates the shipments they're for
+// Shippo sends them for every number that's been looked up with the tracks endpoint, which Shippo::track uses
+// Returns if the server fails, shipments are still polled so the bot keeps running without it
+pub async fn serve_webhooks(listen: String, token: String, http: Arc<Http>, db: Pool<Postgres>) {
+    let app = Router::new()
+        .route("/shippo", post(webhook))
+        .with_state(Arc::new(WebhookState { http, db, token }));
+    let listener = match tokio::net::TcpListener::bind(&listen).await {
+        Ok(l) => l,
+        Err(e) => {
+            error!(error = %e, listen, "unable to listen for shippo webhooks");
+            return;
+        }
+    };
+    info!(listen, "starting shippo webhook server");
+    if let Err(e) = axum::serve(listener, app).await {
+        error!(error = %e, "error running shippo webhook server");
+    }
+}
+
+async fn webhook(
+    State(state): State<Arc<WebhookState>>,
+    Query(query): Query<WebhookQuery>,
+    Json(body): Json<WebhookBody>,
+) -> StatusCode {
+    if !query.token.is_some_and(|t| token_matches(&t, &state.token)) {
+        return StatusCode::UNAUTHORIZED;
+    }
+    if body.event != "track_updated" {
+        return StatusCode::OK;
+    }
+    let Some(tracking_number) =
+        TrackingNumber::from_carrier(&body.data.carrier, body.data.tracking_number)
+    else {
+        debug!(
+            carrier = body.data.carrier,
+            "webhook for unsupported carrier"
+        );
+        return StatusCode::OK;
+    };
+
+    match tracking::receive_tracking(
+        &state.http,
+        &state.db,
+        &tracking_number,
+        body.data.track.into(),
+    )
+    .await
+    {
+        Ok(true) => StatusCode::OK,
+        Ok(false) => {
+            debug!(%tracking_number, "webhook for untracked shipment");
+            StatusCode::OK
+        }
+        Err(e) => {
+            error!(error = %e, %tracking_number, "error updating shipment from webhook");
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+// Compares every byte so how long it takes doesn't give away how much of the token was right
+// An empty token never matches, main refuses to start the server with one
+fn token_matches(given: &str, token: &str) -> bool {
+    !token.is_empty()
+        && given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+#[derive(Debug, Deserialize)]
+struct TrackingResponse {
+    eta: Option<DateTime<Utc>>,
+    tracking_status: Option<TrackingStatus>,
+    #[serde(default)]
+    tracking_history: Vec<TrackingStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrackingStatus {
+    status: ShippoStatus,
+    status_details: String,
+    status_date: DateTime<Utc>,
+    substatus: Option<Substatus>,
+    location: Option<Location>,
+}
+
+impl From<TrackingResponse> for Tracking {
+    fn from(response: TrackingResponse) -> Self {
+        Self {
+            eta: response.eta,
+            status: response.tracking_status.map(TrackingEvent::from),
+            events: response
+                .tracking_history
+                .into_iter()
+                .map(TrackingEvent::from)
+                .collect(),
+        }
+    }
+}
+
+impl From<TrackingStatus> for TrackingEvent {
+    fn from(status: TrackingStatus) -> Self {
+        Self {
+            status: status.status.into(),
+            substatus: status.substatus.map(|s| s.code),
+            details: status.status_details,
+            location: status.location.map(|l| l.to_string()),
+            date: status.status_date,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum ShippoStatus {
+    Unknown,
+    PreTransit,
+    Transit,
+    Delivered,
+    Returned,
+    Failure,
+}
+
+impl From<ShippoStatus> for Status {
+    fn from(status: ShippoStatus) -> Self {
+        match status {
+            ShippoStatus::Unknown => Status::Unknown,
+            ShippoStatus::PreTransit => Status::PreTransit,
+            ShippoStatus::Transit => Status::Transit,
+            ShippoStatus::Delivered => Status::Delivered,
+            ShippoStatus::Returned => Status::Returned,
+            ShippoStatus::Failure => Status::Failure,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Substatus {
+    // like "out_for_delivery" or "package_delayed", matching the substatus codes of TrackingEvent
+    code: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Location {
+    city: Option<String>,
+    state: Option<String>,
+    country: Option<String>,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<&str> = [&self.city, &self.state, &self.country]
+            .into_iter()
+            .filter_map(|p| p.as_deref().filter(|p| !p.is_empty()))
+            .collect();
+        write!(f, "{}", parts.join(", "))
+    }
+}
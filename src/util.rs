@@ -1,5 +1,8 @@
 use crate::error::CommandError;
 use crate::model::LastUserPresence;
+use chrono::{Duration, prelude::*};
+use regex::{Match, Regex};
+use serenity::all::{CommandDataOption, CommandDataOptionValue, CommandInteraction, Permissions};
 use serenity::client::Context;
 use serenity::http::Http;
 use serenity::model::{
@@ -8,7 +11,22 @@ use serenity::model::{
     user::OnlineStatus,
 };
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::str::FromStr;
+use std::sync::{Arc, LazyLock};
+
+#[allow(clippy::unwrap_used)]
+static DURATION_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)(?:(?:(?:(\d+)\s+years?)|(?:(\d+)\s+months?)|(?:(\d+)\s+weeks?)|(?:(\d+)\s+days?)|(?:(\d+)\s+hours?)|(?:(\d+)\s+minutes?)|(?:(\d+)\s+seconds?))\s?)+",
+    )
+    .unwrap()
+});
+
+// Matches a unit without a count, such as "last week" or "a month", which is treated as a count of one
+#[allow(clippy::unwrap_used)]
+static SINGLE_UNIT_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(?:a|an|last|past)\s+(year|month|week|day|hour|minute|second)\b").unwrap()
+});
 
 // This feels a little clunky (as its also combined with get_username below)
 // However in testing it seems faster than not mapping and instead hitting guild.member(&ctx) (falling back to http.get_user) for each member
@@ -75,3 +93,98 @@ pub async fn get_user_status(
     }
     None
 }
+
+// Returns the value of the string option with the given name, if it was given
+pub fn get_string_option<'a>(options: &'a [CommandDataOption], name: &str) -> Option<&'a str> {
+    options.iter().find(|o| o.name == name).and_then(|o| {
+        if let CommandDataOptionValue::String(s) = &o.value {
+            Some(s.as_str())
+        } else {
+            None
+        }
+    })
+}
+
+// Returns true if the user running the command can manage the guild it was run in
+pub fn is_guild_admin(interaction: &CommandInteraction) -> bool {
+    interaction
+        .member
+        .as_ref()
+        .and_then(|m| m.permissions)
+        .is_some_and(Permissions::manage_guild)
+}
+
+// Parses a human readable time duration (2 days, 1 hour, 3 months, last 30 days, past week, etc) and returns the date that far before now
+// Returns None if no duration could be found in arg
+pub fn start_date_from_duration(
+    arg: &str,
+    now: DateTime<Utc>,
+) -> Result<Option<DateTime<Utc>>, CommandError> {
+    let arg = SINGLE_UNIT_REGEX.replace_all(arg, "1 $1");
+    let Some(captures) = DURATION_REGEX.captures(&arg) else {
+        return Ok(None);
+    };
+    let years = get_digit_from_match(captures.get(1))?;
+    let months = get_digit_from_match(captures.get(2))?;
+    let weeks = get_digit_from_match(captures.get(3))?;
+    let days = get_digit_from_match(captures.get(4))?;
+    let hours = get_digit_from_match(captures.get(5))?;
+    let minutes = get_digit_from_match(captures.get(6))?;
+    let seconds = get_digit_from_match(captures.get(7))?;
+    let Some(month_days) = months_to_days(now, months) else {
+        return Err("months overflow".into());
+    };
+    Ok(Some(
+        now - Duration::try_days(years * 365)
+            .ok_or_else(|| CommandError::from("invalid number of years"))?
+            - Duration::try_days(month_days)
+                .ok_or_else(|| CommandError::from("invalid number of months"))?
+            - Duration::try_days(weeks * 7)
+                .ok_or_else(|| CommandError::from("invalid number of weeks"))?
+            - Duration::try_days(days)
+                .ok_or_else(|| CommandError::from("invalid number of days"))?
+            - Duration::try_hours(hours)
+                .ok_or_else(|| CommandError::from("invalid number of hours"))?
+            - Duration::try_minutes(minutes)
+                .ok_or_else(|| CommandError::from("invalid number of minutes"))?
+            - Duration::try_seconds(seconds)
+                .ok_or_else(|| CommandError::from("invalid number of seconds"))?,
+    ))
+}
+
+fn get_digit_from_match(mat: Option<Match>) -> Result<i64, std::num::ParseIntError> {
+    match mat {
+        None => Ok(0),
+        Some(mat) => i64::from_str(mat.as_str()),
+    }
+}
+
+// takes months and turns it to days by counting the days of each month, supports + or - months
+fn months_to_days(now: DateTime<Utc>, mut months: i64) -> Option<i64> {
+    let mut end = now;
+    loop {
+        if months == 0 {
+            break Some((end - now).num_days());
+        }
+        end = end.checked_add_signed(Duration::try_days(
+            NaiveDate::from_ymd_opt(
+                match end.month() {
+                    12 => end.year() + 1,
+                    _ => end.year(),
+                },
+                match end.month() {
+                    12 => 1,
+                    _ => end.month() + 1,
+                },
+                1,
+            )?
+            .signed_duration_since(NaiveDate::from_ymd_opt(end.year(), end.month(), 1)?)
+            .num_days(),
+        )?)?;
+        if months > 0 {
+            months -= 1;
+        } else {
+            months += 1;
+        }
+    }
+}